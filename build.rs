@@ -0,0 +1,532 @@
+//! Generates the register map from `l9961_regmap.csv`.
+//!
+//! For every register in the CSV, `regmap.rs` in `OUT_DIR` receives:
+//! - a module with the address, reset value, access type, writable mask and field layout of the register
+//! - a [`Register`] implementation for the hand-written register type, or a generated register type with
+//!   field accessors, `Debug` and `defmt::Format` for registers which do not have one
+//! - an entry in the `REGISTERS` table used to decode and format raw register values
+
+use std::{env, fmt::Write, fs, path::Path};
+
+const REGMAP: &str = "l9961_regmap.csv";
+
+/// How a register of the CSV is represented in the crate
+enum Kind {
+    /// A hand-written newtype implementing `From<u16>` and `Deref<Target = u16>`
+    Newtype(&'static str),
+    /// A hand-written bitflags type
+    Flags(&'static str),
+    /// A register type generated from the CSV
+    Generated,
+}
+
+/// The `Registers` variant and representation of every register in the CSV
+#[rustfmt::skip]
+const REGISTERS: [(&str, &str, Kind); 48] = [
+    ("CHIPID", "ChipID", Kind::Newtype("ChipID")),
+    ("CFG3_ACT", "Cfg3Act", Kind::Newtype("Cfg3Act")),
+    ("CFG1_FILTERS_CYCLES", "Cfg1FiltersCycles", Kind::Newtype("Cfg1FiltersCycles")),
+    ("DEV_ADDR", "DevAddr", Kind::Newtype("DevAddr")),
+    ("CFG2_ENABLES", "Cfg2Enables", Kind::Newtype("Cfg2Enables")),
+    ("CSA_GAIN_FACTOR", "CsaGainFactor", Kind::Newtype("CsaGainFactor")),
+    ("VCELL_OV_TH", "VCellOvTh", Kind::Newtype("VCellOvTh")),
+    ("VCELL_UV_TH", "VCellUvTh", Kind::Newtype("VCellUvTh")),
+    ("VCELL_SEVERE_DELTA_THRS", "VCellSevereDeltaThrs", Kind::Newtype("VCellSevereDeltaThrs")),
+    ("VCELL_BAL_UV_DELTA_TH", "VCellBalUvDeltaTh", Kind::Newtype("VCellBalUvDeltaTh")),
+    ("VB_OV_TH", "VBOvTh", Kind::Newtype("VBOvTh")),
+    ("VB_UV_TH", "VBUvTh", Kind::Newtype("VBUvTh")),
+    ("VB_SUM_MAX_DIFF_TH", "VBSumMaxDiffTh", Kind::Newtype("VBSumMaxDiffTh")),
+    ("VNTC_OT_TH", "VNTCOTTh", Kind::Newtype("VNTCOTTh")),
+    ("VNTC_UT_TH", "VNTCUTTh", Kind::Newtype("VNTCUTTh")),
+    ("VNTC_SEVERE_OT_DELTA_TH", "VNTCSevereOTTh", Kind::Newtype("VNTCSevereOTTh")),
+    ("OVC_THRESHOLDS", "OvCThresholds", Kind::Newtype("OvCThresholds")),
+    ("PERSISTENT_OVC_THRESHOLDS", "PersistentOvCThresholds", Kind::Newtype("PersistentOvCThreshold")),
+    ("SC_THRESHOLD", "SCThreshold", Kind::Newtype("SCThreshold")),
+    ("TO_PRDRV_BAL_MSK", "ToPrdrvBalMask", Kind::Flags("ToPrdrvBalMask")),
+    ("TO_FUSE_RST_MSK", "ToFuseRstMask", Kind::Flags("ToFuseRstMask")),
+    ("TO_FAULTN_MSK", "ToFaultnMsk", Kind::Flags("ToFaultnMsk")),
+    ("CURR_MSK", "CurrMsk", Kind::Flags("CurrMsk")),
+    ("MANUFACTURER_NAME_MSB", "ManufacturerNameMsb", Kind::Generated),
+    ("MANUFACTURER_NAME_LSB", "ManufacturerNameLsb", Kind::Generated),
+    ("MANUFACTURING_DATE", "ManufacturingDate", Kind::Generated),
+    ("FIRST_USAGE_DATE", "FirstUsageDate", Kind::Generated),
+    ("SERIAL_NUMBER_MSB", "SerialNumberMsb", Kind::Generated),
+    ("SERIAL_NUMBER_LSB", "SerialNumberLsb", Kind::Generated),
+    ("DEVICE_NAME_MSB", "DeviceNameMsb", Kind::Generated),
+    ("DEVICE_NAME_LSB", "DeviceNameLsb", Kind::Generated),
+    ("NVM_1", "Nvm1", Kind::Generated),
+    ("NVM_2", "Nvm2", Kind::Generated),
+    ("VCELL1", "VCell1", Kind::Newtype("VCell1Faults")),
+    ("VCELL2", "VCell2", Kind::Generated),
+    ("VCELL3", "VCell3", Kind::Generated),
+    ("VCELL4", "VCell4", Kind::Generated),
+    ("VCELL5", "VCell5", Kind::Generated),
+    ("VCELLSUM", "VCellSum", Kind::Newtype("VCellSum")),
+    ("VB", "VB", Kind::Newtype("VB")),
+    ("NTC_GPIO", "NtcGpio", Kind::Newtype("NtcGpio")),
+    ("DIE_TEMP", "DieTemp", Kind::Newtype("DieTemp")),
+    ("DIAG_OV_OT_UT", "DiagOvOtUt", Kind::Flags("DiagOvOtUt")),
+    ("DIAG_UV", "DiagUv", Kind::Flags("DiagUv")),
+    ("CC_INST_MEAS", "CCInstMeas", Kind::Generated),
+    ("CC_ACC_MSB", "CCAccMsb", Kind::Generated),
+    ("CC_ACC_LSB_CNTR", "CCAccLsbCntr", Kind::Newtype("CCAccLsbCntr")),
+    ("DIAG_CURR", "DiagCurr", Kind::Flags("DiagCurr")),
+];
+
+struct Field {
+    name: String,
+    access: &'static str,
+    offset: u32,
+    width: u32,
+}
+
+impl Field {
+    fn mask(&self) -> u16 {
+        (((1u32 << self.width) - 1) << self.offset) as u16
+    }
+}
+
+struct Register {
+    name: String,
+    address: u8,
+    reset_value: u16,
+    fields: Vec<Field>,
+}
+
+impl Register {
+    fn module(&self) -> String {
+        self.name.to_lowercase()
+    }
+
+    fn writable_mask(&self) -> u16 {
+        self.fields
+            .iter()
+            .filter(|field| field.access != "ReadOnly")
+            .fold(0, |mask, field| mask | field.mask())
+    }
+
+    /// Access type of the register as a whole:
+    /// read only or write only if every field is, latched if any field is, read-write otherwise
+    fn access(&self) -> &'static str {
+        let all = |access| self.fields.iter().all(|field| field.access == access);
+        if all("ReadOnly") {
+            "ReadOnly"
+        } else if all("WriteOnly") {
+            "WriteOnly"
+        } else if self
+            .fields
+            .iter()
+            .any(|field| field.access == "ReadLatchedWrite")
+        {
+            "ReadLatchedWrite"
+        } else {
+            "ReadWrite"
+        }
+    }
+
+    /// Name of the accessors for a field of a generated register type.
+    /// Registers consisting of a single 16 bit field name the accessors after the register.
+    fn accessor(&self, field: &Field) -> String {
+        match (self.fields.len(), field.width) {
+            (1, 16) => self.module(),
+            _ => field.name.to_lowercase(),
+        }
+    }
+}
+
+fn access(kind: &str) -> &'static str {
+    match kind {
+        "RO" => "ReadOnly",
+        "RW" => "ReadWrite",
+        "RLW" => "ReadLatchedWrite",
+        "WO" => "WriteOnly",
+        _ => panic!("Unknown access type {kind}"),
+    }
+}
+
+fn parse(csv: &str) -> Vec<Register> {
+    let mut registers: Vec<Register> = Vec::new();
+    for line in csv
+        .lines()
+        .skip_while(|line| !line.starts_with("Register Name"))
+        .skip(1)
+    {
+        let columns: Vec<&str> = line.split(',').collect();
+        if !columns[0].is_empty() {
+            let address = u8::from_str_radix(columns[1].trim_start_matches("0x"), 16).unwrap();
+            registers.push(Register {
+                name: columns[0].to_string(),
+                address,
+                reset_value: 0,
+                fields: Vec::new(),
+            });
+            continue;
+        }
+        let register = registers.last_mut().unwrap();
+        let offset: u32 = columns[4].parse().unwrap();
+        let width: u32 = columns[5].parse().unwrap();
+        // Unknown reset values (e.g. the chip ID) are treated as 0
+        let reset = u16::from_str_radix(columns[6].trim_start_matches("0x"), 16).unwrap_or(0);
+        register.reset_value |= reset << offset;
+        if columns[2].starts_with("UNUSED") {
+            continue;
+        }
+        let field = Field {
+            name: columns[2].to_string(),
+            access: access(columns[3]),
+            offset,
+            width,
+        };
+        register.fields.push(field);
+    }
+    for register in &mut registers {
+        merge_bit_fields(&mut register.fields);
+    }
+    // The CSV swaps the field names of the two manufacturer name registers,
+    // so a field spanning a whole register is named after the register it is in
+    let names: Vec<String> = registers
+        .iter()
+        .map(|register| register.name.clone())
+        .collect();
+    for register in &mut registers {
+        if let [field] = register.fields.as_mut_slice()
+            && field.width == 16
+            && names.contains(&field.name)
+        {
+            field.name = register.name.clone();
+        }
+    }
+    registers
+}
+
+/// Merge multi-bit values listed bit by bit as `NAME_n` .. `NAME_0` into a single `NAME` field.
+/// Runs which do not end at `_0` (e.g. `VCELL_EN_5` .. `VCELL_EN_1`) are independent fields.
+fn merge_bit_fields(fields: &mut Vec<Field>) {
+    let mut index = 0;
+    while index < fields.len() {
+        let Some(prefix) = fields[index].name.strip_suffix("_0").map(str::to_string) else {
+            index += 1;
+            continue;
+        };
+        // Walk back over NAME_1, NAME_2, .. in the adjacent higher bits
+        let mut first = index;
+        while first > 0 {
+            let (previous, next) = (&fields[first - 1], &fields[first]);
+            let expected = format!("{prefix}_{}", index - first + 1);
+            if previous.name != expected
+                || previous.access != next.access
+                || previous.offset != next.offset + 1
+            {
+                break;
+            }
+            first -= 1;
+        }
+        if first < index {
+            let width = (first..=index).map(|i| fields[i].width).sum();
+            let merged = Field {
+                name: prefix,
+                access: fields[index].access,
+                offset: fields[index].offset,
+                width,
+            };
+            fields.splice(first..=index, [merged]);
+        }
+        index = first + 1;
+    }
+}
+
+fn generate(registers: &[Register]) -> String {
+    let mut out = String::new();
+    let o = &mut out;
+    writeln!(o, "// Generated by build.rs from {REGMAP}, do not edit.\n").unwrap();
+
+    for (register, (csv_name, variant, kind)) in registers.iter().zip(REGISTERS.iter()) {
+        assert_eq!(&register.name, csv_name, "Register map order changed");
+        let module = register.module();
+        let name = &register.name;
+        let access = register.access();
+        writeln!(o, "/// Layout of the {name} register").unwrap();
+        writeln!(o, "pub mod {module} {{").unwrap();
+        writeln!(
+            o,
+            "    use super::{{Access, Field, FieldInfo, Registers}};\n"
+        )
+        .unwrap();
+        writeln!(o, "    /// Address of the {name} register").unwrap();
+        writeln!(
+            o,
+            "    pub const ADDRESS: Registers = Registers::{variant};"
+        )
+        .unwrap();
+        writeln!(
+            o,
+            "    /// Value of the {name} register after power-on reset"
+        )
+        .unwrap();
+        writeln!(
+            o,
+            "    pub const RESET_VALUE: u16 = {:#06X};",
+            register.reset_value
+        )
+        .unwrap();
+        writeln!(
+            o,
+            "    /// Bits of the {name} register which hold writable fields"
+        )
+        .unwrap();
+        writeln!(
+            o,
+            "    pub const WRITABLE_MASK: u16 = {:#06X};",
+            register.writable_mask()
+        )
+        .unwrap();
+        writeln!(o, "    /// Access type of the {name} register").unwrap();
+        writeln!(o, "    pub const ACCESS: Access = Access::{access};").unwrap();
+        writeln!(o, "    /// Type level access marker of the {name} register").unwrap();
+        writeln!(o, "    pub type AccessType = super::super::{access};").unwrap();
+        for field in &register.fields {
+            writeln!(
+                o,
+                "    /// {} field, bits {}..={}",
+                field.name,
+                field.offset,
+                field.offset + field.width - 1
+            )
+            .unwrap();
+            writeln!(
+                o,
+                "    pub const {}: Field = Field::new({}, {}, Access::{});",
+                field.name, field.offset, field.width, field.access
+            )
+            .unwrap();
+        }
+        writeln!(
+            o,
+            "    /// Every field of the {name} register, most significant first"
+        )
+        .unwrap();
+        writeln!(
+            o,
+            "    pub const FIELDS: [FieldInfo; {}] = [",
+            register.fields.len()
+        )
+        .unwrap();
+        for field in &register.fields {
+            writeln!(
+                o,
+                "        FieldInfo {{ name: \"{0}\", field: {0} }},",
+                field.name
+            )
+            .unwrap();
+        }
+        writeln!(o, "    ];").unwrap();
+        writeln!(o, "}}\n").unwrap();
+        writeln!(
+            o,
+            "const _: () = assert!(Registers::{variant} as u8 == {:#04X});\n",
+            register.address
+        )
+        .unwrap();
+
+        match kind {
+            Kind::Newtype(ty) => {
+                impl_register(o, register, ty, "value.into()", "**self");
+                impl_debug(o, register, ty);
+                impl_serde(o, register, ty);
+            }
+            Kind::Flags(ty) => {
+                let from_raw = "Self::from_bits_truncate(value)";
+                impl_register(o, register, ty, from_raw, "self.bits()");
+                impl_serde(o, register, ty);
+            }
+            Kind::Generated => {
+                generated_type(o, register, variant);
+                impl_register(o, register, variant, "Self(value)", "self.0");
+                impl_serde(o, register, variant);
+            }
+        }
+    }
+
+    writeln!(o, "/// Layout of every register, indexed by address").unwrap();
+    writeln!(
+        o,
+        "pub const REGISTERS: [RegisterInfo; {}] = [",
+        registers.len()
+    )
+    .unwrap();
+    for (index, register) in registers.iter().enumerate() {
+        assert_eq!(
+            register.address as usize, index,
+            "Register addresses are not contiguous"
+        );
+        let module = register.module();
+        writeln!(o, "    RegisterInfo {{").unwrap();
+        writeln!(o, "        name: \"{}\",", register.name).unwrap();
+        writeln!(o, "        address: {module}::ADDRESS,").unwrap();
+        writeln!(o, "        access: {module}::ACCESS,").unwrap();
+        writeln!(o, "        reset_value: {module}::RESET_VALUE,").unwrap();
+        writeln!(o, "        writable_mask: {module}::WRITABLE_MASK,").unwrap();
+        writeln!(o, "        fields: &{module}::FIELDS,").unwrap();
+        writeln!(o, "    }},").unwrap();
+    }
+    writeln!(o, "];").unwrap();
+    out
+}
+
+fn impl_register(o: &mut String, register: &Register, ty: &str, from_raw: &str, raw: &str) {
+    let module = register.module();
+    writeln!(
+        o,
+        "impl Register for {ty} {{
+    const ADDRESS: Registers = {module}::ADDRESS;
+    const RESET_VALUE: u16 = {module}::RESET_VALUE;
+    const WRITABLE_MASK: u16 = {module}::WRITABLE_MASK;
+    type Access = {module}::AccessType;
+
+    fn from_raw(value: u16) -> Self {{
+        {from_raw}
+    }}
+
+    fn raw(&self) -> u16 {{
+        {raw}
+    }}
+}}
+"
+    )
+    .unwrap();
+}
+
+fn generated_type(o: &mut String, register: &Register, ty: &str) {
+    let module = register.module();
+    let name = &register.name;
+    writeln!(o, "/// {name} register").unwrap();
+    writeln!(o, "#[derive(Clone, Copy, Eq, PartialEq)]").unwrap();
+    writeln!(o, "pub struct {ty}(u16);\n").unwrap();
+    writeln!(o, "impl {ty} {{").unwrap();
+    writeln!(o, "    /// Create a new {name} register value").unwrap();
+    writeln!(o, "    pub const fn new(value: u16) -> Self {{").unwrap();
+    writeln!(o, "        Self(value)").unwrap();
+    writeln!(o, "    }}").unwrap();
+    for field in &register.fields {
+        let accessor = register.accessor(field);
+        if field.access != "WriteOnly" {
+            writeln!(o, "\n    /// Get the {} field", field.name).unwrap();
+            writeln!(o, "    pub const fn get_{accessor}(&self) -> u16 {{").unwrap();
+            writeln!(o, "        {module}::{}.get(self.0)", field.name).unwrap();
+            writeln!(o, "    }}").unwrap();
+        }
+        if field.access != "ReadOnly" {
+            writeln!(o, "\n    /// Set the {} field", field.name).unwrap();
+            writeln!(
+                o,
+                "    pub const fn set_{accessor}(&mut self, value: u16) {{"
+            )
+            .unwrap();
+            writeln!(
+                o,
+                "        self.0 = {module}::{}.set(self.0, value);",
+                field.name
+            )
+            .unwrap();
+            writeln!(o, "    }}").unwrap();
+        }
+    }
+    writeln!(o, "}}\n").unwrap();
+    writeln!(
+        o,
+        "impl core::ops::Deref for {ty} {{
+    type Target = u16;
+    fn deref(&self) -> &u16 {{
+        &self.0
+    }}
+}}
+
+impl From<u16> for {ty} {{
+    fn from(value: u16) -> Self {{
+        Self(value)
+    }}
+}}
+
+#[cfg(feature = \"defmt\")]
+impl defmt::Format for {ty} {{
+    fn format(&self, f: defmt::Formatter) {{
+        defmt::Format::format(&RegisterInfo::of({module}::ADDRESS).decode(self.0), f)
+    }}
+}}
+
+impl core::fmt::Display for {ty} {{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
+        core::fmt::Display::fmt(&RegisterInfo::of({module}::ADDRESS).decode(self.0), f)
+    }}
+}}
+"
+    )
+    .unwrap();
+    impl_debug(o, register, ty);
+}
+
+/// `Debug` formats a register as the value of each of its fields
+fn impl_debug(o: &mut String, register: &Register, ty: &str) {
+    let module = register.module();
+    writeln!(
+        o,
+        "impl core::fmt::Debug for {ty} {{
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {{
+        core::fmt::Debug::fmt(&RegisterInfo::of({module}::ADDRESS).decode(self.raw()), f)
+    }}
+}}
+"
+    )
+    .unwrap();
+}
+
+/// Registers serialize as their raw value.
+/// Deserializing rejects values with bits set outside of the register's fields.
+fn impl_serde(o: &mut String, register: &Register, ty: &str) {
+    let name = &register.name;
+    let fields = register
+        .fields
+        .iter()
+        .fold(0, |mask, field| mask | field.mask());
+    let deserialize = match fields {
+        0xFFFF => "Ok(Self::from_raw(raw))".to_string(),
+        _ => format!(
+            "match raw & !{fields:#06X} {{
+            0 => Ok(Self::from_raw(raw)),
+            _ => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(raw as u64),
+                &\"a {name} register value\",
+            )),
+        }}"
+        ),
+    };
+    writeln!(
+        o,
+        "#[cfg(feature = \"serde\")]
+impl serde::Serialize for {ty} {{
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {{
+        serializer.serialize_u16(self.raw())
+    }}
+}}
+
+#[cfg(feature = \"serde\")]
+impl<'de> serde::Deserialize<'de> for {ty} {{
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {{
+        let raw = <u16 as serde::Deserialize>::deserialize(deserializer)?;
+        {deserialize}
+    }}
+}}
+"
+    )
+    .unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed={REGMAP}");
+    println!("cargo:rerun-if-changed=build.rs");
+    let csv = fs::read_to_string(REGMAP).expect("Failed to read the register map");
+    let registers = parse(&csv);
+    assert_eq!(registers.len(), REGISTERS.len(), "Register count changed");
+    let out = Path::new(&env::var("OUT_DIR").unwrap()).join("regmap.rs");
+    fs::write(out, generate(&registers)).expect("Failed to write the register map");
+}
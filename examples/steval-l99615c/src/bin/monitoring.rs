@@ -5,8 +5,8 @@
 use embassy_executor::Spawner;
 use embassy_time::Delay;
 use l9961::{
-    config::{CounterThreshold, VoltageThresholds},
-    registers::{Cfg2Enables, FetConfig},
+    config::{CounterThreshold, Enables, VoltageThresholds},
+    registers::FetConfig,
     Config,
 };
 use steval_l99615c::{configure_l9961_peripherals, exit};
@@ -28,6 +28,15 @@ async fn main(_spawner: Spawner) -> ! {
             max_pack_cell_sum_delta_mv: 500,
             fault_counter_threshold: CounterThreshold::default(),
         },
+        // Convert all five cells and the pack voltage, driving high-side FETs.
+        // The NTC and coulomb counter are enabled by the crate features
+        enables: Enables {
+            cells: 5,
+            pack_voltage: true,
+            discharge_fet: FetConfig::HighSide,
+            charge_fet: FetConfig::HighSide,
+            crc: false,
+        },
         ..Default::default()
     };
 
@@ -36,33 +45,11 @@ async fn main(_spawner: Spawner) -> ! {
     let mut delay = Delay;
     l9961.wake_if_asleep(&mut delay).await;
 
+    // Write the thresholds and enables, which restarts measurements once the device is configured
     l9961.apply_config().await.unwrap();
-    // Make sure measurements are disabled before changing settings
-    l9961.disable_measurements().await.unwrap();
-
-    // Configure cell and battery measurements
-    let enables = Cfg2Enables::new(
-        true,                // Enable cell 1 voltage measurement
-        true,                // Enable cell 2 voltage measurement
-        true,                // Enable cell 3 voltage measurement
-        true,                // Enable cell 4 voltage measurement
-        true,                // Enable cell 5 voltage measurement
-        true,                // Enable Battery voltage measurement
-        true,                // Enable temperature measurement
-        true,                // Enable current measurement
-        true,                // Enable coulomb counter
-        false,               // Disable over-current detection
-        false,               // Disable short-circuit detection
-        FetConfig::HighSide, // Set the discharge FET configuration to high-side
-        FetConfig::HighSide, // Set the charge FET configuration to high-side
-        false,               // Disable data CRC
-    );
-    l9961.write_cfg2_enables(enables).await.unwrap();
 
     // Clear any faults that may have been triggered
     l9961.clear_all_faults().await.unwrap();
-    // Start the measurement loop
-    l9961.enable_measurements().await.unwrap();
     let mut counter = 0;
     while counter < 100 {
         let measurement = l9961.make_measurement(&mut delay).await.unwrap();
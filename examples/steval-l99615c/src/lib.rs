@@ -26,7 +26,7 @@ bind_interrupts!(struct Irqs {
 pub fn configure_l9961_peripherals<'a>(
     peripherals: Peripherals,
     config: Config,
-) -> L9961<I2c<'a, Async>, ExtiInput<'a>, Output<'a>> {
+) -> L9961<I2c<'a, Async>, ExtiInput<'a>, ExtiInput<'a>, Output<'a>> {
     let i2c = I2c::new(
         peripherals.I2C2,
         peripherals.PB13,
@@ -0,0 +1,33 @@
+//! Adapters between the host's blocking peripherals and the driver's async traits
+
+use std::time::Duration;
+
+use embedded_hal::i2c::{ErrorType, I2c as BlockingI2c, Operation};
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+
+/// Runs a blocking I2C bus behind the driver's async I2C trait
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+pub struct Blocking<T>(pub T);
+
+impl<T: ErrorType> ErrorType for Blocking<T> {
+    type Error = T::Error;
+}
+
+impl<T: BlockingI2c> I2c for Blocking<T> {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        self.0.transaction(address, operations)
+    }
+}
+
+/// Delays by sleeping the thread
+pub struct SleepDelay;
+
+impl DelayNs for SleepDelay {
+    async fn delay_ns(&mut self, ns: u32) {
+        std::thread::sleep(Duration::from_nanos(ns as u64));
+    }
+}
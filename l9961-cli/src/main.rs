@@ -0,0 +1,334 @@
+//! # l9961-cli
+//! Command line access to an L9961 from a host, through Linux i2c-dev or the crate's built-in simulator.
+
+mod backend;
+
+use std::{fmt::Debug, path::PathBuf};
+
+use anyhow::{Context, Result, anyhow, bail};
+use clap::{Parser, Subcommand};
+use embedded_hal_async::{delay::DelayNs, i2c::I2c};
+use l9961::{
+    Config, L9961, NoPin,
+    config::pack_file::{PackConfiguration, PackRegisters},
+    measurement::Measurement,
+    registers::{Access, DevAddr, REGISTERS, RegisterInfo, RegisterSnapshot},
+    simulator::Simulator,
+};
+
+use backend::{Blocking, SleepDelay};
+
+/// Register access, configuration and monitoring of the ST L9961 Industrial BMS
+#[derive(Parser)]
+#[command(version, about)]
+struct Cli {
+    /// I2C bus device, e.g. /dev/i2c-1
+    #[arg(long, conflicts_with = "simulate")]
+    device: Option<PathBuf>,
+    /// Talk to the built-in simulator rather than a device
+    #[arg(long)]
+    simulate: bool,
+    /// 7-bit I2C address of the L9961
+    #[arg(long, default_value = "0x49", value_parser = parse_u8)]
+    address: u8,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Read and decode every readable register
+    Dump {
+        /// Only list the fields which differ from their reset values
+        #[arg(long)]
+        diff: bool,
+    },
+    /// Decode a raw register value without accessing a device
+    Decode {
+        /// Register name from the register map, or its address
+        register: String,
+        /// Raw value of the register
+        #[arg(value_parser = parse_u16)]
+        value: u16,
+    },
+    /// Read and decode a single register
+    Read {
+        /// Register name from the register map, or its address
+        register: String,
+    },
+    /// Write a raw value to a register
+    Write {
+        /// Register name from the register map, or its address
+        register: String,
+        /// Raw value to write
+        #[arg(value_parser = parse_u16)]
+        value: u16,
+    },
+    /// Apply a configuration file to the device
+    Apply {
+        /// TOML pack configuration file, or JSON file in the serde representation of `l9961::Config`
+        file: PathBuf,
+    },
+    /// Report the worst-case timing of each protection for a configuration file, without accessing a device
+    Timing {
+        /// TOML pack configuration file, or JSON file in the serde representation of `l9961::Config`
+        file: PathBuf,
+        /// Time taken by the pack's FETs to turn off in us
+        #[arg(long, default_value_t = 0)]
+        fet_turn_off_us: u32,
+    },
+    /// Transfer the configuration between the registers and NVM
+    Nvm {
+        #[command(subcommand)]
+        direction: NvmDirection,
+    },
+    /// Print measurements as the device makes them, leaving its diagnostic registers latched
+    Monitor {
+        /// Time between measurements in ms, at least the measurement cycle configured on the device
+        #[arg(long, default_value_t = 1000)]
+        interval_ms: u32,
+        /// Stop after this many measurements
+        #[arg(long)]
+        count: Option<usize>,
+        /// Print each measurement as a line of JSON
+        #[arg(long)]
+        json: bool,
+    },
+}
+
+#[derive(Subcommand)]
+enum NvmDirection {
+    /// Store the current register configuration in NVM
+    Upload,
+    /// Load the register configuration from NVM
+    Download,
+}
+
+fn main() -> Result<()> {
+    let cli = Cli::parse();
+
+    if let Command::Decode { register, value } = &cli.command {
+        println!("{}", register_info(register)?.decode(*value));
+        return Ok(());
+    }
+    if let Command::Timing {
+        file,
+        fet_turn_off_us,
+    } = &cli.command
+    {
+        return print_timing(file, *fet_turn_off_us);
+    }
+
+    match (&cli.device, cli.simulate) {
+        (_, true) => {
+            embassy_futures::block_on(execute(Simulator::with_nominal_measurements(), &cli))
+        }
+        (Some(device), false) => open_device(device, &cli),
+        (None, false) => bail!("Select a device with --device, or the simulator with --simulate"),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn open_device(device: &PathBuf, cli: &Cli) -> Result<()> {
+    let i2c = linux_embedded_hal::I2cdev::new(device)
+        .with_context(|| format!("Failed to open {}", device.display()))?;
+    embassy_futures::block_on(execute(Blocking(i2c), cli))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn open_device(_device: &PathBuf, _cli: &Cli) -> Result<()> {
+    bail!("I2C devices are only supported on Linux, use --simulate instead")
+}
+
+async fn execute<I2C: I2c>(i2c: I2C, cli: &Cli) -> Result<()> {
+    // The device is always addressed at --address, a configuration file only sets the address to program
+    let (config, pack_registers) = match &cli.command {
+        Command::Apply { file } => load_config(file)?,
+        _ => (Config::default(), None),
+    };
+    let programmed_address = config.address;
+    let config = Config {
+        address: cli.address,
+        ..config
+    };
+    let mut l9961 =
+        L9961::new_with_optional_pins(i2c, None::<NoPin>, None::<NoPin>, None::<NoPin>, config);
+
+    match &cli.command {
+        Command::Decode { .. } | Command::Timing { .. } => {
+            unreachable!("Decoding and timing do not access the device")
+        }
+        Command::Dump { diff } => {
+            let snapshot = l9961.read_register_snapshot().await.map_err(i2c_error)?;
+            match diff {
+                true => {
+                    for change in snapshot.diff(&RegisterSnapshot::reset_values()) {
+                        println!("{change}");
+                    }
+                }
+                false => print!("{snapshot}"),
+            }
+        }
+        Command::Read { register } => {
+            let info = register_info(register)?;
+            if info.access == Access::WriteOnly {
+                bail!("{} is write only", info.name);
+            }
+            let value = l9961.read_register(info.address).await.map_err(i2c_error)?;
+            println!("{}", info.decode(value));
+        }
+        Command::Write { register, value } => {
+            let info = register_info(register)?;
+            if info.access == Access::ReadOnly {
+                bail!("{} is read only", info.name);
+            }
+            if value & !info.writable_mask != 0 {
+                eprintln!(
+                    "Warning: bits {:#06X} of {} are not writable",
+                    value & !info.writable_mask,
+                    info.name
+                );
+            }
+            l9961
+                .write_register(info.address, *value)
+                .await
+                .map_err(i2c_error)?;
+        }
+        Command::Apply { file } => {
+            l9961.apply_config().await.map_err(i2c_error)?;
+            if let Some(registers) = pack_registers {
+                l9961
+                    .apply_pack_registers(&registers)
+                    .await
+                    .map_err(i2c_error)?;
+            }
+            if programmed_address != cli.address {
+                l9961
+                    .write_device_address(DevAddr::from(programmed_address as u16))
+                    .await
+                    .map_err(i2c_error)?;
+                println!("The device now answers at {programmed_address:#04X}");
+            }
+            println!("Applied {}", file.display());
+        }
+        Command::Nvm { direction } => match direction {
+            NvmDirection::Upload => l9961
+                .upload_configuration_to_nvm()
+                .await
+                .map_err(i2c_error)?,
+            NvmDirection::Download => l9961
+                .download_configuration_from_nvm()
+                .await
+                .map_err(i2c_error)?,
+        },
+        Command::Monitor {
+            interval_ms,
+            count,
+            json,
+        } => {
+            // Reading rather than waiting for measurements leaves the faults for the pack's own firmware to clear
+            let cycle = l9961
+                .read_cfg1_filters_cycles()
+                .await
+                .map_err(i2c_error)?
+                .get_t_meas_cycle();
+            if cycle.is_disabled() {
+                eprintln!(
+                    "Warning: the measurement cycle is disabled, the measurements will not change"
+                );
+            }
+            let interval_ms = (*interval_ms).max(cycle.period_ms() as u32);
+            let mut delay = SleepDelay;
+            for _ in 0..count.unwrap_or(usize::MAX) {
+                delay.delay_ms(interval_ms).await;
+                let measurement = l9961.read_measurement().await.map_err(i2c_error)?;
+                match json {
+                    true => println!("{}", serde_json::to_string(&measurement)?),
+                    false => print_measurement(&measurement),
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn print_measurement(measurement: &Measurement) {
+    let cells = (1..=5)
+        .filter_map(|cell| measurement.cell(cell))
+        .map(|cell| format!("{:>5}", cell.voltage_mv))
+        .collect::<Vec<_>>()
+        .join(" ");
+    println!(
+        "cells [{cells}] mV  sum {} mV  vbat {} mV  ntc {} mV  die {} C  current {}  faults {:?}",
+        measurement.cell_sum_mv,
+        measurement.vbat_mv,
+        measurement.ntc_mv,
+        measurement.die_temp,
+        measurement.cc_inst_meas,
+        measurement.pack_faults
+    );
+}
+
+fn print_timing(file: &PathBuf, fet_turn_off_us: u32) -> Result<()> {
+    let (config, _) = load_config(file)?;
+    let calculator = config.protection_timing().fet_turn_off_us(fet_turn_off_us);
+    for (protection, timing) in calculator.report() {
+        match timing {
+            Some(timing) => println!("{protection:<40} {timing}"),
+            None => println!("{protection:<40} disabled"),
+        }
+    }
+    Ok(())
+}
+
+/// Load a TOML pack configuration, or a JSON driver configuration
+fn load_config(file: &PathBuf) -> Result<(Config, Option<PackRegisters>)> {
+    if file
+        .extension()
+        .is_some_and(|extension| extension == "toml")
+    {
+        let pack = PackConfiguration::load(file)
+            .with_context(|| format!("Failed to load {}", file.display()))?;
+        return Ok((pack.config, Some(pack.registers)));
+    }
+    let contents = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read {}", file.display()))?;
+    let config = serde_json::from_str(&contents)
+        .with_context(|| format!("Invalid configuration in {}", file.display()))?;
+    Ok((config, None))
+}
+
+/// Look up a register by its register map name, case insensitive, or by its address
+fn register_info(register: &str) -> Result<&'static RegisterInfo> {
+    if let Some(info) = REGISTERS
+        .iter()
+        .find(|info| info.name.eq_ignore_ascii_case(register))
+    {
+        return Ok(info);
+    }
+    let address = parse_u8(register).map_err(|_| anyhow!("Unknown register {register}"))?;
+    REGISTERS
+        .get(address as usize)
+        .ok_or_else(|| anyhow!("No register at address {address:#04X}"))
+}
+
+fn i2c_error(error: impl Debug) -> anyhow::Error {
+    anyhow!("I2C error: {error:?}")
+}
+
+fn parse_u8(value: &str) -> Result<u8, String> {
+    parse_u16(value)?
+        .try_into()
+        .map_err(|_| format!("{value} does not fit in a byte"))
+}
+
+fn parse_u16(value: &str) -> Result<u16, String> {
+    match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u16::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+    .map_err(|error| format!("Invalid value {value}: {error}"))
+}
@@ -0,0 +1,370 @@
+//! # CAN
+//! Mapping of pack telemetry and control onto CAN frames, for any CAN peripheral implementing the
+//! [`embedded_can::Frame`] trait.
+//!
+//! Frames use IDs at fixed offsets from a configurable base ID, which may be standard or extended.
+//! All multi-byte values are little endian.
+//!
+//! | Offset | Frame          | Content                                                                         |
+//! |--------|----------------|---------------------------------------------------------------------------------|
+//! | 0x00   | Pack status    | Pack voltage mV (u16), cell sum mV (u16), current mA (i16), die temp °C (i8), counter (u8) |
+//! | 0x01   | Cells 1-4      | Cell 1-4 voltages in mV (u16)                                                   |
+//! | 0x02   | Cell 5 and NTC | Cell 5 voltage mV (u16), NTC voltage mV (u16), content flags (u8)               |
+//! | 0x03   | Coulomb count  | Accumulated current code (i32), instantaneous current code (i16), samples (u8)  |
+//! | 0x04   | Faults         | [`PackFaults`] (u16), cell 1-5 [`CellFaults`] (u8)                              |
+//! | 0x05   | Limits         | Charge voltage mV (u16), discharge voltage mV (u16), charge and discharge current in 100 mA (u16) |
+//! | 0x10   | Control        | Received: command (u8) followed by its arguments                                |
+//!
+//! The content flags hold the number of cells in bits 0-2, and flag the NTC (bit 3) and coulomb counter (bit 4)
+//! values as present. Values the device was not built to measure are sent as 0.
+//! The pack status counter increments with every measurement, so that receivers can detect stale data.
+//!
+//! Control commands:
+//! - `0x01` Set FETs: byte 1 bit 0 enables the charge FET, bit 1 the discharge FET
+//! - `0x02` Set balancing: byte 1 bits 0-4 enable balancing of cells 1-5
+//! - `0x03` Ship mode: bytes 1 and 2 must be [`SHIP_MODE_KEY`], to guard against accidental shutdown
+
+use embedded_can::{ExtendedId, Frame, Id, StandardId};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961,
+    faults::{CellFaults, PackFaults},
+    measurement::Measurement,
+    registers::Cfg3Act,
+    ship_mode::{ShipEntry, ShipOptions},
+};
+
+/// Offset of the pack status frame from the base ID
+pub const PACK_STATUS_OFFSET: u32 = 0x00;
+/// Offset of the cell 1-4 voltage frame from the base ID
+pub const CELLS_OFFSET: u32 = 0x01;
+/// Offset of the cell 5 and NTC frame from the base ID
+pub const CELL_5_NTC_OFFSET: u32 = 0x02;
+/// Offset of the coulomb counter frame from the base ID
+pub const COULOMB_COUNT_OFFSET: u32 = 0x03;
+/// Offset of the fault frame from the base ID
+pub const FAULTS_OFFSET: u32 = 0x04;
+/// Offset of the limits frame from the base ID
+pub const LIMITS_OFFSET: u32 = 0x05;
+/// Offset of the received control frame from the base ID
+pub const CONTROL_OFFSET: u32 = 0x10;
+/// Bytes 1 and 2 of a ship mode control frame
+pub const SHIP_MODE_KEY: [u8; 2] = [0x5A, 0xA5];
+
+const NTC_PRESENT: u8 = 0x08;
+const CC_PRESENT: u8 = 0x10;
+
+/// Base ID and cycle rates of the CAN frames
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct CanConfig {
+    base_id: Id,
+    /// Period of the measurement frames in ms, 0 to disable them
+    pub measurement_period_ms: u32,
+    /// Period of the fault frame in ms, 0 to only send it when the faults change
+    pub fault_period_ms: u32,
+    /// Period of the limits frame in ms, 0 to disable it
+    pub limits_period_ms: u32,
+}
+
+impl CanConfig {
+    /// Create a configuration with the given base ID, sending measurements and faults every 100ms and limits every second.
+    /// Returns `None` if the control frame offset does not fit in the ID.
+    pub fn new(base_id: Id) -> Option<Self> {
+        let config = Self {
+            base_id,
+            measurement_period_ms: 100,
+            fault_period_ms: 100,
+            limits_period_ms: 1000,
+        };
+        config.offset_id(CONTROL_OFFSET).map(|_| config)
+    }
+
+    /// The base ID of the frames
+    pub fn base_id(&self) -> Id {
+        self.base_id
+    }
+
+    /// The ID of the frame at the given offset from the base ID
+    pub fn id(&self, offset: u32) -> Id {
+        self.offset_id(offset)
+            .expect("Frame offsets are validated by CanConfig::new")
+    }
+
+    fn offset_id(&self, offset: u32) -> Option<Id> {
+        match self.base_id {
+            Id::Standard(id) => {
+                StandardId::new((id.as_raw() as u32 + offset).try_into().ok()?).map(Id::Standard)
+            }
+            Id::Extended(id) => ExtendedId::new(id.as_raw() + offset).map(Id::Extended),
+        }
+    }
+
+    /// Decode a received frame into a control command.
+    /// Returns `None` if the frame is not a control frame, or is malformed.
+    pub fn decode_control(&self, frame: &impl Frame) -> Option<ControlCommand> {
+        if !frame.is_data_frame() || frame.id() != self.id(CONTROL_OFFSET) {
+            return None;
+        }
+        match frame.data() {
+            [0x01, fets, ..] => Some(ControlCommand::SetFets {
+                charge: fets & 0x01 != 0,
+                discharge: fets & 0x02 != 0,
+            }),
+            [0x02, cells, ..] => Some(ControlCommand::SetBalancing(cells & 0x1F)),
+            [0x03, key @ ..] if key.starts_with(&SHIP_MODE_KEY) => Some(ControlCommand::ShipMode),
+            _ => None,
+        }
+    }
+}
+
+/// Charge and discharge limits of the pack, reported to the vehicle
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PackLimits {
+    /// Maximum charge voltage in mV
+    pub charge_voltage_mv: u16,
+    /// Minimum discharge voltage in mV
+    pub discharge_voltage_mv: u16,
+    /// Maximum charge current in mA
+    pub charge_current_ma: u32,
+    /// Maximum discharge current in mA
+    pub discharge_current_ma: u32,
+}
+
+/// A command received in a control frame
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ControlCommand {
+    /// Enable or disable the charge and discharge FETs
+    SetFets {
+        /// Enable the charge FET
+        charge: bool,
+        /// Enable the discharge FET
+        discharge: bool,
+    },
+    /// Enable balancing of the cells set in the mask, bit 0 being cell 1
+    SetBalancing(u8),
+    /// Put the device into ship mode, once the FETs are verified open
+    ShipMode,
+}
+
+/// Encodes telemetry into CAN frames, and schedules them according to the configured cycle rates
+pub struct CanTelemetry {
+    config: CanConfig,
+    counter: u8,
+    last_measurement_ms: Option<u64>,
+    last_faults_ms: Option<u64>,
+    last_limits_ms: Option<u64>,
+    last_faults: Option<(PackFaults, [CellFaults; 5])>,
+}
+
+impl CanTelemetry {
+    /// Create a new encoder with the given configuration
+    pub fn new(config: CanConfig) -> Self {
+        Self {
+            config,
+            counter: 0,
+            last_measurement_ms: None,
+            last_faults_ms: None,
+            last_limits_ms: None,
+            last_faults: None,
+        }
+    }
+
+    /// The configuration of the encoder
+    pub fn config(&self) -> &CanConfig {
+        &self.config
+    }
+
+    /// The frames due at `now_ms`, given the latest measurement and limits.
+    /// The fault frame is additionally sent whenever the faults change.
+    pub fn frames_due<F: Frame>(
+        &mut self,
+        now_ms: u64,
+        measurement: &Measurement,
+        limits: &PackLimits,
+    ) -> impl Iterator<Item = F> + use<F> {
+        let faults = (measurement.pack_faults, cell_faults(measurement));
+        let faults_changed = self.last_faults != Some(faults);
+
+        let mut frames: [Option<F>; 6] = [const { None }; 6];
+        if due(
+            &mut self.last_measurement_ms,
+            now_ms,
+            self.config.measurement_period_ms,
+        ) {
+            let [status, cells, cell_5_ntc, coulomb_count] = self.measurement_frames(measurement);
+            frames[0] = status;
+            frames[1] = cells;
+            frames[2] = cell_5_ntc;
+            frames[3] = coulomb_count;
+        }
+        if due(
+            &mut self.last_faults_ms,
+            now_ms,
+            self.config.fault_period_ms,
+        ) | faults_changed
+        {
+            self.last_faults_ms = Some(now_ms);
+            self.last_faults = Some(faults);
+            frames[4] = self.fault_frame(measurement);
+        }
+        if due(
+            &mut self.last_limits_ms,
+            now_ms,
+            self.config.limits_period_ms,
+        ) {
+            frames[5] = self.limits_frame(limits);
+        }
+        frames.into_iter().flatten()
+    }
+
+    /// Encode a measurement into the pack status, cell, NTC and coulomb counter frames,
+    /// incrementing the pack status counter
+    pub fn measurement_frames<F: Frame>(&mut self, measurement: &Measurement) -> [Option<F>; 4] {
+        let voltages = cell_voltages(measurement);
+        let cell_count = (1..=5)
+            .filter(|cell| measurement.cell(*cell).is_some())
+            .count() as u8;
+
+        #[cfg(feature = "coulomb_counting")]
+        let (current_ma, acc, inst_meas, samples) = (
+            crate::conversions::current_ma_from_signed_code(measurement.cc_inst_meas)
+                .clamp(i16::MIN as i32, i16::MAX as i32) as i16,
+            measurement.cc_acc,
+            measurement.cc_inst_meas,
+            measurement.cc_samples,
+        );
+        #[cfg(not(feature = "coulomb_counting"))]
+        let (current_ma, acc, inst_meas, samples) = (0i16, 0i32, 0i16, 0u8);
+        #[cfg(feature = "ntc")]
+        let ntc_mv = measurement.ntc_mv;
+        #[cfg(not(feature = "ntc"))]
+        let ntc_mv = 0u16;
+
+        let mut status = [0; 8];
+        status[0..2].copy_from_slice(&measurement.vbat_mv.to_le_bytes());
+        status[2..4].copy_from_slice(&measurement.cell_sum_mv.to_le_bytes());
+        status[4..6].copy_from_slice(&current_ma.to_le_bytes());
+        status[6] = measurement.die_temp.min(i8::MAX as u16) as u8;
+        status[7] = self.counter;
+        self.counter = self.counter.wrapping_add(1);
+
+        let mut cells = [0; 8];
+        for (bytes, voltage) in cells.chunks_exact_mut(2).zip(voltages) {
+            bytes.copy_from_slice(&voltage.to_le_bytes());
+        }
+
+        let mut flags = cell_count;
+        if cfg!(feature = "ntc") {
+            flags |= NTC_PRESENT;
+        }
+        if cfg!(feature = "coulomb_counting") {
+            flags |= CC_PRESENT;
+        }
+        let mut cell_5_ntc = [0; 5];
+        cell_5_ntc[0..2].copy_from_slice(&voltages[4].to_le_bytes());
+        cell_5_ntc[2..4].copy_from_slice(&ntc_mv.to_le_bytes());
+        cell_5_ntc[4] = flags;
+
+        let mut coulomb_count = [0; 7];
+        coulomb_count[0..4].copy_from_slice(&acc.to_le_bytes());
+        coulomb_count[4..6].copy_from_slice(&inst_meas.to_le_bytes());
+        coulomb_count[6] = samples;
+
+        [
+            F::new(self.config.id(PACK_STATUS_OFFSET), &status),
+            F::new(self.config.id(CELLS_OFFSET), &cells),
+            F::new(self.config.id(CELL_5_NTC_OFFSET), &cell_5_ntc),
+            F::new(self.config.id(COULOMB_COUNT_OFFSET), &coulomb_count),
+        ]
+    }
+
+    /// Encode the pack and cell faults of a measurement into the fault frame
+    pub fn fault_frame<F: Frame>(&self, measurement: &Measurement) -> Option<F> {
+        let mut data = [0; 7];
+        data[0..2].copy_from_slice(&measurement.pack_faults.bits().to_le_bytes());
+        for (byte, faults) in data[2..].iter_mut().zip(cell_faults(measurement)) {
+            *byte = faults.bits();
+        }
+        F::new(self.config.id(FAULTS_OFFSET), &data)
+    }
+
+    /// Encode the pack limits into the limits frame
+    pub fn limits_frame<F: Frame>(&self, limits: &PackLimits) -> Option<F> {
+        let deciamps = |current_ma: u32| (current_ma / 100).min(u16::MAX as u32) as u16;
+        let mut data = [0; 8];
+        data[0..2].copy_from_slice(&limits.charge_voltage_mv.to_le_bytes());
+        data[2..4].copy_from_slice(&limits.discharge_voltage_mv.to_le_bytes());
+        data[4..6].copy_from_slice(&deciamps(limits.charge_current_ma).to_le_bytes());
+        data[6..8].copy_from_slice(&deciamps(limits.discharge_current_ma).to_le_bytes());
+        F::new(self.config.id(LIMITS_OFFSET), &data)
+    }
+}
+
+/// Whether a periodic frame is due, recording the send time if it is
+fn due(last_ms: &mut Option<u64>, now_ms: u64, period_ms: u32) -> bool {
+    let due = period_ms != 0
+        && last_ms.is_none_or(|last_ms| now_ms.saturating_sub(last_ms) >= period_ms as u64);
+    if due {
+        *last_ms = Some(now_ms);
+    }
+    due
+}
+
+fn cell_voltages(measurement: &Measurement) -> [u16; 5] {
+    [1, 2, 3, 4, 5].map(|cell| measurement.cell(cell).map_or(0, |cell| cell.voltage_mv))
+}
+
+fn cell_faults(measurement: &Measurement) -> [CellFaults; 5] {
+    [1, 2, 3, 4, 5].map(|cell| {
+        measurement
+            .cell(cell)
+            .map_or(CellFaults::empty(), |cell| cell.faults)
+    })
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Execute a command received in a CAN control frame.
+    /// Ship mode is entered through [`L9961::enter_ship_mode`], without committing the configuration to NVM,
+    /// and its outcome is returned so the application can report a refusal.
+    pub async fn execute_control_command(
+        &mut self,
+        command: ControlCommand,
+        delay: &mut impl DelayNs,
+    ) -> Result<Option<ShipEntry>, I2C::Error> {
+        match command {
+            ControlCommand::SetFets { charge, discharge } => {
+                self.modify(|cfg3: &mut Cfg3Act| {
+                    cfg3.set_charge_enabled(charge);
+                    cfg3.set_discharge_enabled(discharge);
+                })
+                .await?;
+            }
+            ControlCommand::SetBalancing(cells) => {
+                self.modify(|cfg3: &mut Cfg3Act| {
+                    cfg3.set_cell_1_balance_enabled(cells & 0x01 != 0);
+                    cfg3.set_cell_2_balance_enabled(cells & 0x02 != 0);
+                    cfg3.set_cell_3_balance_enabled(cells & 0x04 != 0);
+                    cfg3.set_cell_4_balance_enabled(cells & 0x08 != 0);
+                    cfg3.set_cell_5_balance_enabled(cells & 0x10 != 0);
+                })
+                .await?;
+            }
+            ControlCommand::ShipMode => {
+                let entry = self.enter_ship_mode(delay, ShipOptions::default()).await?;
+                return Ok(Some(entry));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -15,11 +15,12 @@ const NVM_WRITE_READ_CODE_CMD_DOWNLOAD: u16 = 0x5555;
 /// Register value for GO2 commands
 const CMD_VAL: u16 = 0x2000;
 
-impl<I2C, I, O> L9961<I2C, I, O>
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: I2c,
-    I: Wait,
-    O: OutputPin,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
 {
     /// Download the stored device configuration from NVM
     pub async fn download_configuration_from_nvm(&mut self) -> Result<(), I2C::Error> {
@@ -4,12 +4,22 @@
 //! with validation of the values to ensure they are within the valid range for the L9961.
 //! The various configuration structs are used to set the configuration registers on the L9961.
 
+mod builder;
+mod enables;
 #[cfg(feature = "ntc")]
 mod ntc_thresholds;
+#[cfg(feature = "toml")]
+pub mod pack_file;
+mod presets;
+mod timing;
 mod voltage_thresholds;
 
+pub use builder::{ConfigBuilder, ConfigError, ConfigField, ConfigViolation};
+pub use enables::{CurrentProtection, Enables, OverCurrentProtection, ShortCircuitProtection};
 #[cfg(feature = "ntc")]
 pub use ntc_thresholds::NtcThresholds;
+pub use presets::{CellLimits, Chemistry, NtcSensor, TemperatureLimits};
+pub use timing::{Protection, ProtectionTiming, TimingCalculator};
 pub use voltage_thresholds::VoltageThresholds;
 
 use crate::{
@@ -17,8 +27,18 @@ use crate::{
     L9961,
 };
 
+/// Number of cells enabled by the cell count features
+pub const CELL_COUNT: u8 = if cfg!(feature = "5_cells") {
+    5
+} else if cfg!(feature = "4_cells") {
+    4
+} else {
+    3
+};
+
 /// Configuration struct for the L9961
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Config {
     /// I2C address of the l9961 device
     pub address: u8,
@@ -29,10 +49,22 @@ pub struct Config {
     pub ntc_thresholds: NtcThresholds,
     /// Configuration block the timing of measurements
     pub measurement_cycles: Cfg1FiltersCycles,
+    /// Enabled cells and FET topology
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub enables: Enables,
+    /// Over-current and short circuit protection
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub current_protection: CurrentProtection,
 }
 
 impl Config {
-    /// Create a new L9961 Config with default address, voltage thresholds, NTC thresholds, and measurement cycles
+    /// Start building a checked configuration
+    pub const fn builder() -> ConfigBuilder {
+        ConfigBuilder::new()
+    }
+
+    /// Create a new L9961 Config with default address, voltage thresholds, NTC thresholds, measurement cycles and enables,
+    /// with current protection disabled
     pub const fn default() -> Self {
         Self {
             address: 0x49,
@@ -40,6 +72,8 @@ impl Config {
             #[cfg(feature = "ntc")]
             ntc_thresholds: NtcThresholds::new(),
             measurement_cycles: Cfg1FiltersCycles::default(),
+            enables: Enables::new(),
+            current_protection: CurrentProtection::disabled(),
         }
     }
 }
@@ -53,13 +87,26 @@ impl Default for Config {
 /// Newtype for the counter threshold value to ensure a valid range.
 /// The counter threshold is a 4-bit value used to determine how many times a fault condition must occur before the fault is triggered.
 /// Default value is 10.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct CounterThreshold(u8);
 
 impl CounterThreshold {
-    /// Create a new CounterThreshold with the given value
+    /// Create a new CounterThreshold with the given value.
+    /// Panics if the value does not fit in 4 bits, which fails compilation when used in a `const`.
     pub const fn new(value: u8) -> Self {
-        debug_assert!(value < 16);
-        CounterThreshold(value)
+        match Self::try_new(value) {
+            Some(threshold) => threshold,
+            None => panic!("Counter threshold must be between 0 and 15"),
+        }
+    }
+
+    /// Create a new CounterThreshold, or `None` if the value does not fit in 4 bits
+    pub const fn try_new(value: u8) -> Option<Self> {
+        match value {
+            0..16 => Some(Self(value)),
+            _ => None,
+        }
     }
 
     /// Create a new CounterThreshold with the default value of 10
@@ -68,25 +115,51 @@ impl CounterThreshold {
     }
 
     /// Get the internal value
-    pub(crate) const fn value(&self) -> u8 {
+    pub const fn value(&self) -> u8 {
         self.0
     }
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
+#[cfg(feature = "serde")]
+impl serde::Serialize for CounterThreshold {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_u8(self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for CounterThreshold {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = u8::deserialize(deserializer)?;
+        match Self::try_new(value) {
+            Some(threshold) => Ok(threshold),
+            None => Err(serde::de::Error::invalid_value(
+                serde::de::Unexpected::Unsigned(value as u64),
+                &"a counter threshold between 0 and 15",
+            )),
+        }
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: embedded_hal_async::i2c::I2c,
-    I: embedded_hal_async::digital::Wait,
-    O: embedded_hal::digital::OutputPin,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
 {
-    /// Apply the given configuration to the L9961
+    /// Apply the given configuration to the L9961.
+    /// Measurements are disabled while the configuration is written, as CFG2_ENABLES may only be changed
+    /// while measurements are disabled, and are restarted with the configured measurement cycle afterwards.
     pub async fn apply_config(&mut self) -> Result<(), I2C::Error> {
         self.write_device_address(DevAddr::from(self.config.address as u16))
             .await?;
+        self.disable_measurements().await?;
         self.apply_voltage_threshold_configuration().await?;
         #[cfg(feature = "ntc")]
         self.apply_ntc_threshold_configuration().await?;
-
-        Ok(())
+        self.apply_current_protection_configuration().await?;
+        self.write_cfg2_enables(self.config.cfg2_enables()).await?;
+        self.enable_measurements().await
     }
 }
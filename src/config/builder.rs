@@ -0,0 +1,694 @@
+use core::fmt;
+
+#[cfg(feature = "ntc")]
+use super::NtcThresholds;
+use super::{CELL_COUNT, Config, CurrentProtection, Enables, VoltageThresholds};
+use crate::{
+    conversions::{
+        cell_voltage_measurement_mv_from_code, cell_voltage_threshold_mv_from_code,
+        pack_voltage_threshold_mv_from_code,
+    },
+    registers::{
+        Cfg1FiltersCycles,
+        regmap::{dev_addr, sc_threshold},
+    },
+};
+
+/// Largest cell threshold which fits in the 8 bit threshold fields
+const MAX_CELL_THRESHOLD_MV: u16 = cell_voltage_threshold_mv_from_code(u8::MAX);
+/// Largest cell voltage which can be measured
+const MAX_CELL_MEASUREMENT_MV: u16 = cell_voltage_measurement_mv_from_code(0x0FFF);
+/// Largest pack threshold which fits in the 8 bit threshold fields
+const MAX_PACK_THRESHOLD_MV: u16 = pack_voltage_threshold_mv_from_code(u8::MAX);
+/// Largest NTC threshold which fits in the 12 bit threshold fields
+#[cfg(feature = "ntc")]
+const MAX_NTC_THRESHOLD_MV: u16 = crate::conversions::ntc_voltage_mv_from_code(0x0FFF);
+/// Each setting can be out of range or lose resolution, plus the ordering, consistency and support checks
+const MAX_VIOLATIONS: usize = 25;
+
+/// Builder for a [`Config`] which checks the configuration before it is used.
+///
+/// [`ConfigBuilder::build`] reports every setting which does not fit its register,
+/// cannot be programmed exactly, or is inconsistent with the other thresholds.
+/// The builder is usable in `const` contexts, so configurations can be checked at compile time:
+///
+/// ```
+/// use l9961::config::ConfigBuilder;
+///
+/// const CONFIG: l9961::Config = match ConfigBuilder::new().build() {
+///     Ok(config) => config,
+///     Err(_) => panic!("Invalid configuration"),
+/// };
+/// ```
+pub struct ConfigBuilder {
+    config: Config,
+    allow_rounding: bool,
+}
+
+impl ConfigBuilder {
+    /// Start from the default configuration, for the number of cells enabled by the cell count features
+    pub const fn new() -> Self {
+        Self {
+            config: Config::default(),
+            allow_rounding: false,
+        }
+    }
+
+    /// Set the I2C address of the device
+    pub const fn address(mut self, address: u8) -> Self {
+        self.config.address = address;
+        self
+    }
+
+    /// Set the number of cells in series, whose conversions are enabled and which the pack thresholds are checked against
+    pub const fn cells(mut self, cells: u8) -> Self {
+        self.config.enables.cells = cells;
+        self
+    }
+
+    /// Set the enabled cells and FET topology, replacing the number of cells set by [`ConfigBuilder::cells`]
+    pub const fn enables(mut self, enables: Enables) -> Self {
+        self.config.enables = enables;
+        self
+    }
+
+    /// Set the over-current and short circuit protection
+    pub const fn current_protection(mut self, protection: CurrentProtection) -> Self {
+        self.config.current_protection = protection;
+        self
+    }
+
+    /// Set the cell and pack voltage thresholds
+    pub const fn voltage_thresholds(mut self, thresholds: VoltageThresholds) -> Self {
+        self.config.voltage_thresholds = thresholds;
+        self
+    }
+
+    /// Set the NTC thresholds
+    #[cfg(feature = "ntc")]
+    pub const fn ntc_thresholds(mut self, thresholds: NtcThresholds) -> Self {
+        self.config.ntc_thresholds = thresholds;
+        self
+    }
+
+    /// Set the filter times and measurement cycle
+    pub const fn measurement_cycles(mut self, measurement_cycles: Cfg1FiltersCycles) -> Self {
+        self.config.measurement_cycles = measurement_cycles;
+        self
+    }
+
+    /// Accept thresholds which are rounded to the nearest programmable value,
+    /// rather than reporting them as [`ConfigViolation::ResolutionLoss`]
+    pub const fn allow_rounding(mut self) -> Self {
+        self.allow_rounding = true;
+        self
+    }
+
+    /// Check the configuration, returning it if every setting is valid
+    // The violations are held inline, as there is no allocator to box them
+    #[allow(clippy::result_large_err)]
+    pub const fn build(self) -> Result<Config, ConfigError> {
+        let mut error = ConfigError::new();
+        let config = &self.config;
+        let thresholds = &config.voltage_thresholds;
+        // The thresholds are checked as programmed, each rounded in the direction of earlier protection
+        let programmed = thresholds.programmed();
+
+        if config.address as u16 > dev_addr::DEV_ADDR_ID.mask() {
+            error.push(ConfigViolation::OutOfRange {
+                field: ConfigField::Address,
+                value: config.address as u16,
+                min: 0,
+                max: dev_addr::DEV_ADDR_ID.mask(),
+            });
+        }
+        let cells = config.enables.cells;
+        if cells < 3 || cells > CELL_COUNT {
+            error.push(ConfigViolation::OutOfRange {
+                field: ConfigField::Cells,
+                value: cells as u16,
+                min: 3,
+                max: CELL_COUNT as u16,
+            });
+        }
+        // The driver does not frame its transfers with a CRC, so it could no longer talk to the device
+        if config.enables.crc {
+            error.push(ConfigViolation::Unsupported {
+                field: ConfigField::Crc,
+            });
+        }
+
+        let cell_thresholds = [
+            (
+                ConfigField::CellOverVoltage,
+                thresholds.cell_over_voltage_threshold_mv,
+                programmed.cell_over_voltage_threshold_mv,
+            ),
+            (
+                ConfigField::CellSevereOverVoltageDelta,
+                thresholds.cell_severe_over_voltage_delta_threshold_mv,
+                programmed.cell_severe_over_voltage_delta_threshold_mv,
+            ),
+            (
+                ConfigField::CellUnderVoltage,
+                thresholds.cell_under_voltage_threshold_mv,
+                programmed.cell_under_voltage_threshold_mv,
+            ),
+            (
+                ConfigField::CellSevereUnderVoltageDelta,
+                thresholds.cell_severe_under_voltage_delta_threshold_mv,
+                programmed.cell_severe_under_voltage_delta_threshold_mv,
+            ),
+            (
+                ConfigField::CellBalancingUnderVoltageDelta,
+                thresholds.cell_balancing_under_voltage_delta_threshold_mv,
+                programmed.cell_balancing_under_voltage_delta_threshold_mv,
+            ),
+        ];
+        let mut i = 0;
+        while i < cell_thresholds.len() {
+            let (field, value, programmed_mv) = cell_thresholds[i];
+            if value > MAX_CELL_THRESHOLD_MV {
+                error.out_of_range(field, value, MAX_CELL_THRESHOLD_MV);
+            } else if !self.allow_rounding && programmed_mv != value {
+                error.resolution_loss(field, value, programmed_mv);
+            }
+            i += 1;
+        }
+
+        let pack_thresholds = [
+            (
+                ConfigField::PackOverVoltage,
+                thresholds.pack_over_voltage_threshold_mv,
+                programmed.pack_over_voltage_threshold_mv,
+            ),
+            (
+                ConfigField::PackUnderVoltage,
+                thresholds.pack_under_voltage_threshold_mv,
+                programmed.pack_under_voltage_threshold_mv,
+            ),
+            (
+                ConfigField::MaxPackCellSumDelta,
+                thresholds.max_pack_cell_sum_delta_mv,
+                programmed.max_pack_cell_sum_delta_mv,
+            ),
+        ];
+        let mut i = 0;
+        while i < pack_thresholds.len() {
+            let (field, value, programmed_mv) = pack_thresholds[i];
+            if value > MAX_PACK_THRESHOLD_MV {
+                error.out_of_range(field, value, MAX_PACK_THRESHOLD_MV);
+            } else if !self.allow_rounding && programmed_mv != value {
+                error.resolution_loss(field, value, programmed_mv);
+            }
+            i += 1;
+        }
+
+        let cell_ov = programmed.cell_over_voltage_threshold_mv;
+        let severe_ov_delta = programmed.cell_severe_over_voltage_delta_threshold_mv;
+        let cell_uv = programmed.cell_under_voltage_threshold_mv;
+        let severe_uv_delta = programmed.cell_severe_under_voltage_delta_threshold_mv;
+        let balancing_delta = programmed.cell_balancing_under_voltage_delta_threshold_mv;
+        let pack_ov = programmed.pack_over_voltage_threshold_mv;
+        let pack_uv = programmed.pack_under_voltage_threshold_mv;
+
+        if cell_uv >= cell_ov {
+            error.ordering(ConfigField::CellUnderVoltage, ConfigField::CellOverVoltage);
+        }
+        if balancing_delta == 0 {
+            error.ordering(
+                ConfigField::CellUnderVoltage,
+                ConfigField::CellBalancingUnderVoltageDelta,
+            );
+        } else if cell_uv as u32 + balancing_delta as u32 >= cell_ov as u32 {
+            error.ordering(
+                ConfigField::CellBalancingUnderVoltageDelta,
+                ConfigField::CellOverVoltage,
+            );
+        }
+        // A severe over-voltage threshold beyond the measurement range can never trip
+        if severe_ov_delta > MAX_CELL_MEASUREMENT_MV.saturating_sub(cell_ov) {
+            error.out_of_range(
+                ConfigField::CellSevereOverVoltageDelta,
+                thresholds.cell_severe_over_voltage_delta_threshold_mv,
+                MAX_CELL_MEASUREMENT_MV.saturating_sub(cell_ov),
+            );
+        }
+        if severe_uv_delta >= cell_uv {
+            error.ordering(
+                ConfigField::CellSevereUnderVoltageDelta,
+                ConfigField::CellUnderVoltage,
+            );
+        }
+        if pack_uv >= pack_ov {
+            error.ordering(ConfigField::PackUnderVoltage, ConfigField::PackOverVoltage);
+        }
+        // Pack thresholds outside the sum of the cell thresholds can never trip before a cell fault
+        let cells = cells as u32;
+        if pack_ov as u32 > cells * cell_ov as u32 {
+            error.push(ConfigViolation::PackInconsistent {
+                field: ConfigField::PackOverVoltage,
+                pack_mv: pack_ov,
+                cells_mv: cells * cell_ov as u32,
+            });
+        }
+        if (pack_uv as u32) < cells * cell_uv as u32 {
+            error.push(ConfigViolation::PackInconsistent {
+                field: ConfigField::PackUnderVoltage,
+                pack_mv: pack_uv,
+                cells_mv: cells * cell_uv as u32,
+            });
+        }
+
+        #[cfg(feature = "ntc")]
+        {
+            let ntc = &config.ntc_thresholds;
+            let ntc_thresholds = [
+                (ConfigField::NtcOverTemperature, ntc.over_temp_threshold_mv),
+                (
+                    ConfigField::NtcSevereOverTemperatureDelta,
+                    ntc.severe_over_temp_delta_threshold_mv,
+                ),
+                (
+                    ConfigField::NtcUnderTemperature,
+                    ntc.under_temp_threshold_mv,
+                ),
+            ];
+            let mut i = 0;
+            while i < ntc_thresholds.len() {
+                let (field, value) = ntc_thresholds[i];
+                if value > MAX_NTC_THRESHOLD_MV {
+                    error.out_of_range(field, value, MAX_NTC_THRESHOLD_MV);
+                }
+                i += 1;
+            }
+            // The NTC voltage falls as the temperature rises
+            if ntc.over_temp_threshold_mv >= ntc.under_temp_threshold_mv {
+                error.ordering(
+                    ConfigField::NtcOverTemperature,
+                    ConfigField::NtcUnderTemperature,
+                );
+            }
+        }
+
+        if let Some(short_circuit) = config.current_protection.short_circuit {
+            if short_circuit.threshold as u16 > sc_threshold::SC_TH.get(u16::MAX) {
+                error.out_of_range(
+                    ConfigField::ShortCircuitThreshold,
+                    short_circuit.threshold as u16,
+                    sc_threshold::SC_TH.get(u16::MAX),
+                );
+            }
+            if short_circuit.persistence as u16 > sc_threshold::SC_PERSIST_TH.get(u16::MAX) {
+                error.out_of_range(
+                    ConfigField::ShortCircuitPersistence,
+                    short_circuit.persistence as u16,
+                    sc_threshold::SC_PERSIST_TH.get(u16::MAX),
+                );
+            }
+        }
+
+        match error.is_empty() {
+            true => Ok(self.config),
+            false => Err(error),
+        }
+    }
+}
+
+impl Default for ConfigBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A setting checked by [`ConfigBuilder::build`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigField {
+    /// [`Config::address`]
+    Address,
+    /// [`Enables::cells`]
+    Cells,
+    /// [`Enables::crc`]
+    Crc,
+    /// [`VoltageThresholds::cell_over_voltage_threshold_mv`]
+    CellOverVoltage,
+    /// [`VoltageThresholds::cell_severe_over_voltage_delta_threshold_mv`]
+    CellSevereOverVoltageDelta,
+    /// [`VoltageThresholds::cell_under_voltage_threshold_mv`]
+    CellUnderVoltage,
+    /// [`VoltageThresholds::cell_severe_under_voltage_delta_threshold_mv`]
+    CellSevereUnderVoltageDelta,
+    /// [`VoltageThresholds::cell_balancing_under_voltage_delta_threshold_mv`]
+    CellBalancingUnderVoltageDelta,
+    /// [`VoltageThresholds::pack_over_voltage_threshold_mv`]
+    PackOverVoltage,
+    /// [`VoltageThresholds::pack_under_voltage_threshold_mv`]
+    PackUnderVoltage,
+    /// [`VoltageThresholds::max_pack_cell_sum_delta_mv`]
+    MaxPackCellSumDelta,
+    /// NtcThresholds::over_temp_threshold_mv
+    NtcOverTemperature,
+    /// NtcThresholds::severe_over_temp_delta_threshold_mv
+    NtcSevereOverTemperatureDelta,
+    /// NtcThresholds::under_temp_threshold_mv
+    NtcUnderTemperature,
+    /// [`ShortCircuitProtection::threshold`](super::ShortCircuitProtection::threshold)
+    ShortCircuitThreshold,
+    /// [`ShortCircuitProtection::persistence`](super::ShortCircuitProtection::persistence)
+    ShortCircuitPersistence,
+}
+
+impl fmt::Display for ConfigField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ConfigField::Address => "address",
+            ConfigField::Cells => "cell count",
+            ConfigField::Crc => "I2C CRC",
+            ConfigField::CellOverVoltage => "cell over-voltage threshold",
+            ConfigField::CellSevereOverVoltageDelta => "cell severe over-voltage delta",
+            ConfigField::CellUnderVoltage => "cell under-voltage threshold",
+            ConfigField::CellSevereUnderVoltageDelta => "cell severe under-voltage delta",
+            ConfigField::CellBalancingUnderVoltageDelta => "cell balancing under-voltage delta",
+            ConfigField::PackOverVoltage => "pack over-voltage threshold",
+            ConfigField::PackUnderVoltage => "pack under-voltage threshold",
+            ConfigField::MaxPackCellSumDelta => "pack vs cell sum delta",
+            ConfigField::NtcOverTemperature => "NTC over-temperature threshold",
+            ConfigField::NtcSevereOverTemperatureDelta => "NTC severe over-temperature delta",
+            ConfigField::NtcUnderTemperature => "NTC under-temperature threshold",
+            ConfigField::ShortCircuitThreshold => "short circuit threshold",
+            ConfigField::ShortCircuitPersistence => "short circuit persistence",
+        })
+    }
+}
+
+/// A problem with a setting found by [`ConfigBuilder::build`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigViolation {
+    /// The value does not fit in its register field
+    OutOfRange {
+        /// The setting
+        field: ConfigField,
+        /// Requested value
+        value: u16,
+        /// Smallest accepted value
+        min: u16,
+        /// Largest accepted value
+        max: u16,
+    },
+    /// The value is not a multiple of the register resolution, and would be rounded.
+    /// See [`VoltageThresholds::programmed`] for the direction each threshold is rounded in.
+    ResolutionLoss {
+        /// The setting
+        field: ConfigField,
+        /// Requested value in mV
+        requested_mv: u16,
+        /// Value which would be programmed, in mV
+        programmed_mv: u16,
+    },
+    /// The threshold `lower` must be below the threshold `upper`
+    Ordering {
+        /// Setting which must be lower
+        lower: ConfigField,
+        /// Setting which must be higher
+        upper: ConfigField,
+    },
+    /// A pack threshold lies outside the cell threshold multiplied by the number of cells
+    PackInconsistent {
+        /// The pack threshold
+        field: ConfigField,
+        /// Pack threshold in mV, as programmed
+        pack_mv: u16,
+        /// Cell threshold multiplied by the number of cells, in mV
+        cells_mv: u32,
+    },
+    /// The setting is not supported by the driver
+    Unsupported {
+        /// The setting
+        field: ConfigField,
+    },
+}
+
+impl fmt::Display for ConfigViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigViolation::OutOfRange {
+                field,
+                value,
+                min,
+                max,
+            } => write!(f, "{field} {value} is outside the range {min} to {max}"),
+            ConfigViolation::ResolutionLoss {
+                field,
+                requested_mv,
+                programmed_mv,
+            } => write!(
+                f,
+                "{field} {requested_mv} mV would be programmed as {programmed_mv} mV"
+            ),
+            ConfigViolation::Ordering { lower, upper } => {
+                write!(f, "{lower} must be below the {upper}")
+            }
+            ConfigViolation::PackInconsistent {
+                field,
+                pack_mv,
+                cells_mv,
+            } => write!(
+                f,
+                "{field} {pack_mv} mV is beyond the cell threshold sum of {cells_mv} mV"
+            ),
+            ConfigViolation::Unsupported { field } => {
+                write!(f, "{field} is not supported by the driver")
+            }
+        }
+    }
+}
+
+/// Every violation found by [`ConfigBuilder::build`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct ConfigError {
+    violations: [Option<ConfigViolation>; MAX_VIOLATIONS],
+    len: usize,
+}
+
+impl ConfigError {
+    const fn new() -> Self {
+        Self {
+            violations: [None; MAX_VIOLATIONS],
+            len: 0,
+        }
+    }
+
+    const fn push(&mut self, violation: ConfigViolation) {
+        if self.len < MAX_VIOLATIONS {
+            self.violations[self.len] = Some(violation);
+            self.len += 1;
+        }
+    }
+
+    const fn out_of_range(&mut self, field: ConfigField, value: u16, max: u16) {
+        self.push(ConfigViolation::OutOfRange {
+            field,
+            value,
+            min: 0,
+            max,
+        });
+    }
+
+    const fn resolution_loss(&mut self, field: ConfigField, requested_mv: u16, programmed_mv: u16) {
+        self.push(ConfigViolation::ResolutionLoss {
+            field,
+            requested_mv,
+            programmed_mv,
+        });
+    }
+
+    const fn ordering(&mut self, lower: ConfigField, upper: ConfigField) {
+        self.push(ConfigViolation::Ordering { lower, upper });
+    }
+
+    /// Number of violations
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether there are no violations
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Iterate over the violations, in the order they were found
+    pub fn violations(&self) -> impl Iterator<Item = &ConfigViolation> {
+        self.violations.iter().flatten()
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "invalid configuration:")?;
+        for violation in self.violations() {
+            write!(f, "\n  {violation}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for ConfigError {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "invalid configuration:");
+        for violation in self.violations() {
+            defmt::write!(f, "\n  {}", violation);
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ConfigError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::conversions::round_trip_cell_voltage_threshold;
+
+    fn violations(builder: ConfigBuilder) -> ConfigError {
+        match builder.build() {
+            Ok(_) => panic!("expected violations, the configuration was accepted"),
+            Err(error) => error,
+        }
+    }
+
+    fn assert_violations(error: ConfigError, expected: &[ConfigViolation]) {
+        assert_eq!(error.len(), expected.len(), "{error:?}");
+        for (violation, expected) in error.violations().zip(expected) {
+            assert_eq!(violation, expected);
+        }
+    }
+
+    #[test]
+    fn default_configuration_is_valid() {
+        assert!(ConfigBuilder::new().build().is_ok());
+    }
+
+    #[test]
+    fn misordered_thresholds() {
+        let thresholds = VoltageThresholds {
+            cell_over_voltage_threshold_mv: 2986,
+            cell_under_voltage_threshold_mv: 4196,
+            pack_over_voltage_threshold_mv: 7808,
+            pack_under_voltage_threshold_mv: 19520,
+            ..VoltageThresholds::default()
+        };
+        let error = violations(
+            ConfigBuilder::new()
+                .cells(3)
+                .voltage_thresholds(thresholds)
+                .allow_rounding(),
+        );
+        assert_violations(
+            error,
+            &[
+                ConfigViolation::Ordering {
+                    lower: ConfigField::CellUnderVoltage,
+                    upper: ConfigField::CellOverVoltage,
+                },
+                ConfigViolation::Ordering {
+                    lower: ConfigField::CellBalancingUnderVoltageDelta,
+                    upper: ConfigField::CellOverVoltage,
+                },
+                ConfigViolation::Ordering {
+                    lower: ConfigField::PackUnderVoltage,
+                    upper: ConfigField::PackOverVoltage,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn pack_threshold_beyond_cell_sum() {
+        let thresholds = VoltageThresholds {
+            pack_over_voltage_threshold_mv: 19520,
+            ..VoltageThresholds::default()
+        };
+        let error = violations(
+            ConfigBuilder::new()
+                .cells(3)
+                .voltage_thresholds(thresholds)
+                .allow_rounding(),
+        );
+        assert_violations(
+            error,
+            &[ConfigViolation::PackInconsistent {
+                field: ConfigField::PackOverVoltage,
+                pack_mv: 19520,
+                cells_mv: 3 * round_trip_cell_voltage_threshold(4196) as u32,
+            }],
+        );
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let error = violations(ConfigBuilder::new().address(0x80).enables(Enables {
+            crc: true,
+            ..Enables::new()
+        }));
+        assert_violations(
+            error,
+            &[
+                ConfigViolation::OutOfRange {
+                    field: ConfigField::Address,
+                    value: 0x80,
+                    min: 0,
+                    max: 0x7F,
+                },
+                ConfigViolation::Unsupported {
+                    field: ConfigField::Crc,
+                },
+            ],
+        );
+    }
+
+    #[test]
+    fn rounding_is_reported_unless_allowed() {
+        let builder = || {
+            ConfigBuilder::new().voltage_thresholds(VoltageThresholds {
+                cell_over_voltage_threshold_mv: 4200,
+                ..VoltageThresholds::default()
+            })
+        };
+        assert_violations(
+            violations(builder()),
+            &[ConfigViolation::ResolutionLoss {
+                field: ConfigField::CellOverVoltage,
+                requested_mv: 4200,
+                programmed_mv: round_trip_cell_voltage_threshold(4200),
+            }],
+        );
+        assert!(builder().allow_rounding().build().is_ok());
+    }
+
+    #[test]
+    fn thresholds_round_towards_earlier_protection() {
+        let thresholds = VoltageThresholds {
+            cell_over_voltage_threshold_mv: 4210,
+            cell_under_voltage_threshold_mv: 2990,
+            pack_over_voltage_threshold_mv: 20950,
+            pack_under_voltage_threshold_mv: 14550,
+            ..VoltageThresholds::default()
+        };
+        let programmed = thresholds.programmed();
+        assert_eq!(programmed.cell_over_voltage_threshold_mv, 4196);
+        assert_eq!(programmed.cell_under_voltage_threshold_mv, 3006);
+        assert_eq!(programmed.pack_over_voltage_threshold_mv, 20886);
+        assert_eq!(programmed.pack_under_voltage_threshold_mv, 14640);
+
+        let error = violations(ConfigBuilder::new().voltage_thresholds(thresholds));
+        assert!(error.violations().any(|violation| *violation
+            == ConfigViolation::ResolutionLoss {
+                field: ConfigField::CellOverVoltage,
+                requested_mv: 4210,
+                programmed_mv: 4196,
+            }));
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    L9961,
+    registers::{Cfg2Enables, FetConfig, OvCThresholds, PersistentOvCThreshold, SCThreshold},
+};
+
+use super::{CELL_COUNT, Config};
+
+/// Conversions and FET topology of the pack, from which CFG2_ENABLES is derived.
+/// The NTC and coulomb counter are enabled by the `ntc` and `coulomb_counting` features,
+/// and over-current and short circuit protection by [`CurrentProtection`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Enables {
+    /// Number of cells in series, whose voltage conversions are enabled
+    pub cells: u8,
+    /// Whether the battery pack voltage is converted
+    pub pack_voltage: bool,
+    /// Discharge FET configuration
+    pub discharge_fet: FetConfig,
+    /// Charge FET configuration
+    pub charge_fet: FetConfig,
+    /// Whether I2C transfers carry a CRC.
+    /// The driver does not frame its transfers with a CRC yet, so enabling it loses communication with the device,
+    /// and [`ConfigBuilder::build`](super::ConfigBuilder::build) rejects it.
+    pub crc: bool,
+}
+
+impl Enables {
+    /// Enable every cell allowed by the cell count features and the pack voltage, with high-side FETs and no CRC
+    pub const fn new() -> Self {
+        Self {
+            cells: CELL_COUNT,
+            pack_voltage: true,
+            discharge_fet: FetConfig::HighSide,
+            charge_fet: FetConfig::HighSide,
+            crc: false,
+        }
+    }
+}
+
+impl Default for Enables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Over-current and short circuit protection settings.
+/// A protection which is `None` is disabled.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CurrentProtection {
+    /// Over-current protection in charge and discharge
+    pub over_current: Option<OverCurrentProtection>,
+    /// Short circuit protection in discharge
+    pub short_circuit: Option<ShortCircuitProtection>,
+}
+
+impl CurrentProtection {
+    /// Current protection disabled
+    pub const fn disabled() -> Self {
+        Self {
+            over_current: None,
+            short_circuit: None,
+        }
+    }
+}
+
+/// Over-current thresholds, as raw codes which scale with the shunt and CSA gain
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OverCurrentProtection {
+    /// OVC_CHG_TH
+    pub charge_threshold: u8,
+    /// OVC_DCHG_TH
+    pub discharge_threshold: u8,
+    /// PERSIST_OVC_TH, the number of over-current conversions before a persistent over-current fault
+    pub persistence: u8,
+}
+
+/// Short circuit thresholds, as raw 4 bit codes
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ShortCircuitProtection {
+    /// SC_TH
+    pub threshold: u8,
+    /// SC_PERSIST_TH, the number of short circuits before a persistent short circuit fault
+    pub persistence: u8,
+}
+
+impl Config {
+    /// The CFG2_ENABLES value for this configuration.
+    /// The current sense ADC is enabled when the coulomb counter or over-current protection need it.
+    pub const fn cfg2_enables(&self) -> Cfg2Enables {
+        let enables = &self.enables;
+        let coulomb_counter = cfg!(feature = "coulomb_counting");
+        let over_current = self.current_protection.over_current.is_some();
+        Cfg2Enables::builder()
+            .cell_channels(enables.cells)
+            .pack_voltage(enables.pack_voltage)
+            .ntc(cfg!(feature = "ntc"))
+            .current_sense(coulomb_counter || over_current)
+            .coulomb_counter(coulomb_counter)
+            .over_current(over_current)
+            .short_circuit(self.current_protection.short_circuit.is_some())
+            .discharge_fet(enables.discharge_fet)
+            .charge_fet(enables.charge_fet)
+            .crc(enables.crc)
+            .build()
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
+{
+    /// Write the thresholds of the enabled current protections
+    pub async fn apply_current_protection_configuration(&mut self) -> Result<(), I2C::Error> {
+        let protection = self.config.current_protection;
+        if let Some(over_current) = protection.over_current {
+            self.write_ovc_thresholds(OvCThresholds::new(
+                over_current.discharge_threshold,
+                over_current.charge_threshold,
+            ))
+            .await?;
+            self.write_persistent_ovc_thresholds(PersistentOvCThreshold::new(
+                over_current.persistence,
+            ))
+            .await?;
+        }
+        if let Some(short_circuit) = protection.short_circuit {
+            self.write_sc_threshold(SCThreshold::new(
+                short_circuit.threshold,
+                short_circuit.persistence,
+            ))
+            .await?;
+        }
+        Ok(())
+    }
+}
@@ -1,11 +1,12 @@
 use crate::{
-    config::CounterThreshold,
+    L9961,
+    config::{CounterThreshold, NtcSensor, TemperatureLimits},
     conversions::ntc_voltage_code_from_mv,
     registers::{VNTCOTTh, VNTCSevereOTTh, VNTCUTTh},
-    L9961,
 };
 
 /// Temperature threshold configuration struct
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct NtcThresholds {
     /// NTC over temperature threshold in mV
     pub over_temp_threshold_mv: u16,
@@ -28,6 +29,20 @@ impl NtcThresholds {
         }
     }
 
+    /// Create the thresholds for the given temperature limits, as measured through the given NTC.
+    /// The NTC voltage falls as the temperature rises, so the severe over-temperature delta is
+    /// the drop from the over-temperature voltage to the severe over-temperature voltage.
+    pub const fn for_temperatures(limits: TemperatureLimits, sensor: &NtcSensor) -> Self {
+        let over_temp_threshold_mv = sensor.voltage_mv(limits.over_temperature_c);
+        Self {
+            over_temp_threshold_mv,
+            severe_over_temp_delta_threshold_mv: over_temp_threshold_mv
+                .saturating_sub(sensor.voltage_mv(limits.severe_over_temperature_c)),
+            under_temp_threshold_mv: sensor.voltage_mv(limits.under_temperature_c),
+            fault_counter_threshold: limits.fault_counter_threshold,
+        }
+    }
+
     /// Get the ntc over-temperature register value based on this configuration
     pub(crate) fn over_temperature_configuration(&self) -> VNTCOTTh {
         let over_temp_code = ntc_voltage_code_from_mv(self.over_temp_threshold_mv);
@@ -48,6 +63,31 @@ impl NtcThresholds {
     }
 }
 
+impl Default for NtcThresholds {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl core::fmt::Display for NtcThresholds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Ntc Thresholds {{
+over temp threshold mv: {},
+severe over temp delta threshold mv: {},
+under under temp threshold mv: {},
+fault counter threshold: {},
+}}",
+            self.over_temp_threshold_mv,
+            self.severe_over_temp_delta_threshold_mv,
+            self.under_temp_threshold_mv,
+            self.fault_counter_threshold.value(),
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
 impl defmt::Format for NtcThresholds {
     fn format(&self, f: defmt::Formatter) {
         defmt::write!(
@@ -65,11 +105,12 @@ fault counter threshold: {},
         )
     }
 }
-impl<I2C, I, O> L9961<I2C, I, O>
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: embedded_hal_async::i2c::I2c,
-    I: embedded_hal_async::digital::Wait,
-    O: embedded_hal::digital::OutputPin,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
 {
     /// Configure the NTC thresholds
     pub async fn apply_ntc_threshold_configuration(&mut self) -> Result<(), I2C::Error> {
@@ -0,0 +1,906 @@
+//! # Pack configuration files
+//! Loads a complete pack configuration from a TOML file, as used by end-of-line stations programming many pack variants.
+//!
+//! Values are given in engineering units and checked against the range the L9961 can represent.
+//! Every out-of-range or unknown value in the file is reported, rather than only the first.
+//! Voltage thresholds must be programmable exactly, unless `allow_rounding` is set,
+//! in which case each is rounded as described by [`VoltageThresholds::programmed`].
+//!
+//! ```toml
+//! # I2C address, defaults to 0x49
+//! address = 0x49
+//! # Number of cells in series, between 3 and the number enabled by the cell count features
+//! cells = 5
+//! # Round voltage thresholds to their resolution rather than rejecting them, defaults to false
+//! allow_rounding = false
+//!
+//! # Optional, written to the user registers
+//! [identity]
+//! manufacturer_name = "ACME"    # up to 4 ASCII characters
+//! device_name = "P5S1"          # up to 4 ASCII characters
+//! serial_number = 1234
+//! manufacturing_date = 2025-06-01
+//! first_usage_date = 2025-06-01
+//!
+//! # Cell thresholds, 0 to 4977 mV in steps of 19.52 mV
+//! [cell_voltage]
+//! over_voltage_mv = 4196
+//! severe_over_voltage_delta_mv = 97
+//! under_voltage_mv = 2908
+//! severe_under_voltage_delta_mv = 390
+//! balancing_under_voltage_delta_mv = 605
+//! fault_count = 10              # 0 to 15, shared with the pack thresholds, defaults to 10
+//!
+//! # Pack thresholds, 0 to 24888 mV in steps of 97.6 mV
+//! [pack_voltage]
+//! over_voltage_mv = 20886
+//! under_voltage_mv = 14542
+//! max_cell_sum_delta_mv = 488
+//!
+//! # Optional, requires the `ntc` feature. NTC voltages, 0 to 3300 mV in steps of 0.806 mV
+//! [ntc]
+//! over_temperature_mv = 1000
+//! severe_over_temperature_delta_mv = 200
+//! under_temperature_mv = 2800
+//! fault_count = 10              # 0 to 15, defaults to 10
+//!
+//! # Optional, raw threshold codes, which scale with the shunt and CSA gain. Default to 0.
+//! # Only written when the protection is enabled
+//! [current]
+//! over_current_charge = 40      # 0 to 255
+//! over_current_discharge = 60   # 0 to 255
+//! persistent_over_current = 80  # 0 to 255
+//! short_circuit = 4             # 0 to 15
+//! persistent_short_circuit = 6  # 0 to 15
+//!
+//! # Optional, defaults to the values of `Cfg1FiltersCycles::default`
+//! [timing]
+//! cell_filter_us = 4380             # 800, 1310, 4380 or 16670
+//! short_circuit_filter_us = 128     # 32, 64, 128, 256 or 512
+//! current_filter_us = 16900         # 4220, 8440, 16900 or 33800
+//! measurement_cycle_ms = 300        # 10 to 300 in steps of 10
+//!
+//! # Optional. Cell measurements are enabled for the configured number of cells,
+//! # and the NTC and coulomb counter by the `ntc` and `coulomb_counting` features
+//! [enables]
+//! pack_voltage = true           # defaults to true
+//! over_current = true           # defaults to false
+//! short_circuit = true          # defaults to false
+//! crc = false                   # defaults to false, `true` is not supported yet
+//! charge_fet = "high_side"      # "high_side" (default) or "low_side"
+//! discharge_fet = "high_side"   # "high_side" (default) or "low_side"
+//!
+//! # Optional, the field names of the mask registers to set. Default to no masks
+//! [fault_masks]
+//! faultn = ["DIE_OT_FAULTN_MSK"]
+//! prdrv_bal = []
+//! fuse_rst = ["CELL_SEVERE_OV_FUSE_MSK"]
+//! current = []
+//! ```
+
+use core::fmt;
+use std::{path::Path, string::String, vec::Vec};
+
+use serde::Deserialize;
+use toml::value::Datetime;
+
+#[cfg(feature = "ntc")]
+use super::NtcThresholds;
+use super::{
+    CELL_COUNT, Config, ConfigBuilder, ConfigField, ConfigViolation, CounterThreshold,
+    CurrentProtection, Enables, OverCurrentProtection, ShortCircuitProtection, VoltageThresholds,
+};
+#[cfg(feature = "ntc")]
+use crate::conversions::ntc_voltage_mv_from_code;
+use crate::{
+    L9961,
+    conversions::{cell_voltage_threshold_mv_from_code, pack_voltage_threshold_mv_from_code},
+    registers::{
+        Cfg1FiltersCycles, CurrMsk, FetConfig, RegisterInfo, Registers, TCellFilter, TCurFilter,
+        TMeasCycle, TSCFilter, ToFaultnMsk, ToFuseRstMask, ToPrdrvBalMask, regmap::dev_addr,
+    },
+    sbs::{Identity, sbs_date},
+};
+
+/// Supported cell voltage acquisition times, indexed by T_CELL_FILTER code
+const CELL_FILTERS_US: [u32; 4] = [800, 1310, 4380, 16670];
+/// Supported short circuit filter times, indexed by T_SC_FILTER code
+const SC_FILTERS_US: [u32; 5] = [32, 64, 128, 256, 512];
+/// Supported current acquisition times, indexed by T_CUR_FILTER code
+const CURRENT_FILTERS_US: [u32; 4] = [4220, 8440, 16900, 33800];
+/// Dates which can be packed by [`sbs_date`]
+const SBS_YEARS: (u32, u32) = (1980, 2107);
+
+/// A validated pack configuration
+pub struct PackConfiguration {
+    /// Driver configuration, holding the address, thresholds, measurement timing, enables and current protection
+    pub config: Config,
+    /// Register values which are not part of [`Config`]
+    pub registers: PackRegisters,
+}
+
+/// Register values of a pack configuration which are not part of [`Config`].
+/// Written by [`L9961::apply_pack_registers`].
+#[derive(Clone, Copy)]
+pub struct PackRegisters {
+    /// Faults masked from the FAULTN output
+    pub faultn_mask: ToFaultnMsk,
+    /// Faults masked from the FET drivers and balancing
+    pub prdrv_bal_mask: ToPrdrvBalMask,
+    /// Faults masked from the fuse and reset outputs
+    pub fuse_rst_mask: ToFuseRstMask,
+    /// Current faults masked from the FET drivers, fuse and FAULTN outputs
+    pub current_mask: CurrMsk,
+    /// Identity written to the user registers. `chip_id` is read only and is not written.
+    pub identity: Identity,
+}
+
+/// Error loading a pack configuration file
+#[derive(Debug)]
+pub enum PackFileError {
+    /// The file could not be read
+    Io(std::io::Error),
+    /// The file is not valid TOML, or does not follow the schema
+    Parse(toml::de::Error),
+    /// The file follows the schema, but contains values the L9961 cannot represent
+    Invalid(Vec<Violation>),
+}
+
+impl fmt::Display for PackFileError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PackFileError::Io(error) => write!(f, "failed to read pack configuration: {error}"),
+            PackFileError::Parse(error) => write!(f, "invalid pack configuration: {error}"),
+            PackFileError::Invalid(violations) => {
+                write!(f, "invalid pack configuration:")?;
+                for violation in violations {
+                    write!(f, "\n  {violation}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for PackFileError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            PackFileError::Io(error) => Some(error),
+            PackFileError::Parse(error) => Some(error),
+            PackFileError::Invalid(_) => None,
+        }
+    }
+}
+
+/// A value in a pack configuration file which the L9961 cannot represent
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Violation {
+    /// Path of the value in the file, e.g. `cell_voltage.over_voltage_mv`
+    pub field: &'static str,
+    /// What is wrong with the value
+    pub problem: Problem,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.field, self.problem)
+    }
+}
+
+/// The ways a value in a pack configuration file can be invalid
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum Problem {
+    /// The value is outside the range of the register field
+    OutOfRange {
+        /// Value in the file
+        value: u32,
+        /// Smallest accepted value
+        min: u32,
+        /// Largest accepted value
+        max: u32,
+    },
+    /// The value is not one of the discrete settings of the register field
+    Unsupported {
+        /// Value in the file
+        value: u32,
+        /// Accepted values
+        supported: &'static [u32],
+    },
+    /// The value is not a multiple of the resolution of the register field
+    NotAMultiple {
+        /// Value in the file
+        value: u32,
+        /// Resolution of the field
+        step: u32,
+    },
+    /// A fault mask name which is not a field of the mask register
+    UnknownMask(String),
+    /// A name which is longer than 4 characters or not ASCII
+    InvalidName(String),
+    /// A date-time without a date
+    MissingDate,
+    /// A section which requires a feature which is not enabled
+    FeatureDisabled(&'static str),
+    /// The setting is not supported by the driver
+    NotSupported,
+    /// The value fails a check of [`ConfigBuilder::build`],
+    /// e.g. it is inconsistent with the other thresholds or cannot be programmed exactly
+    Inconsistent(ConfigViolation),
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::OutOfRange { value, min, max } => {
+                write!(f, "{value} is outside the range {min} to {max}")
+            }
+            Problem::Unsupported { value, supported } => {
+                write!(f, "{value} is not one of {supported:?}")
+            }
+            Problem::NotAMultiple { value, step } => {
+                write!(f, "{value} is not a multiple of {step}")
+            }
+            Problem::UnknownMask(name) => write!(f, "unknown mask {name}"),
+            Problem::InvalidName(name) => {
+                write!(f, "\"{name}\" is not up to 4 ASCII characters")
+            }
+            Problem::MissingDate => write!(f, "expected a date, e.g. 2025-06-01"),
+            Problem::FeatureDisabled(feature) => {
+                write!(f, "requires the `{feature}` feature")
+            }
+            Problem::NotSupported => write!(f, "not supported by the driver"),
+            Problem::Inconsistent(violation) => write!(f, "{violation}"),
+        }
+    }
+}
+
+impl PackConfiguration {
+    /// Load and validate a pack configuration file
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PackFileError> {
+        let contents = std::fs::read_to_string(path).map_err(PackFileError::Io)?;
+        Self::from_toml(&contents)
+    }
+
+    /// Parse and validate a pack configuration
+    pub fn from_toml(contents: &str) -> Result<Self, PackFileError> {
+        let file: PackFile = toml::from_str(contents).map_err(PackFileError::Parse)?;
+        file.validate().map_err(PackFileError::Invalid)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackFile {
+    #[serde(default = "default_address")]
+    address: u32,
+    cells: u32,
+    #[serde(default)]
+    allow_rounding: bool,
+    #[serde(default)]
+    identity: IdentitySection,
+    cell_voltage: CellVoltageSection,
+    pack_voltage: PackVoltageSection,
+    ntc: Option<NtcSection>,
+    #[serde(default)]
+    current: CurrentSection,
+    #[serde(default)]
+    timing: TimingSection,
+    #[serde(default)]
+    enables: EnablesSection,
+    #[serde(default)]
+    fault_masks: FaultMasksSection,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct IdentitySection {
+    manufacturer_name: Option<String>,
+    device_name: Option<String>,
+    #[serde(default)]
+    serial_number: u32,
+    manufacturing_date: Option<Datetime>,
+    first_usage_date: Option<Datetime>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CellVoltageSection {
+    over_voltage_mv: u32,
+    severe_over_voltage_delta_mv: u32,
+    under_voltage_mv: u32,
+    severe_under_voltage_delta_mv: u32,
+    balancing_under_voltage_delta_mv: u32,
+    #[serde(default = "default_fault_count")]
+    fault_count: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct PackVoltageSection {
+    over_voltage_mv: u32,
+    under_voltage_mv: u32,
+    max_cell_sum_delta_mv: u32,
+}
+
+#[cfg_attr(not(feature = "ntc"), allow(dead_code))]
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NtcSection {
+    over_temperature_mv: u32,
+    severe_over_temperature_delta_mv: u32,
+    under_temperature_mv: u32,
+    #[serde(default = "default_fault_count")]
+    fault_count: u32,
+}
+
+#[derive(Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CurrentSection {
+    #[serde(default)]
+    over_current_charge: u32,
+    #[serde(default)]
+    over_current_discharge: u32,
+    #[serde(default)]
+    persistent_over_current: u32,
+    #[serde(default)]
+    short_circuit: u32,
+    #[serde(default)]
+    persistent_short_circuit: u32,
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct TimingSection {
+    cell_filter_us: u32,
+    short_circuit_filter_us: u32,
+    current_filter_us: u32,
+    measurement_cycle_ms: u32,
+}
+
+impl Default for TimingSection {
+    fn default() -> Self {
+        let defaults = Cfg1FiltersCycles::default();
+        Self {
+            cell_filter_us: CELL_FILTERS_US[defaults.get_t_cell_filter() as usize],
+            short_circuit_filter_us: SC_FILTERS_US[defaults.get_t_sc_filter() as usize],
+            current_filter_us: CURRENT_FILTERS_US[defaults.get_t_curr_filter() as usize],
+            measurement_cycle_ms: defaults.get_t_meas_cycle().period_ms() as u32,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct EnablesSection {
+    pack_voltage: bool,
+    over_current: bool,
+    short_circuit: bool,
+    crc: bool,
+    charge_fet: FetSide,
+    discharge_fet: FetSide,
+}
+
+impl Default for EnablesSection {
+    fn default() -> Self {
+        Self {
+            pack_voltage: true,
+            over_current: false,
+            short_circuit: false,
+            crc: false,
+            charge_fet: FetSide::HighSide,
+            discharge_fet: FetSide::HighSide,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum FetSide {
+    HighSide,
+    LowSide,
+}
+
+impl From<FetSide> for FetConfig {
+    fn from(side: FetSide) -> Self {
+        match side {
+            FetSide::HighSide => FetConfig::HighSide,
+            FetSide::LowSide => FetConfig::LowSide,
+        }
+    }
+}
+
+#[derive(Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct FaultMasksSection {
+    faultn: Vec<String>,
+    prdrv_bal: Vec<String>,
+    fuse_rst: Vec<String>,
+    current: Vec<String>,
+}
+
+fn default_address() -> u32 {
+    Config::default().address as u32
+}
+
+fn default_fault_count() -> u32 {
+    CounterThreshold::default().value() as u32
+}
+
+/// Collects the violations found while converting the file
+#[derive(Default)]
+struct Validator {
+    violations: Vec<Violation>,
+}
+
+impl Validator {
+    fn report(&mut self, field: &'static str, problem: Problem) {
+        self.violations.push(Violation { field, problem });
+    }
+
+    /// Check that a value is within `min..=max`, returning it clamped so conversion can continue
+    fn range(&mut self, field: &'static str, value: u32, min: u32, max: u32) -> u32 {
+        if !(min..=max).contains(&value) {
+            self.report(field, Problem::OutOfRange { value, min, max });
+        }
+        value.clamp(min, max)
+    }
+
+    fn cell_mv(&mut self, field: &'static str, value: u32) -> u16 {
+        self.range(
+            field,
+            value,
+            0,
+            cell_voltage_threshold_mv_from_code(u8::MAX) as u32,
+        ) as u16
+    }
+
+    fn pack_mv(&mut self, field: &'static str, value: u32) -> u16 {
+        self.range(
+            field,
+            value,
+            0,
+            pack_voltage_threshold_mv_from_code(u8::MAX) as u32,
+        ) as u16
+    }
+
+    #[cfg(feature = "ntc")]
+    fn ntc_mv(&mut self, field: &'static str, value: u32) -> u16 {
+        self.range(field, value, 0, ntc_voltage_mv_from_code(0x0FFF) as u32) as u16
+    }
+
+    fn counter(&mut self, field: &'static str, value: u32) -> CounterThreshold {
+        CounterThreshold::new(self.range(field, value, 0, 15) as u8)
+    }
+
+    /// Find the register code of one of a field's discrete settings
+    fn setting(&mut self, field: &'static str, value: u32, supported: &'static [u32]) -> u16 {
+        match supported.iter().position(|setting| *setting == value) {
+            Some(code) => code as u16,
+            None => {
+                self.report(field, Problem::Unsupported { value, supported });
+                0
+            }
+        }
+    }
+
+    /// Pack up to 4 ASCII characters, first character in the most significant byte
+    fn name(&mut self, field: &'static str, name: Option<&str>) -> u32 {
+        let Some(name) = name else {
+            return 0;
+        };
+        if name.len() > 4 || !name.is_ascii() {
+            self.report(field, Problem::InvalidName(name.into()));
+            return 0;
+        }
+        let mut bytes = [0; 4];
+        bytes[..name.len()].copy_from_slice(name.as_bytes());
+        u32::from_be_bytes(bytes)
+    }
+
+    fn date(&mut self, field: &'static str, date: Option<Datetime>) -> u16 {
+        let Some(date) = date else {
+            return 0;
+        };
+        let Some(date) = date.date else {
+            self.report(field, Problem::MissingDate);
+            return 0;
+        };
+        let year = self.range(field, date.year as u32, SBS_YEARS.0, SBS_YEARS.1);
+        // A year out of range has been reported already
+        sbs_date(year as u16, date.month, date.day).unwrap_or(0)
+    }
+
+    /// Combine the named fields of a mask register
+    fn mask(&mut self, field: &'static str, register: Registers, names: &[String]) -> u16 {
+        let info = RegisterInfo::of(register);
+        names.iter().fold(0, |mask, name| match info.field(name) {
+            Some(flag) => mask | flag.mask(),
+            None => {
+                self.report(field, Problem::UnknownMask(name.clone()));
+                mask
+            }
+        })
+    }
+}
+
+impl PackFile {
+    fn validate(self) -> Result<PackConfiguration, Vec<Violation>> {
+        let mut check = Validator::default();
+
+        let address = check.range(
+            "address",
+            self.address,
+            0,
+            dev_addr::DEV_ADDR_ID.mask() as u32,
+        );
+        let cells = check.range("cells", self.cells, 3, CELL_COUNT as u32) as u8;
+
+        let cell = &self.cell_voltage;
+        let pack = &self.pack_voltage;
+        let voltage_thresholds = VoltageThresholds {
+            cell_over_voltage_threshold_mv: check
+                .cell_mv("cell_voltage.over_voltage_mv", cell.over_voltage_mv),
+            cell_severe_over_voltage_delta_threshold_mv: check.cell_mv(
+                "cell_voltage.severe_over_voltage_delta_mv",
+                cell.severe_over_voltage_delta_mv,
+            ),
+            cell_under_voltage_threshold_mv: check
+                .cell_mv("cell_voltage.under_voltage_mv", cell.under_voltage_mv),
+            cell_severe_under_voltage_delta_threshold_mv: check.cell_mv(
+                "cell_voltage.severe_under_voltage_delta_mv",
+                cell.severe_under_voltage_delta_mv,
+            ),
+            cell_balancing_under_voltage_delta_threshold_mv: check.cell_mv(
+                "cell_voltage.balancing_under_voltage_delta_mv",
+                cell.balancing_under_voltage_delta_mv,
+            ),
+            max_pack_cell_sum_delta_mv: check.pack_mv(
+                "pack_voltage.max_cell_sum_delta_mv",
+                pack.max_cell_sum_delta_mv,
+            ),
+            pack_over_voltage_threshold_mv: check
+                .pack_mv("pack_voltage.over_voltage_mv", pack.over_voltage_mv),
+            pack_under_voltage_threshold_mv: check
+                .pack_mv("pack_voltage.under_voltage_mv", pack.under_voltage_mv),
+            fault_counter_threshold: check.counter("cell_voltage.fault_count", cell.fault_count),
+        };
+
+        #[cfg(feature = "ntc")]
+        let ntc_thresholds = match &self.ntc {
+            Some(ntc) => NtcThresholds {
+                over_temp_threshold_mv: check
+                    .ntc_mv("ntc.over_temperature_mv", ntc.over_temperature_mv),
+                severe_over_temp_delta_threshold_mv: check.ntc_mv(
+                    "ntc.severe_over_temperature_delta_mv",
+                    ntc.severe_over_temperature_delta_mv,
+                ),
+                under_temp_threshold_mv: check
+                    .ntc_mv("ntc.under_temperature_mv", ntc.under_temperature_mv),
+                fault_counter_threshold: check.counter("ntc.fault_count", ntc.fault_count),
+            },
+            None => NtcThresholds::new(),
+        };
+        #[cfg(not(feature = "ntc"))]
+        if self.ntc.is_some() {
+            check.report("ntc", Problem::FeatureDisabled("ntc"));
+        }
+
+        let timing = &self.timing;
+        let cell_filter = check.setting(
+            "timing.cell_filter_us",
+            timing.cell_filter_us,
+            &CELL_FILTERS_US,
+        );
+        let sc_filter = check.setting(
+            "timing.short_circuit_filter_us",
+            timing.short_circuit_filter_us,
+            &SC_FILTERS_US,
+        );
+        let current_filter = check.setting(
+            "timing.current_filter_us",
+            timing.current_filter_us,
+            &CURRENT_FILTERS_US,
+        );
+        let cycle_ms = check.range(
+            "timing.measurement_cycle_ms",
+            timing.measurement_cycle_ms,
+            10,
+            300,
+        );
+        if cycle_ms % 10 != 0 {
+            check.report(
+                "timing.measurement_cycle_ms",
+                Problem::NotAMultiple {
+                    value: cycle_ms,
+                    step: 10,
+                },
+            );
+        }
+        let measurement_cycles = Cfg1FiltersCycles::new(
+            TCellFilter::from(cell_filter),
+            TSCFilter::from(sc_filter),
+            TCurFilter::from(current_filter),
+            TMeasCycle::new_ms(cycle_ms as u16 / 10 * 10),
+        );
+
+        let enables = &self.enables;
+        let current = &self.current;
+        let over_current = OverCurrentProtection {
+            charge_threshold: check.range(
+                "current.over_current_charge",
+                current.over_current_charge,
+                0,
+                0xFF,
+            ) as u8,
+            discharge_threshold: check.range(
+                "current.over_current_discharge",
+                current.over_current_discharge,
+                0,
+                0xFF,
+            ) as u8,
+            persistence: check.range(
+                "current.persistent_over_current",
+                current.persistent_over_current,
+                0,
+                0xFF,
+            ) as u8,
+        };
+        let short_circuit = ShortCircuitProtection {
+            threshold: check.range("current.short_circuit", current.short_circuit, 0, 0xF) as u8,
+            persistence: check.range(
+                "current.persistent_short_circuit",
+                current.persistent_short_circuit,
+                0,
+                0xF,
+            ) as u8,
+        };
+        let current_protection = CurrentProtection {
+            over_current: enables.over_current.then_some(over_current),
+            short_circuit: enables.short_circuit.then_some(short_circuit),
+        };
+        // The driver does not frame its transfers with a CRC, so it would lose the device once CRC_EN is set
+        if enables.crc {
+            check.report("enables.crc", Problem::NotSupported);
+        }
+        let enables = Enables {
+            cells,
+            pack_voltage: enables.pack_voltage,
+            discharge_fet: enables.discharge_fet.into(),
+            charge_fet: enables.charge_fet.into(),
+            crc: false,
+        };
+
+        let masks = &self.fault_masks;
+        let faultn_mask = ToFaultnMsk::from_bits_truncate(check.mask(
+            "fault_masks.faultn",
+            Registers::ToFaultnMsk,
+            &masks.faultn,
+        ));
+        let prdrv_bal_mask = ToPrdrvBalMask::from_bits_truncate(check.mask(
+            "fault_masks.prdrv_bal",
+            Registers::ToPrdrvBalMask,
+            &masks.prdrv_bal,
+        ));
+        let fuse_rst_mask = ToFuseRstMask::from_bits_truncate(check.mask(
+            "fault_masks.fuse_rst",
+            Registers::ToFuseRstMask,
+            &masks.fuse_rst,
+        ));
+        let current_mask = CurrMsk::from_bits_truncate(check.mask(
+            "fault_masks.current",
+            Registers::CurrMsk,
+            &masks.current,
+        ));
+
+        let identity = &self.identity;
+        let identity = Identity {
+            chip_id: 0,
+            manufacturer_name: check.name(
+                "identity.manufacturer_name",
+                identity.manufacturer_name.as_deref(),
+            ),
+            manufacturing_date: check
+                .date("identity.manufacturing_date", identity.manufacturing_date),
+            first_usage_date: check.date("identity.first_usage_date", identity.first_usage_date),
+            serial_number: identity.serial_number,
+            device_name: check.name("identity.device_name", identity.device_name.as_deref()),
+        };
+
+        let builder = ConfigBuilder::new()
+            .address(address as u8)
+            .enables(enables)
+            .current_protection(current_protection)
+            .voltage_thresholds(voltage_thresholds)
+            .measurement_cycles(measurement_cycles);
+        let builder = match self.allow_rounding {
+            true => builder.allow_rounding(),
+            false => builder,
+        };
+        #[cfg(feature = "ntc")]
+        let builder = builder.ntc_thresholds(ntc_thresholds);
+        let config = match builder.build() {
+            Ok(config) => Some(config),
+            Err(error) => {
+                for violation in error.violations() {
+                    let field = match violation {
+                        ConfigViolation::OutOfRange { field, .. }
+                        | ConfigViolation::ResolutionLoss { field, .. }
+                        | ConfigViolation::PackInconsistent { field, .. }
+                        | ConfigViolation::Unsupported { field }
+                        | ConfigViolation::Ordering { lower: field, .. } => *field,
+                    };
+                    check.report(file_field(field), Problem::Inconsistent(*violation));
+                }
+                None
+            }
+        };
+
+        match config {
+            Some(config) if check.violations.is_empty() => Ok(PackConfiguration {
+                config,
+                registers: PackRegisters {
+                    faultn_mask,
+                    prdrv_bal_mask,
+                    fuse_rst_mask,
+                    current_mask,
+                    identity,
+                },
+            }),
+            _ => Err(check.violations),
+        }
+    }
+}
+
+/// Path in the file of a setting checked by [`ConfigBuilder`]
+fn file_field(field: ConfigField) -> &'static str {
+    match field {
+        ConfigField::Address => "address",
+        ConfigField::Cells => "cells",
+        ConfigField::Crc => "enables.crc",
+        ConfigField::CellOverVoltage => "cell_voltage.over_voltage_mv",
+        ConfigField::CellSevereOverVoltageDelta => "cell_voltage.severe_over_voltage_delta_mv",
+        ConfigField::CellUnderVoltage => "cell_voltage.under_voltage_mv",
+        ConfigField::CellSevereUnderVoltageDelta => "cell_voltage.severe_under_voltage_delta_mv",
+        ConfigField::CellBalancingUnderVoltageDelta => {
+            "cell_voltage.balancing_under_voltage_delta_mv"
+        }
+        ConfigField::PackOverVoltage => "pack_voltage.over_voltage_mv",
+        ConfigField::PackUnderVoltage => "pack_voltage.under_voltage_mv",
+        ConfigField::MaxPackCellSumDelta => "pack_voltage.max_cell_sum_delta_mv",
+        ConfigField::NtcOverTemperature => "ntc.over_temperature_mv",
+        ConfigField::NtcSevereOverTemperatureDelta => "ntc.severe_over_temperature_delta_mv",
+        ConfigField::NtcUnderTemperature => "ntc.under_temperature_mv",
+        ConfigField::ShortCircuitThreshold => "current.short_circuit",
+        ConfigField::ShortCircuitPersistence => "current.persistent_short_circuit",
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
+{
+    /// Write the registers of a pack configuration which are not covered by [`L9961::apply_config`]
+    pub async fn apply_pack_registers(
+        &mut self,
+        registers: &PackRegisters,
+    ) -> Result<(), I2C::Error> {
+        self.write_to_faultn_msk(registers.faultn_mask).await?;
+        self.write_to_prdrv_bal_mask(registers.prdrv_bal_mask)
+            .await?;
+        self.write_to_fuse_rst_msk(registers.fuse_rst_mask).await?;
+        self.write_curr_msk(registers.current_mask).await?;
+
+        let identity = &registers.identity;
+        self.write_manufacturer_name_msb((identity.manufacturer_name >> 16) as u16)
+            .await?;
+        self.write_manufacturer_name_lsb(identity.manufacturer_name as u16)
+            .await?;
+        self.write_manufacturing_date(identity.manufacturing_date)
+            .await?;
+        self.write_first_usage_date(identity.first_usage_date)
+            .await?;
+        self.write_serial_number_msb((identity.serial_number >> 16) as u16)
+            .await?;
+        self.write_serial_number_lsb(identity.serial_number as u16)
+            .await?;
+        self.write_device_name_msb((identity.device_name >> 16) as u16)
+            .await?;
+        self.write_device_name_lsb(identity.device_name as u16)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The TOML example of the module documentation
+    fn documented_example() -> String {
+        include_str!("pack_file.rs")
+            .lines()
+            .skip_while(|line| *line != "//! ```toml")
+            .skip(1)
+            .take_while(|line| *line != "//! ```")
+            .map(|line| line.trim_start_matches("//!").trim_start())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn violations(contents: &str) -> Vec<Violation> {
+        match PackConfiguration::from_toml(contents) {
+            Err(PackFileError::Invalid(violations)) => violations,
+            Err(error) => panic!("expected violations, got {error}"),
+            Ok(_) => panic!("expected violations, the configuration was accepted"),
+        }
+    }
+
+    #[cfg(all(feature = "5_cells", feature = "ntc"))]
+    #[test]
+    fn documented_example_loads() {
+        let pack = PackConfiguration::from_toml(&documented_example()).unwrap();
+        assert_eq!(pack.config.address, 0x49);
+        assert!(pack.config.enables.pack_voltage);
+        assert!(!pack.config.enables.crc);
+        assert_eq!(pack.registers.faultn_mask, ToFaultnMsk::DIE_OT_FAULTN_MSK);
+        assert_eq!(
+            pack.registers.fuse_rst_mask,
+            ToFuseRstMask::CELL_SEVERE_OV_FUSE_MSK
+        );
+        assert_eq!(pack.registers.identity.serial_number, 1234);
+    }
+
+    #[test]
+    fn crc_enable_is_rejected() {
+        let contents = documented_example().replace("crc = false", "crc = true");
+        assert!(violations(&contents).contains(&Violation {
+            field: "enables.crc",
+            problem: Problem::NotSupported,
+        }));
+    }
+
+    #[test]
+    fn every_violation_is_reported() {
+        let contents = documented_example()
+            .replace("over_voltage_mv = 4196", "over_voltage_mv = 6000")
+            .replace("measurement_cycle_ms = 300", "measurement_cycle_ms = 15")
+            .replace("\"DIE_OT_FAULTN_MSK\"", "\"NOT_A_MASK\"");
+        let fields = violations(&contents)
+            .iter()
+            .map(|violation| violation.field)
+            .collect::<Vec<_>>();
+        assert!(fields.contains(&"cell_voltage.over_voltage_mv"));
+        assert!(fields.contains(&"timing.measurement_cycle_ms"));
+        assert!(fields.contains(&"fault_masks.faultn"));
+    }
+
+    #[test]
+    fn unknown_keys_are_rejected() {
+        let contents = documented_example().replace("cells = 5", "cells = 5\ncolour = \"red\"");
+        assert!(matches!(
+            PackConfiguration::from_toml(&contents),
+            Err(PackFileError::Parse(_))
+        ));
+    }
+
+    #[test]
+    fn rounding_is_rejected_unless_allowed() {
+        let contents =
+            documented_example().replace("over_voltage_mv = 4196", "over_voltage_mv = 4210");
+        assert!(violations(&contents).contains(&Violation {
+            field: "cell_voltage.over_voltage_mv",
+            problem: Problem::Inconsistent(ConfigViolation::ResolutionLoss {
+                field: ConfigField::CellOverVoltage,
+                requested_mv: 4210,
+                programmed_mv: 4196,
+            }),
+        }));
+
+        let contents = contents.replace("allow_rounding = false", "allow_rounding = true");
+        let pack = PackConfiguration::from_toml(&contents).unwrap();
+        let programmed = pack.config.voltage_thresholds.programmed();
+        assert_eq!(programmed.cell_over_voltage_threshold_mv, 4196);
+    }
+}
@@ -0,0 +1,231 @@
+//! # Chemistry presets
+//! Starting points for the voltage and temperature thresholds of common lithium-ion cell chemistries.
+//!
+//! The limits are typical of manufacturer datasheets for each chemistry.
+//! [`VoltageThresholds::for_chemistry`](super::VoltageThresholds::for_chemistry) rounds them to values the L9961 can program exactly,
+//! so the presets build without [`ConfigBuilder::allow_rounding`](super::ConfigBuilder::allow_rounding).
+//! They are not a substitute for the datasheet of the cells in a pack, which should always be checked.
+//!
+//! ```
+//! use l9961::config::{Chemistry, VoltageThresholds};
+//!
+//! const THRESHOLDS: VoltageThresholds = VoltageThresholds::for_chemistry(Chemistry::Lfp, 4);
+//! assert_eq!(THRESHOLDS.cell_over_voltage_threshold_mv, 3650);
+//! ```
+
+use super::CounterThreshold;
+
+/// Voltage counter recommended for every chemistry.
+/// At the default 300 ms measurement cycle a voltage fault must persist for 1.5 s, which rides through load transients.
+const VOLTAGE_FAULT_COUNTER: CounterThreshold = CounterThreshold::new(5);
+/// Temperature counter recommended for every temperature range.
+/// Temperatures change slowly, so a longer persistence filters NTC noise without delaying protection.
+const TEMPERATURE_FAULT_COUNTER: CounterThreshold = CounterThreshold::new(10);
+
+/// Lithium-ion cell chemistries with preset thresholds
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum Chemistry {
+    /// Lithium nickel manganese cobalt oxide, 3.6 V nominal
+    Nmc,
+    /// Lithium iron phosphate, 3.2 V nominal
+    Lfp,
+    /// Lithium titanate, 2.4 V nominal
+    Lto,
+    /// Lithium manganese oxide, 3.7 V nominal
+    Lmo,
+}
+
+/// Per-cell voltage limits of a chemistry, in mV
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CellLimits {
+    /// Voltage above which charging must stop
+    pub over_voltage_mv: u16,
+    /// Voltage above which the cell is expected to be damaged
+    pub severe_over_voltage_mv: u16,
+    /// Voltage below which discharging must stop
+    pub under_voltage_mv: u16,
+    /// Voltage below which the cell is expected to be damaged
+    pub severe_under_voltage_mv: u16,
+    /// Voltage below which the cell is not balanced
+    pub balancing_min_mv: u16,
+    /// Recommended number of measurement cycles a voltage fault must persist
+    pub fault_counter_threshold: CounterThreshold,
+}
+
+impl Chemistry {
+    /// Nominal per-cell voltage limits of the chemistry
+    pub const fn cell_limits(self) -> CellLimits {
+        match self {
+            Chemistry::Nmc => CellLimits {
+                over_voltage_mv: 4200,
+                severe_over_voltage_mv: 4300,
+                under_voltage_mv: 3000,
+                severe_under_voltage_mv: 2500,
+                balancing_min_mv: 3500,
+                fault_counter_threshold: VOLTAGE_FAULT_COUNTER,
+            },
+            Chemistry::Lfp => CellLimits {
+                over_voltage_mv: 3650,
+                severe_over_voltage_mv: 3800,
+                under_voltage_mv: 2500,
+                severe_under_voltage_mv: 2000,
+                balancing_min_mv: 3300,
+                fault_counter_threshold: VOLTAGE_FAULT_COUNTER,
+            },
+            Chemistry::Lto => CellLimits {
+                over_voltage_mv: 2800,
+                severe_over_voltage_mv: 2900,
+                under_voltage_mv: 1800,
+                severe_under_voltage_mv: 1500,
+                balancing_min_mv: 2300,
+                fault_counter_threshold: VOLTAGE_FAULT_COUNTER,
+            },
+            Chemistry::Lmo => CellLimits {
+                over_voltage_mv: 4200,
+                severe_over_voltage_mv: 4300,
+                under_voltage_mv: 2800,
+                severe_under_voltage_mv: 2500,
+                balancing_min_mv: 3600,
+                fault_counter_threshold: VOLTAGE_FAULT_COUNTER,
+            },
+        }
+    }
+
+    /// Temperatures within which the chemistry may be charged
+    pub const fn charge_temperature_limits(self) -> TemperatureLimits {
+        match self {
+            Chemistry::Nmc | Chemistry::Lmo => TemperatureLimits::CHARGE,
+            Chemistry::Lfp => TemperatureLimits {
+                over_temperature_c: 55,
+                ..TemperatureLimits::CHARGE
+            },
+            Chemistry::Lto => TemperatureLimits {
+                under_temperature_c: -20,
+                over_temperature_c: 55,
+                ..TemperatureLimits::CHARGE
+            },
+        }
+    }
+
+    /// Temperatures within which the chemistry may be discharged
+    pub const fn discharge_temperature_limits(self) -> TemperatureLimits {
+        match self {
+            Chemistry::Nmc | Chemistry::Lmo | Chemistry::Lfp => TemperatureLimits::DISCHARGE,
+            Chemistry::Lto => TemperatureLimits {
+                under_temperature_c: -30,
+                ..TemperatureLimits::DISCHARGE
+            },
+        }
+    }
+}
+
+/// Temperature limits in °C, as monitored through the NTC
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureLimits {
+    /// Temperature above which the pack is over-temperature
+    pub over_temperature_c: i8,
+    /// Temperature above which the pack is severely over-temperature
+    pub severe_over_temperature_c: i8,
+    /// Temperature below which the pack is under-temperature
+    pub under_temperature_c: i8,
+    /// Recommended number of measurement cycles a temperature fault must persist
+    pub fault_counter_threshold: CounterThreshold,
+}
+
+impl TemperatureLimits {
+    /// Typical charging range of lithium-ion cells, which must not be charged below freezing
+    pub const CHARGE: Self = Self {
+        over_temperature_c: 45,
+        severe_over_temperature_c: 60,
+        under_temperature_c: 0,
+        fault_counter_threshold: TEMPERATURE_FAULT_COUNTER,
+    };
+
+    /// Typical discharging range of lithium-ion cells
+    pub const DISCHARGE: Self = Self {
+        over_temperature_c: 60,
+        severe_over_temperature_c: 75,
+        under_temperature_c: -20,
+        fault_counter_threshold: TEMPERATURE_FAULT_COUNTER,
+    };
+
+    /// Industrial ambient range, for packs whose cells are rated beyond the typical discharge range
+    pub const INDUSTRIAL: Self = Self {
+        over_temperature_c: 85,
+        severe_over_temperature_c: 100,
+        under_temperature_c: -40,
+        fault_counter_threshold: TEMPERATURE_FAULT_COUNTER,
+    };
+}
+
+/// An NTC thermistor biased through a fixed resistor, with the NTC between the NTC pin and ground.
+/// The NTC voltage falls as the temperature rises.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NtcSensor {
+    /// Resistance of the NTC at 25 °C in Ω
+    pub r25_ohm: u32,
+    /// B constant of the NTC in K
+    pub beta_k: u16,
+    /// Resistance of the bias resistor in Ω
+    pub bias_resistor_ohm: u32,
+    /// Voltage the bias resistor is connected to in mV
+    pub bias_mv: u16,
+}
+
+impl NtcSensor {
+    /// A 10 kΩ, B = 3435 K NTC biased through 10 kΩ from 3.3 V
+    pub const NTC_10K_B3435: Self = Self::new(10_000, 3435, 10_000);
+
+    /// A 10 kΩ, B = 3950 K NTC biased through 10 kΩ from 3.3 V
+    pub const NTC_10K_B3950: Self = Self::new(10_000, 3950, 10_000);
+
+    /// Create an NTC sensor biased from 3.3 V, the full scale of the NTC measurement
+    pub const fn new(r25_ohm: u32, beta_k: u16, bias_resistor_ohm: u32) -> Self {
+        Self {
+            r25_ohm,
+            beta_k,
+            bias_resistor_ohm,
+            bias_mv: 3300,
+        }
+    }
+
+    /// Expected NTC voltage in mV at a temperature in °C, using the B parameter equation
+    pub const fn voltage_mv(&self, temperature_c: i8) -> u16 {
+        const KELVIN_AT_25_C: f64 = 298.15;
+        let kelvin = temperature_c as f64 + 273.15;
+        let exponent = self.beta_k as f64 * (1.0 / kelvin - 1.0 / KELVIN_AT_25_C);
+        let ntc_ohm = self.r25_ohm as f64 * exp(exponent);
+        let voltage_mv = self.bias_mv as f64 * ntc_ohm / (ntc_ohm + self.bias_resistor_ohm as f64);
+        (voltage_mv + 0.5) as u16
+    }
+}
+
+/// `f64::exp` is not available in `core`, or in `const` contexts.
+/// Halving the argument until it is small makes a short Taylor series accurate,
+/// and squaring the result undoes the halving.
+const fn exp(x: f64) -> f64 {
+    const HALVINGS: u32 = 8;
+    let reduced = x / (1u32 << HALVINGS) as f64;
+    let mut sum = 1.0;
+    let mut term = 1.0;
+    let mut n = 1;
+    while n < 10 {
+        term = term * reduced / n as f64;
+        sum += term;
+        n += 1;
+    }
+    let mut i = 0;
+    while i < HALVINGS {
+        sum *= sum;
+        i += 1;
+    }
+    sum
+}
@@ -0,0 +1,271 @@
+//! # Protection timing
+//! Worst-case time-to-detect and time-to-react of each protection, from the filters and measurement cycle,
+//! and the fault counter and current protection persistence thresholds of a configuration.
+//!
+//! The figures follow this model of the device, which should be stated alongside them in a safety case:
+//! - Voltage and temperature channels are converted once per measurement cycle,
+//!   each conversion taking the cell filter time.
+//!   A fault arising just after its channel was converted is first seen by the next conversion,
+//!   so a counter threshold of N is detected N measurement cycles and one conversion later.
+//! - Current is converted continuously, one conversion per current filter time.
+//!   A fault arising just after a conversion started may be averaged out of it,
+//!   so an over-current is detected after at most two conversions, and a persistent over-current
+//!   with a persistence threshold of N after N + 1 conversions.
+//! - Short circuits are detected by a comparator after the short circuit filter time,
+//!   and a persistent short circuit with a persistence threshold of N after N filter times.
+//! - Counter and persistence thresholds of 0 behave as 1.
+//! - Reacting adds the time taken to turn the FETs off, which depends on the FETs and gate drive of the pack.
+//!   Cell balancing under-voltage only stops balancing, so has no FET reaction time.
+//!   The pre-driver masks are not part of the configuration, so the figures assume every fault is unmasked.
+//!
+//! ```
+//! use l9961::config::{Config, Protection};
+//!
+//! let timing = Config::default().protection_timing().fet_turn_off_us(50);
+//! let over_voltage = timing.timing(Protection::CellOverVoltage).unwrap();
+//! // 10 measurement cycles of 300 ms, and one 4.38 ms conversion
+//! assert_eq!(over_voltage.detect_us, 3_004_380);
+//! assert_eq!(over_voltage.react_us, Some(3_004_430));
+//! ```
+
+use core::fmt::{Display, Formatter};
+
+use crate::registers::Cfg1FiltersCycles;
+
+use super::{Config, CounterThreshold, CurrentProtection};
+
+/// Protections of the L9961
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Protection {
+    /// Cell over-voltage
+    CellOverVoltage,
+    /// Cell severe over-voltage
+    CellSevereOverVoltage,
+    /// Cell under-voltage
+    CellUnderVoltage,
+    /// Cell severe under-voltage
+    CellSevereUnderVoltage,
+    /// Cell too low to balance
+    CellBalancingUnderVoltage,
+    /// Pack over-voltage
+    PackOverVoltage,
+    /// Pack under-voltage
+    PackUnderVoltage,
+    /// Mismatch between the pack voltage and the sum of the cell voltages
+    PackCellSumMismatch,
+    /// NTC over-temperature
+    #[cfg(feature = "ntc")]
+    NtcOverTemperature,
+    /// NTC severe over-temperature
+    #[cfg(feature = "ntc")]
+    NtcSevereOverTemperature,
+    /// NTC under-temperature
+    #[cfg(feature = "ntc")]
+    NtcUnderTemperature,
+    /// Over-current in charge
+    OverCurrentCharge,
+    /// Over-current in discharge
+    OverCurrentDischarge,
+    /// Persistent over-current in charge
+    PersistentOverCurrentCharge,
+    /// Persistent over-current in discharge
+    PersistentOverCurrentDischarge,
+    /// Short circuit in discharge
+    ShortCircuitDischarge,
+    /// Persistent short circuit in discharge
+    PersistentShortCircuitDischarge,
+}
+
+impl Protection {
+    /// Every protection enabled by the crate features
+    pub const ALL: &'static [Protection] = &[
+        Protection::CellOverVoltage,
+        Protection::CellSevereOverVoltage,
+        Protection::CellUnderVoltage,
+        Protection::CellSevereUnderVoltage,
+        Protection::CellBalancingUnderVoltage,
+        Protection::PackOverVoltage,
+        Protection::PackUnderVoltage,
+        Protection::PackCellSumMismatch,
+        #[cfg(feature = "ntc")]
+        Protection::NtcOverTemperature,
+        #[cfg(feature = "ntc")]
+        Protection::NtcSevereOverTemperature,
+        #[cfg(feature = "ntc")]
+        Protection::NtcUnderTemperature,
+        Protection::OverCurrentCharge,
+        Protection::OverCurrentDischarge,
+        Protection::PersistentOverCurrentCharge,
+        Protection::PersistentOverCurrentDischarge,
+        Protection::ShortCircuitDischarge,
+        Protection::PersistentShortCircuitDischarge,
+    ];
+}
+
+impl Display for Protection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Protection::CellOverVoltage => "cell over-voltage",
+            Protection::CellSevereOverVoltage => "cell severe over-voltage",
+            Protection::CellUnderVoltage => "cell under-voltage",
+            Protection::CellSevereUnderVoltage => "cell severe under-voltage",
+            Protection::CellBalancingUnderVoltage => "cell balancing under-voltage",
+            Protection::PackOverVoltage => "pack over-voltage",
+            Protection::PackUnderVoltage => "pack under-voltage",
+            Protection::PackCellSumMismatch => "pack and cell sum mismatch",
+            #[cfg(feature = "ntc")]
+            Protection::NtcOverTemperature => "NTC over-temperature",
+            #[cfg(feature = "ntc")]
+            Protection::NtcSevereOverTemperature => "NTC severe over-temperature",
+            #[cfg(feature = "ntc")]
+            Protection::NtcUnderTemperature => "NTC under-temperature",
+            Protection::OverCurrentCharge => "over-current in charge",
+            Protection::OverCurrentDischarge => "over-current in discharge",
+            Protection::PersistentOverCurrentCharge => "persistent over-current in charge",
+            Protection::PersistentOverCurrentDischarge => "persistent over-current in discharge",
+            Protection::ShortCircuitDischarge => "short circuit in discharge",
+            Protection::PersistentShortCircuitDischarge => "persistent short circuit in discharge",
+        })
+    }
+}
+
+/// Worst-case timing of a protection in us
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ProtectionTiming {
+    /// Time from the fault arising to the fault being flagged
+    pub detect_us: u32,
+    /// Time from the fault arising to the FETs being off,
+    /// or `None` if the protection does not turn the FETs off
+    pub react_us: Option<u32>,
+}
+
+impl Display for ProtectionTiming {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(f, "detect within {} us", self.detect_us)?;
+        match self.react_us {
+            Some(react_us) => write!(f, ", react within {} us", react_us),
+            None => write!(f, ", no FET reaction"),
+        }
+    }
+}
+
+/// Calculates the worst-case timing of each protection, see the [module documentation](self) for the model used
+#[derive(Clone, Copy)]
+pub struct TimingCalculator {
+    measurement_cycles: Cfg1FiltersCycles,
+    voltage_counter: CounterThreshold,
+    #[cfg(feature = "ntc")]
+    ntc_counter: CounterThreshold,
+    current_protection: CurrentProtection,
+    fet_turn_off_us: u32,
+}
+
+impl TimingCalculator {
+    /// Create a calculator for the filters, counters and current protection of a configuration,
+    /// with FETs which turn off instantly
+    pub const fn new(config: &Config) -> Self {
+        Self {
+            measurement_cycles: config.measurement_cycles,
+            voltage_counter: config.voltage_thresholds.fault_counter_threshold,
+            #[cfg(feature = "ntc")]
+            ntc_counter: config.ntc_thresholds.fault_counter_threshold,
+            current_protection: config.current_protection,
+            fet_turn_off_us: 0,
+        }
+    }
+
+    /// Set the time taken to turn the FETs off once a fault is flagged
+    pub const fn fet_turn_off_us(mut self, turn_off_us: u32) -> Self {
+        self.fet_turn_off_us = turn_off_us;
+        self
+    }
+
+    /// Worst-case timing of a protection, or `None` if the protection is disabled,
+    /// or relies on measurements which are disabled, and can not trigger
+    pub const fn timing(&self, protection: Protection) -> Option<ProtectionTiming> {
+        let cycles = &self.measurement_cycles;
+        let cycle_us = cycles.get_t_meas_cycle().period_ms() as u32 * 1000;
+        let cell_filter_us = cycles.get_t_cell_filter().duration_us();
+        let current_filter_us = cycles.get_t_curr_filter().duration_us();
+        let sc_filter_us = cycles.get_t_sc_filter().duration_us();
+
+        let detect_us = match protection {
+            Protection::CellOverVoltage
+            | Protection::CellSevereOverVoltage
+            | Protection::CellUnderVoltage
+            | Protection::CellSevereUnderVoltage
+            | Protection::CellBalancingUnderVoltage
+            | Protection::PackOverVoltage
+            | Protection::PackUnderVoltage
+            | Protection::PackCellSumMismatch => {
+                if cycles.get_t_meas_cycle().is_disabled() {
+                    return None;
+                }
+                at_least_one(self.voltage_counter.value()) * cycle_us + cell_filter_us
+            }
+            #[cfg(feature = "ntc")]
+            Protection::NtcOverTemperature
+            | Protection::NtcSevereOverTemperature
+            | Protection::NtcUnderTemperature => {
+                if cycles.get_t_meas_cycle().is_disabled() {
+                    return None;
+                }
+                at_least_one(self.ntc_counter.value()) * cycle_us + cell_filter_us
+            }
+            Protection::OverCurrentCharge | Protection::OverCurrentDischarge => {
+                if self.current_protection.over_current.is_none() {
+                    return None;
+                }
+                2 * current_filter_us
+            }
+            Protection::PersistentOverCurrentCharge
+            | Protection::PersistentOverCurrentDischarge => {
+                let Some(over_current) = self.current_protection.over_current else {
+                    return None;
+                };
+                (at_least_one(over_current.persistence) + 1) * current_filter_us
+            }
+            Protection::ShortCircuitDischarge => {
+                if self.current_protection.short_circuit.is_none() {
+                    return None;
+                }
+                sc_filter_us
+            }
+            Protection::PersistentShortCircuitDischarge => {
+                let Some(short_circuit) = self.current_protection.short_circuit else {
+                    return None;
+                };
+                at_least_one(short_circuit.persistence) * sc_filter_us
+            }
+        };
+        let react_us = match protection {
+            Protection::CellBalancingUnderVoltage => None,
+            _ => Some(detect_us + self.fet_turn_off_us),
+        };
+        Some(ProtectionTiming {
+            detect_us,
+            react_us,
+        })
+    }
+
+    /// Worst-case timing of every protection
+    pub fn report(&self) -> impl Iterator<Item = (Protection, Option<ProtectionTiming>)> + '_ {
+        Protection::ALL
+            .iter()
+            .map(|protection| (*protection, self.timing(*protection)))
+    }
+}
+
+/// Counter and persistence thresholds of 0 behave as 1
+const fn at_least_one(threshold: u8) -> u32 {
+    if threshold == 0 { 1 } else { threshold as u32 }
+}
+
+impl Config {
+    /// Calculate the worst-case protection timing of this configuration
+    pub const fn protection_timing(&self) -> TimingCalculator {
+        TimingCalculator::new(self)
+    }
+}
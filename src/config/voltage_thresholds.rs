@@ -1,8 +1,12 @@
 use crate::{
     L9961,
     conversions::{
-        cell_voltage_threshold_code_from_mv, pack_voltage_threshold_code_from_mv,
-        round_trip_cell_voltage_threshold, round_trip_pack_voltage_threshold,
+        PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV, cell_voltage_threshold_code_from_mv,
+        cell_voltage_threshold_code_from_mv_rounding_up, pack_voltage_threshold_code_from_mv,
+        pack_voltage_threshold_code_from_mv_rounding_up, pack_voltage_threshold_mv_from_code,
+        round_trip_cell_voltage_threshold, round_trip_cell_voltage_threshold_rounding_up,
+        round_trip_pack_voltage_threshold, round_trip_pack_voltage_threshold_rounding_up,
+        saturating_threshold_code,
     },
     registers::{
         VBOvTh, VBSumMaxDiffTh, VBUvTh, VCellBalUvDeltaTh, VCellOvTh, VCellSevereDeltaThrs,
@@ -10,9 +14,15 @@ use crate::{
     },
 };
 
-use super::CounterThreshold;
+use super::{CELL_COUNT, Chemistry, CounterThreshold};
+
+/// Default cell over-voltage threshold
+const CELL_OVER_VOLTAGE_MV: u16 = 4196;
+/// Default cell under-voltage threshold
+const CELL_UNDER_VOLTAGE_MV: u16 = 2986;
 
 /// Voltage threshold configuration struct
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct VoltageThresholds {
     /// # Cell over-voltage threshold in mV
     /// The cell over-voltage threshold is the voltage at which the cell is considered to be over-voltage.
@@ -32,7 +42,7 @@ pub struct VoltageThresholds {
     pub cell_under_voltage_threshold_mv: u16,
     /// # Cell severe under-voltage delta threshold in mV
     /// The cell severe under-voltage delta threshold is the voltage difference between the cell voltage and the under-voltage threshold at which the cell is considered to be severely under-voltage.
-    /// A severe under
+    /// A severe under-voltage fault is expected to cause irreversible damage to the cell.
     pub cell_severe_under_voltage_delta_threshold_mv: u16,
     /// # Cell balancing under-voltage delta threshold in mV
     /// The cell balancing under-voltage delta threshold is the voltage difference above the under-voltage threshold at which the cell voltage is too low to be considered for balancing.
@@ -61,15 +71,85 @@ impl VoltageThresholds {
     /// Create a new CellThresholds struct with the default values.
     pub const fn default() -> Self {
         VoltageThresholds {
-            cell_over_voltage_threshold_mv: 4196,
-            cell_severe_over_voltage_delta_threshold_mv: 4392,
-            cell_under_voltage_threshold_mv: 2986,
-            cell_severe_under_voltage_delta_threshold_mv: 2693,
-            cell_balancing_under_voltage_delta_threshold_mv: 3181,
+            cell_over_voltage_threshold_mv: CELL_OVER_VOLTAGE_MV,
+            cell_severe_over_voltage_delta_threshold_mv: 195,
+            cell_under_voltage_threshold_mv: CELL_UNDER_VOLTAGE_MV,
+            cell_severe_under_voltage_delta_threshold_mv: 292,
+            cell_balancing_under_voltage_delta_threshold_mv: 195,
             fault_counter_threshold: CounterThreshold::default(),
-            max_pack_cell_sum_delta_mv: 995,
-            pack_over_voltage_threshold_mv: 21000,
-            pack_under_voltage_threshold_mv: 15000,
+            max_pack_cell_sum_delta_mv: 976,
+            pack_over_voltage_threshold_mv: pack_threshold_at_or_below(
+                CELL_COUNT,
+                CELL_OVER_VOLTAGE_MV,
+            ),
+            pack_under_voltage_threshold_mv: pack_threshold_at_or_above(
+                CELL_COUNT,
+                CELL_UNDER_VOLTAGE_MV,
+            ),
+        }
+    }
+
+    /// Create the thresholds for a pack of `cells` cells of the given chemistry.
+    /// Each cell threshold and delta is rounded to a programmable voltage as described by [`Self::programmed`],
+    /// and the pack thresholds are the closest programmable voltages inside the sums of the cell thresholds.
+    pub const fn for_chemistry(chemistry: Chemistry, cells: u8) -> Self {
+        let limits = chemistry.cell_limits();
+        let over_voltage_mv = round_trip_cell_voltage_threshold(limits.over_voltage_mv);
+        let under_voltage_mv =
+            round_trip_cell_voltage_threshold_rounding_up(limits.under_voltage_mv);
+        VoltageThresholds {
+            cell_over_voltage_threshold_mv: over_voltage_mv,
+            cell_severe_over_voltage_delta_threshold_mv: round_trip_cell_voltage_threshold(
+                limits.severe_over_voltage_mv - over_voltage_mv,
+            ),
+            cell_under_voltage_threshold_mv: under_voltage_mv,
+            cell_severe_under_voltage_delta_threshold_mv: round_trip_cell_voltage_threshold(
+                under_voltage_mv - limits.severe_under_voltage_mv,
+            ),
+            cell_balancing_under_voltage_delta_threshold_mv:
+                round_trip_cell_voltage_threshold_rounding_up(
+                    limits.balancing_min_mv - under_voltage_mv,
+                ),
+            fault_counter_threshold: limits.fault_counter_threshold,
+            max_pack_cell_sum_delta_mv: 976,
+            pack_over_voltage_threshold_mv: pack_threshold_at_or_below(cells, over_voltage_mv),
+            pack_under_voltage_threshold_mv: pack_threshold_at_or_above(cells, under_voltage_mv),
+        }
+    }
+
+    /// The thresholds as programmed into the device.
+    /// Each threshold is rounded to the resolution of its register in the direction which makes its fault trip
+    /// no later than requested: the over-voltage thresholds, the severe under-voltage delta and the pack to
+    /// cell sum delta are rounded down, and the under-voltage thresholds and the balancing delta are rounded up.
+    /// Thresholds beyond the range of their register are limited to the largest programmable value.
+    pub const fn programmed(&self) -> Self {
+        Self {
+            cell_over_voltage_threshold_mv: round_trip_cell_voltage_threshold(
+                self.cell_over_voltage_threshold_mv,
+            ),
+            cell_severe_over_voltage_delta_threshold_mv: round_trip_cell_voltage_threshold(
+                self.cell_severe_over_voltage_delta_threshold_mv,
+            ),
+            cell_under_voltage_threshold_mv: round_trip_cell_voltage_threshold_rounding_up(
+                self.cell_under_voltage_threshold_mv,
+            ),
+            cell_severe_under_voltage_delta_threshold_mv: round_trip_cell_voltage_threshold(
+                self.cell_severe_under_voltage_delta_threshold_mv,
+            ),
+            cell_balancing_under_voltage_delta_threshold_mv:
+                round_trip_cell_voltage_threshold_rounding_up(
+                    self.cell_balancing_under_voltage_delta_threshold_mv,
+                ),
+            max_pack_cell_sum_delta_mv: round_trip_pack_voltage_threshold(
+                self.max_pack_cell_sum_delta_mv,
+            ),
+            pack_over_voltage_threshold_mv: round_trip_pack_voltage_threshold(
+                self.pack_over_voltage_threshold_mv,
+            ),
+            pack_under_voltage_threshold_mv: round_trip_pack_voltage_threshold_rounding_up(
+                self.pack_under_voltage_threshold_mv,
+            ),
+            fault_counter_threshold: self.fault_counter_threshold,
         }
     }
 
@@ -83,7 +163,7 @@ impl VoltageThresholds {
     /// Get the cell under-voltage register config based on this configuration
     pub(crate) fn cell_under_voltage_configuration(&self) -> VCellUvTh {
         let cell_under_voltage_code =
-            cell_voltage_threshold_code_from_mv(self.cell_under_voltage_threshold_mv);
+            cell_voltage_threshold_code_from_mv_rounding_up(self.cell_under_voltage_threshold_mv);
         VCellUvTh::new(
             cell_under_voltage_code,
             self.fault_counter_threshold.value(),
@@ -92,9 +172,10 @@ impl VoltageThresholds {
 
     /// Get the cell balancing under-voltage delta register config based on this configuration
     pub(crate) fn cell_balancing_under_voltage_delta_configuration(&self) -> VCellBalUvDeltaTh {
-        let cell_balancing_under_voltage_delta_code = cell_voltage_threshold_code_from_mv(
-            self.cell_balancing_under_voltage_delta_threshold_mv,
-        );
+        let cell_balancing_under_voltage_delta_code =
+            cell_voltage_threshold_code_from_mv_rounding_up(
+                self.cell_balancing_under_voltage_delta_threshold_mv,
+            );
         VCellBalUvDeltaTh::new(
             cell_balancing_under_voltage_delta_code,
             self.fault_counter_threshold.value(),
@@ -123,7 +204,7 @@ impl VoltageThresholds {
     /// Get the pack under-voltage threshold register config based on this configuration
     pub(crate) fn pack_under_voltage_threshold(&self) -> VBUvTh {
         let under_voltage_code =
-            pack_voltage_threshold_code_from_mv(self.pack_under_voltage_threshold_mv);
+            pack_voltage_threshold_code_from_mv_rounding_up(self.pack_under_voltage_threshold_mv);
         VBUvTh::new(under_voltage_code, self.fault_counter_threshold.value())
     }
 
@@ -135,14 +216,58 @@ impl VoltageThresholds {
     }
 }
 
+/// The highest pack threshold at or below the sum of the cell thresholds
+const fn pack_threshold_at_or_below(cells: u8, cell_mv: u16) -> u16 {
+    let code = cells as u32 * cell_mv as u32 * 1000 / PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV;
+    pack_voltage_threshold_mv_from_code(saturating_threshold_code(code))
+}
+
+/// The lowest pack threshold at or above the sum of the cell thresholds
+const fn pack_threshold_at_or_above(cells: u8, cell_mv: u16) -> u16 {
+    let code =
+        (cells as u32 * cell_mv as u32 * 1000).div_ceil(PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV);
+    pack_voltage_threshold_mv_from_code(saturating_threshold_code(code))
+}
+
 impl Default for VoltageThresholds {
     fn default() -> Self {
         VoltageThresholds::default()
     }
 }
 
+impl core::fmt::Display for VoltageThresholds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let programmed = self.programmed();
+        write!(
+            f,
+            "CellThresholds {{
+    cell over voltage threshold mv: {},
+    cell severe over voltage delta threshold mv: {},
+    cell under voltage threshold mv: {},
+    cell severe under voltage threshold mv: {},
+    cell balancing under voltage delta threshold mv: {},
+    fault counter threshold: {},
+    max pack cell sum delta mv: {},
+    pack over voltage threshold mv: {}
+    pack under voltage threshold mv: {}
+}}",
+            programmed.cell_over_voltage_threshold_mv,
+            programmed.cell_severe_over_voltage_delta_threshold_mv,
+            programmed.cell_under_voltage_threshold_mv,
+            programmed.cell_severe_under_voltage_delta_threshold_mv,
+            programmed.cell_balancing_under_voltage_delta_threshold_mv,
+            programmed.fault_counter_threshold.value(),
+            programmed.max_pack_cell_sum_delta_mv,
+            programmed.pack_over_voltage_threshold_mv,
+            programmed.pack_under_voltage_threshold_mv
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
 impl defmt::Format for VoltageThresholds {
     fn format(&self, f: defmt::Formatter) {
+        let programmed = self.programmed();
         defmt::write!(
             f,
             "CellThresholds {{
@@ -156,24 +281,25 @@ impl defmt::Format for VoltageThresholds {
     pack over voltage threshold mv: {}
     pack under voltage threshold mv: {}
 }}",
-            round_trip_cell_voltage_threshold(self.cell_over_voltage_threshold_mv),
-            round_trip_cell_voltage_threshold(self.cell_severe_over_voltage_delta_threshold_mv),
-            round_trip_cell_voltage_threshold(self.cell_under_voltage_threshold_mv),
-            round_trip_cell_voltage_threshold(self.cell_severe_under_voltage_delta_threshold_mv),
-            round_trip_cell_voltage_threshold(self.cell_balancing_under_voltage_delta_threshold_mv),
-            self.fault_counter_threshold.value(),
-            round_trip_pack_voltage_threshold(self.max_pack_cell_sum_delta_mv),
-            round_trip_pack_voltage_threshold(self.pack_over_voltage_threshold_mv),
-            round_trip_pack_voltage_threshold(self.pack_under_voltage_threshold_mv)
+            programmed.cell_over_voltage_threshold_mv,
+            programmed.cell_severe_over_voltage_delta_threshold_mv,
+            programmed.cell_under_voltage_threshold_mv,
+            programmed.cell_severe_under_voltage_delta_threshold_mv,
+            programmed.cell_balancing_under_voltage_delta_threshold_mv,
+            programmed.fault_counter_threshold.value(),
+            programmed.max_pack_cell_sum_delta_mv,
+            programmed.pack_over_voltage_threshold_mv,
+            programmed.pack_under_voltage_threshold_mv
         )
     }
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: embedded_hal_async::i2c::I2c,
-    I: embedded_hal_async::digital::Wait,
-    O: embedded_hal::digital::OutputPin,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
 {
     /// Configure the cell voltage thresholds
     pub async fn apply_voltage_threshold_configuration(&mut self) -> Result<(), I2C::Error> {
@@ -2,21 +2,53 @@
 //! The L9961 uses coded values for many of its registers.
 //! These functions convert between the coded values and the actual values in mV, mA, etc.
 
+/// Resolution of the 8-bit cell voltage threshold codes in uV
+pub(crate) const CELL_VOLTAGE_THRESHOLD_RESOLUTION_UV: u32 = 19520;
+/// Resolution of the 8-bit pack voltage threshold codes in uV
+pub(crate) const PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV: u32 = 97600;
+
+/// Limit a computed threshold code to the 8-bit register field, rather than wrapping
+pub(crate) const fn saturating_threshold_code(code: u32) -> u8 {
+    if code > u8::MAX as u32 {
+        u8::MAX
+    } else {
+        code as u8
+    }
+}
+
 /// Convert a cell voltage threshold register code to mV
 pub const fn cell_voltage_threshold_mv_from_code(code: u8) -> u16 {
-    ((19520 * code as u32) / 1000) as u16
+    ((CELL_VOLTAGE_THRESHOLD_RESOLUTION_UV * code as u32) / 1000) as u16
 }
 
-/// Convert a cell voltage in mV to a register code
+/// Convert a cell voltage in mV to a register code, rounding down and saturating at the highest code.
+/// Rounds to the highest code whose voltage, truncated to mV as by [`cell_voltage_threshold_mv_from_code`],
+/// is at most `voltage_mv`, so converting a programmable voltage back gives the same code.
 pub const fn cell_voltage_threshold_code_from_mv(voltage_mv: u16) -> u8 {
-    ((voltage_mv as u32 * 1000) / 19520) as u8
+    saturating_threshold_code(
+        ((voltage_mv as u32 + 1) * 1000 - 1) / CELL_VOLTAGE_THRESHOLD_RESOLUTION_UV,
+    )
 }
 
-/// Convert from mv to code and back to get the actual value which will be set given a target mv value
+/// Convert a cell voltage in mV to a register code, rounding up and saturating at the highest code
+pub const fn cell_voltage_threshold_code_from_mv_rounding_up(voltage_mv: u16) -> u8 {
+    saturating_threshold_code(
+        (voltage_mv as u32 * 1000).div_ceil(CELL_VOLTAGE_THRESHOLD_RESOLUTION_UV),
+    )
+}
+
+/// Convert from mv to code and back to get the actual value which will be set given a target mv value,
+/// rounding down
 pub const fn round_trip_cell_voltage_threshold(voltage_mv: u16) -> u16 {
     cell_voltage_threshold_mv_from_code(cell_voltage_threshold_code_from_mv(voltage_mv))
 }
 
+/// Convert from mv to code and back to get the actual value which will be set given a target mv value,
+/// rounding up
+pub const fn round_trip_cell_voltage_threshold_rounding_up(voltage_mv: u16) -> u16 {
+    cell_voltage_threshold_mv_from_code(cell_voltage_threshold_code_from_mv_rounding_up(voltage_mv))
+}
+
 /// Convert a cell voltage measurement register code to mV
 pub const fn cell_voltage_measurement_mv_from_code(code: u16) -> u16 {
     ((122 * code as u32) / 100) as u16
@@ -34,19 +66,37 @@ pub const fn round_trip_cell_voltage_measurement(voltage_mv: u16) -> u16 {
 
 /// Convert a pack voltage threshold register code to mV
 pub const fn pack_voltage_threshold_mv_from_code(code: u8) -> u16 {
-    ((code as u32 * 97600) / 1000) as u16
+    ((code as u32 * PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV) / 1000) as u16
 }
 
-/// Convert a pack voltage in mV to a register code
+/// Convert a pack voltage in mV to a register code, rounding down and saturating at the highest code.
+/// Rounds to the highest code whose voltage, truncated to mV as by [`pack_voltage_threshold_mv_from_code`],
+/// is at most `voltage_mv`, so converting a programmable voltage back gives the same code.
 pub const fn pack_voltage_threshold_code_from_mv(voltage_mv: u16) -> u8 {
-    ((1000 * voltage_mv as u32) / 97600) as u8
+    saturating_threshold_code(
+        ((voltage_mv as u32 + 1) * 1000 - 1) / PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV,
+    )
 }
 
-/// Convert from pack mv to code and back to get the actual value which will be set given a target mv value
+/// Convert a pack voltage in mV to a register code, rounding up and saturating at the highest code
+pub const fn pack_voltage_threshold_code_from_mv_rounding_up(voltage_mv: u16) -> u8 {
+    saturating_threshold_code(
+        (1000 * voltage_mv as u32).div_ceil(PACK_VOLTAGE_THRESHOLD_RESOLUTION_UV),
+    )
+}
+
+/// Convert from pack mv to code and back to get the actual value which will be set given a target mv value,
+/// rounding down
 pub const fn round_trip_pack_voltage_threshold(voltage_mv: u16) -> u16 {
     pack_voltage_threshold_mv_from_code(pack_voltage_threshold_code_from_mv(voltage_mv))
 }
 
+/// Convert from pack mv to code and back to get the actual value which will be set given a target mv value,
+/// rounding up
+pub const fn round_trip_pack_voltage_threshold_rounding_up(voltage_mv: u16) -> u16 {
+    pack_voltage_threshold_mv_from_code(pack_voltage_threshold_code_from_mv_rounding_up(voltage_mv))
+}
+
 /// Convert a pack voltage measurement register code to mV
 pub const fn pack_voltage_measurement_mv_from_code(code: u16) -> u16 {
     ((61 * code as u32) / 10) as u16
@@ -84,3 +134,17 @@ const CURRENT_RESISTOR_SHUNT_VALUE_MOHM: u32 = 10;
 pub const fn current_measurement_ma_from_code(code: u32) -> u16 {
     (((code * CURRENT_RESOLUTION_VAL) / CURRENT_RESISTOR_SHUNT_VALUE_MOHM) / 1000u32) as u16
 }
+
+/// Convert a signed instantaneous current measurement code to mA
+pub const fn current_ma_from_signed_code(code: i16) -> i32 {
+    ((code as i32 * CURRENT_RESOLUTION_VAL as i32) / CURRENT_RESISTOR_SHUNT_VALUE_MOHM as i32)
+        / 1000
+}
+
+/// Convert a sum of instantaneous current measurement codes to a charge in uAh,
+/// given the time each measurement was integrated over in us
+pub const fn charge_uah_from_accumulated_code(code: i64, sample_period_us: u32) -> i64 {
+    const US_PER_HOUR: i128 = 3_600_000_000;
+    (code as i128 * CURRENT_RESOLUTION_VAL as i128 * sample_period_us as i128
+        / (CURRENT_RESISTOR_SHUNT_VALUE_MOHM as i128 * US_PER_HOUR)) as i64
+}
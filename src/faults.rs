@@ -1,11 +1,50 @@
 //! The fault module defines the faults returned by the L9961 driver when making measurements
 //! It implements the logic to translate the fault registers into the measurement struct,
-//! and to clear the fault registers while doing so.
+//! and to clear the latched fault flags while doing so.
+//! Flags are cleared selectively and verified by reading them back,
+//! so faults which are still present are reported rather than hidden.
+//! Fault onsets and clears can additionally be recorded in the [`FaultLog`] held by the driver,
+//! and FAULTN can be monitored independently of the measurement loop through a [`FaultMonitor`].
+
+/// Serialize a bitflags type as its bits, rejecting unknown bits when deserializing
+macro_rules! impl_serde_bits {
+    ($flags:ty, $bits:ty) => {
+        #[cfg(feature = "serde")]
+        impl serde::Serialize for $flags {
+            fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                <$bits as serde::Serialize>::serialize(&self.bits(), serializer)
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> serde::Deserialize<'de> for $flags {
+            fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                let bits = <$bits as serde::Deserialize>::deserialize(deserializer)?;
+                Self::from_bits(bits).ok_or_else(|| {
+                    serde::de::Error::invalid_value(
+                        serde::de::Unexpected::Unsigned(bits as u64),
+                        &stringify!($flags),
+                    )
+                })
+            }
+        }
+    };
+}
+
+mod event_log;
+mod fault;
+mod monitor;
+
+pub use event_log::{FAULT_LOG_CAPACITY, FaultEvent, FaultLog, FaultTransition};
+pub use fault::{Cell, Fault, FaultMasks, FaultReactions, FaultRecord, FaultReport, FaultSource};
+pub use monitor::FaultMonitor;
 
 use crate::{
+    L9961, Registers,
     measurement::Measurement,
-    registers::{DiagCurr, DiagOvOtUt, DiagUv},
-    Registers, L9961,
+    registers::{
+        CurrMsk, DiagCurr, DiagOvOtUt, DiagUv, ToFaultnMsk, ToFuseRstMask, ToPrdrvBalMask,
+    },
 };
 
 #[cfg(not(feature = "defmt"))]
@@ -32,6 +71,9 @@ bitflags! {
     }
 }
 
+impl_serde_bits!(CellFaults, u8);
+impl_display_flags!(CellFaults);
+
 bitflags! {
     /// Pack and BMS fault flags
     pub struct PackFaults:u16 {
@@ -51,8 +93,6 @@ bitflags! {
         const CELL_VOLTAGE_SUM_VB_MISMATCH = 0x40;
         /// Coulomb counter saturation
         const COULOMB_COUNTER_SATURATED = 0x80;
-        /// Coulomb counter saturation
-        const CC_SAT = 0x8000;
         /// Over current during charge
         const OVER_CURRENT_CHARGE = 0x0100;
         /// Persistent over-current during charge
@@ -75,159 +115,218 @@ bitflags! {
     }
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
-where
-    I2C: embedded_hal_async::i2c::I2c,
-    I: embedded_hal_async::digital::Wait,
-    O: embedded_hal::digital::OutputPin,
-{
-    /// Update the measurement with fault registers
-    pub(crate) async fn read_fault_registers(
-        &mut self,
-        measurement: &mut Measurement,
-    ) -> Result<(), I2C::Error> {
-        let register_values = self.read_registers(Registers::DiagOvOtUt, 2).await.unwrap();
-        let diag_1 = DiagOvOtUt::from_bits_truncate(register_values[0]);
-        let diag_2 = DiagUv::from_bits_truncate(register_values[1]);
-        let diag3 = self.read_diag_curr().await?;
-
-        // Set any cell 1 faults'
-        if diag_1.contains(DiagOvOtUt::CELL1_OV) {
-            measurement.cell_1.faults |= CellFaults::OVER_VOLTAGE;
-        }
-        if diag_1.contains(DiagOvOtUt::CELL1_SEVERE_OV) {
-            measurement.cell_1.faults |= CellFaults::EXTREME_OVER_VOLTAGE;
-        }
-        if diag_2.contains(DiagUv::CELL1_UV) {
-            measurement.cell_1.faults |= CellFaults::UNDER_VOLTAGE;
-        }
-        if diag_2.contains(DiagUv::BAL1_UV) {
-            measurement.cell_1.faults |= CellFaults::UNDER_VOLTAGE_FOR_BALANCING;
-        }
-        if diag_2.contains(DiagUv::V_SEVERE_CELL1_UV) {
-            measurement.cell_1.faults |= CellFaults::EXTREME_UNDER_VOLTAGE;
-        }
+impl_serde_bits!(PackFaults, u16);
+impl_display_flags!(PackFaults);
 
-        // Set any cell 2 faults'
-        if diag_1.contains(DiagOvOtUt::CELL2_OV) {
-            measurement.cell_2.faults |= CellFaults::OVER_VOLTAGE;
-        }
-        if diag_1.contains(DiagOvOtUt::CELL2_SEVERE_OV) {
-            measurement.cell_2.faults |= CellFaults::EXTREME_OVER_VOLTAGE;
+/// Combined contents of the three diagnostic registers
+/// DIAG_OV_OT_UT, DIAG_UV and DIAG_CURR, as read at a single point in time
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DiagnosticFlags {
+    /// Over-voltage, over-temperature and under-temperature flags
+    pub ov_ot_ut: DiagOvOtUt,
+    /// Under-voltage flags
+    pub uv: DiagUv,
+    /// Current and external fault flags
+    pub curr: DiagCurr,
+}
+
+impl DiagnosticFlags {
+    /// Diagnostic flags with no faults set
+    pub const fn empty() -> Self {
+        Self {
+            ov_ot_ut: DiagOvOtUt::empty(),
+            uv: DiagUv::empty(),
+            curr: DiagCurr::empty(),
         }
-        if diag_2.contains(DiagUv::CELL2_UV) {
-            measurement.cell_2.faults |= CellFaults::UNDER_VOLTAGE;
+    }
+
+    /// Whether no fault flags are set
+    pub const fn is_empty(&self) -> bool {
+        self.ov_ot_ut.is_empty() && self.uv.is_empty() && self.curr.is_empty()
+    }
+
+    /// Flags which are set in `self` but not in `other`
+    pub fn difference(&self, other: &Self) -> Self {
+        Self {
+            ov_ot_ut: self.ov_ot_ut - other.ov_ot_ut,
+            uv: self.uv - other.uv,
+            curr: self.curr - other.curr,
         }
-        if diag_2.contains(DiagUv::BAL2_UV) {
-            measurement.cell_2.faults |= CellFaults::UNDER_VOLTAGE_FOR_BALANCING;
+    }
+
+    /// Flags which are set in both `self` and `other`
+    pub fn intersection(&self, other: &Self) -> Self {
+        Self {
+            ov_ot_ut: self.ov_ot_ut & other.ov_ot_ut,
+            uv: self.uv & other.uv,
+            curr: self.curr & other.curr,
         }
-        if diag_2.contains(DiagUv::V_SEVERE_CELL2_UV) {
-            measurement.cell_2.faults |= CellFaults::EXTREME_UNDER_VOLTAGE;
+    }
+
+    /// Flags which are set in either `self` or `other`
+    pub fn union(&self, other: &Self) -> Self {
+        Self {
+            ov_ot_ut: self.ov_ot_ut | other.ov_ot_ut,
+            uv: self.uv | other.uv,
+            curr: self.curr | other.curr,
         }
+    }
 
-        // Set any cell 3 faults'
-        if diag_1.contains(DiagOvOtUt::CELL3_OV) {
-            measurement.cell_3.faults |= CellFaults::OVER_VOLTAGE;
+    /// Only the flags which are latched until cleared by the MCU (RLW).
+    /// CC_SAT and FUSE_EXT reflect the live state of the fault and cannot be cleared.
+    pub fn latched(&self) -> Self {
+        Self {
+            ov_ot_ut: self.ov_ot_ut,
+            uv: self.uv,
+            curr: self.curr - (DiagCurr::CC_SAT | DiagCurr::FUSE_EXT),
         }
-        if diag_1.contains(DiagOvOtUt::CELL3_SEVERE_OV) {
-            measurement.cell_3.faults |= CellFaults::EXTREME_OVER_VOLTAGE;
+    }
+}
+
+impl Default for DiagnosticFlags {
+    fn default() -> Self {
+        Self::empty()
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: embedded_hal_async::i2c::I2c,
+    READY: embedded_hal_async::digital::Wait,
+    FAULTN: embedded_hal_async::digital::Wait,
+    WAKEUP: embedded_hal::digital::OutputPin,
+{
+    /// Read the DIAG_OV_OT_UT, DIAG_UV and DIAG_CURR registers
+    pub async fn read_diagnostic_flags(&mut self) -> Result<DiagnosticFlags, I2C::Error> {
+        let register_values = self.read_registers(Registers::DiagOvOtUt, 2).await?;
+        let ov_ot_ut = DiagOvOtUt::from_bits_truncate(register_values[0]);
+        let uv = DiagUv::from_bits_truncate(register_values[1]);
+        let curr = self.read_diag_curr().await?;
+        Ok(DiagnosticFlags { ov_ot_ut, uv, curr })
+    }
+
+    /// Read the four registers masking the hardware reactions to faults
+    pub async fn read_fault_masks(&mut self) -> Result<FaultMasks, I2C::Error> {
+        let register_values = self.read_registers(Registers::ToPrdrvBalMask, 4).await?;
+        Ok(FaultMasks {
+            prdrv_bal: ToPrdrvBalMask::from_bits_truncate(register_values[0]),
+            fuse_rst: ToFuseRstMask::from_bits_truncate(register_values[1]),
+            faultn: ToFaultnMsk::from_bits_truncate(register_values[2]),
+            curr: CurrMsk::from_bits_truncate(register_values[3]),
+        })
+    }
+
+    /// Read the diagnostic registers, clear the latched faults and read them again,
+    /// reporting which faults were latched, which remained active, and the reactions they triggered
+    pub async fn read_faults(&mut self) -> Result<FaultReport, I2C::Error> {
+        let latched = self.read_diagnostic_flags().await?;
+        let masks = self.read_fault_masks().await?;
+        let still_active = self.clear_faults(latched).await?;
+        Ok(FaultReport {
+            latched,
+            still_active,
+            masks,
+        })
+    }
+
+    /// Clear the given faults by writing 1 to their RLW flags, leaving every other flag latched,
+    /// then read the diagnostic registers back.
+    /// Returns the subset of `flags` which are still set, i.e. faults which are still present and immediately re-latched,
+    /// as well as any RO flags which cannot be cleared.
+    pub async fn clear_faults(
+        &mut self,
+        flags: DiagnosticFlags,
+    ) -> Result<DiagnosticFlags, I2C::Error> {
+        if flags.is_empty() {
+            return Ok(flags);
         }
-        if diag_2.contains(DiagUv::CELL3_UV) {
-            measurement.cell_3.faults |= CellFaults::UNDER_VOLTAGE;
+        let to_clear = flags.latched();
+        if !to_clear.ov_ot_ut.is_empty() {
+            self.write_diag_ov_ot_ut(to_clear.ov_ot_ut).await?;
         }
-        if diag_2.contains(DiagUv::BAL3_UV) {
-            measurement.cell_3.faults |= CellFaults::UNDER_VOLTAGE_FOR_BALANCING;
+        if !to_clear.uv.is_empty() {
+            self.write_diag_uv(to_clear.uv).await?;
         }
-        if diag_2.contains(DiagUv::V_SEVERE_CELL3_UV) {
-            measurement.cell_3.faults |= CellFaults::EXTREME_UNDER_VOLTAGE;
+        if !to_clear.curr.is_empty() {
+            self.write_diag_curr(to_clear.curr).await?;
         }
+        let remaining = self.read_diagnostic_flags().await?;
+        Ok(flags.intersection(&remaining))
+    }
 
-        #[cfg(feature = "4_cells")]
-        {
-            // Set any cell 4 faults'
-            if diag_1.contains(DiagOvOtUt::CELL4_OV) {
-                measurement.cell_4.faults |= CellFaults::OVER_VOLTAGE;
-            }
-            if diag_1.contains(DiagOvOtUt::CELL4_SEVERE_OV) {
-                measurement.cell_4.faults |= CellFaults::EXTREME_OVER_VOLTAGE;
-            }
-            if diag_2.contains(DiagUv::CELL4_UV) {
-                measurement.cell_4.faults |= CellFaults::UNDER_VOLTAGE;
-            }
-            if diag_2.contains(DiagUv::BAL4_UV) {
-                measurement.cell_4.faults |= CellFaults::UNDER_VOLTAGE_FOR_BALANCING;
+    /// Clear a single fault, returning whether it was still set afterwards
+    pub async fn clear_fault(&mut self, fault: Fault) -> Result<bool, I2C::Error> {
+        let remaining = self
+            .clear_faults(DiagnosticFlags::from_fault(fault))
+            .await?;
+        Ok(!remaining.is_empty())
+    }
+
+    /// Update the measurement with fault registers
+    pub(crate) async fn read_fault_registers(
+        &mut self,
+        measurement: &mut Measurement,
+    ) -> Result<DiagnosticFlags, I2C::Error> {
+        let flags = self.read_diagnostic_flags().await?;
+        apply_diagnostic_flags(&flags, measurement);
+        Ok(flags)
+    }
+}
+
+/// Fold the diagnostic register contents into the per-cell and pack faults of a measurement
+fn apply_diagnostic_flags(flags: &DiagnosticFlags, measurement: &mut Measurement) {
+    for fault in flags.faults() {
+        let (cell, cell_fault) = match fault {
+            Fault::CellOverVoltage(cell) => (cell, CellFaults::OVER_VOLTAGE),
+            Fault::CellSevereOverVoltage(cell) => (cell, CellFaults::EXTREME_OVER_VOLTAGE),
+            Fault::CellUnderVoltage(cell) => (cell, CellFaults::UNDER_VOLTAGE),
+            Fault::CellBalancingUnderVoltage(cell) => {
+                (cell, CellFaults::UNDER_VOLTAGE_FOR_BALANCING)
             }
-            if diag_2.contains(DiagUv::V_SEVERE_CELL4_UV) {
-                measurement.cell_4.faults |= CellFaults::EXTREME_UNDER_VOLTAGE;
+            Fault::CellSevereUnderVoltage(cell) => (cell, CellFaults::EXTREME_UNDER_VOLTAGE),
+            pack_fault => {
+                measurement.pack_faults |= PackFaults::from(pack_fault);
+                continue;
             }
+        };
+        // Faults for cells which are not enabled by features are ignored
+        if let Some(cell) = measurement.cell_mut(cell.number()) {
+            cell.faults |= cell_fault;
         }
+    }
+}
 
-        #[cfg(feature = "5_cells")]
-        {
-            // Set any cell 5 faults'
-            if diag_1.contains(DiagOvOtUt::CELL5_OV) {
-                measurement.cell_5.faults |= CellFaults::OVER_VOLTAGE;
-            }
-            if diag_1.contains(DiagOvOtUt::CELL5_SEVERE_OV) {
-                measurement.cell_5.faults |= CellFaults::EXTREME_OVER_VOLTAGE;
+impl From<Fault> for PackFaults {
+    /// The pack fault flag corresponding to a fault, empty for per-cell faults
+    fn from(fault: Fault) -> Self {
+        match fault {
+            Fault::PackOverVoltage => PackFaults::OVER_VOLTAGE,
+            Fault::PackUnderVoltage => PackFaults::UNDER_VOLTAGE,
+            Fault::NtcOverTemp => PackFaults::NTC_OVER_TEMP,
+            Fault::NtcUnderTemp => PackFaults::NTC_UNDER_TEMP,
+            Fault::NtcSevereOverTemp => PackFaults::NTC_SEVERE_OVER_TEMP,
+            Fault::DieOverTemp => PackFaults::DIE_OVER_TEMP,
+            Fault::VbSumCheck => PackFaults::CELL_VOLTAGE_SUM_VB_MISMATCH,
+            Fault::CoulombCounterSaturated => PackFaults::COULOMB_COUNTER_SATURATED,
+            Fault::OverCurrentCharge => PackFaults::OVER_CURRENT_CHARGE,
+            Fault::PersistentOverCurrentCharge => PackFaults::PERSISTENT_OVER_CURRENT_IN_CHARGE,
+            Fault::OverCurrentDischarge => PackFaults::OVER_CURRENT_DISCHARGE,
+            Fault::PersistentOverCurrentDischarge => {
+                PackFaults::PERSISTENT_OVER_CURRENT_IN_DISCHARGE
             }
-            if diag_2.contains(DiagUv::CELL5_UV) {
-                measurement.cell_5.faults |= CellFaults::UNDER_VOLTAGE;
+            Fault::ShortCircuitDischarge => PackFaults::SHORT_CIRCUIT_DISCHARGE,
+            Fault::PersistentShortCircuitDischarge => {
+                PackFaults::PERSISTENT_SHORT_CIRCUIT_DISCHARGE
             }
-            if diag_2.contains(DiagUv::BAL5_UV) {
-                measurement.cell_5.faults |= CellFaults::UNDER_VOLTAGE_FOR_BALANCING;
-            }
-            if diag_2.contains(DiagUv::V_SEVERE_CELL5_UV) {
-                measurement.cell_5.faults |= CellFaults::EXTREME_UNDER_VOLTAGE;
-            }
-        }
-
-        // Set any pack faults
-        if diag_1.contains(DiagOvOtUt::DIE_OT) {
-            measurement.pack_faults |= PackFaults::DIE_OVER_TEMP;
-        }
-        if diag_1.contains(DiagOvOtUt::NTC_OT) {
-            measurement.pack_faults |= PackFaults::NTC_OVER_TEMP;
-        }
-        if diag_1.contains(DiagOvOtUt::NTC_SEVERE_OT) {
-            measurement.pack_faults |= PackFaults::NTC_SEVERE_OVER_TEMP;
-        }
-        if diag_1.contains(DiagOvOtUt::NTC_UT) {
-            measurement.pack_faults |= PackFaults::NTC_UNDER_TEMP;
-        }
-        if diag_1.contains(DiagOvOtUt::PACK_OV) {
-            measurement.pack_faults |= PackFaults::OVER_VOLTAGE;
+            Fault::FuseExternal => PackFaults::FUSE_EXTERNAL,
+            Fault::FaultnExternal => PackFaults::FAULTN_EXTERNAL,
+            Fault::CellOverVoltage(_)
+            | Fault::CellSevereOverVoltage(_)
+            | Fault::CellUnderVoltage(_)
+            | Fault::CellBalancingUnderVoltage(_)
+            | Fault::CellSevereUnderVoltage(_) => PackFaults::empty(),
         }
-        if diag_1.contains(DiagOvOtUt::VB_SUM_CHECK_FAIL) {
-            measurement.pack_faults |= PackFaults::NTC_OVER_TEMP;
-        }
-        if diag_2.contains(DiagUv::VB_UV) {
-            measurement.pack_faults |= PackFaults::UNDER_VOLTAGE;
-        }
-        if diag3.contains(DiagCurr::CC_SAT) {
-            measurement.pack_faults |= PackFaults::COULOMB_COUNTER_SATURATED;
-        }
-        if diag3.contains(DiagCurr::OVC_CHG) {
-            measurement.pack_faults |= PackFaults::OVER_CURRENT_CHARGE;
-        }
-        if diag3.contains(DiagCurr::SC_DCHG) {
-            measurement.pack_faults |= PackFaults::CC_SAT;
-        }
-        if diag3.contains(DiagCurr::PERSIST_SC_DCHG) {
-            measurement.pack_faults |= PackFaults::CC_SAT;
-        }
-        if diag3.contains(DiagCurr::PERSIST_OVC_DCHG) {
-            measurement.pack_faults |= PackFaults::CC_SAT;
-        }
-        if diag3.contains(DiagCurr::PERSIST_OVC_CHG | DiagCurr::FAULTN_EXT | DiagCurr::FUSE_EXT) {
-            measurement.pack_faults |= PackFaults::CC_SAT;
-        }
-        Ok(())
-    }
-
-    pub(crate) async fn clear_fault_registers(&mut self) -> Result<(), I2C::Error> {
-        self.write_diag_ov_ot_ut(DiagOvOtUt::all()).await?;
-        self.write_diag_uv(DiagUv::all()).await?;
-        self.write_diag_curr(DiagCurr::all()).await
     }
 }
@@ -0,0 +1,202 @@
+use crate::measurement::Measurement;
+
+use super::{DiagnosticFlags, Fault};
+
+/// Number of events retained by the driver's fault log before the oldest are overwritten
+pub const FAULT_LOG_CAPACITY: usize = 16;
+
+/// Whether a fault event marks faults appearing or going away
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FaultTransition {
+    /// The faults were not latched at the previous observation and are now
+    Onset,
+    /// The faults were latched at the previous observation and no longer are
+    Cleared,
+}
+
+/// A single entry in the fault log
+#[derive(Clone, Copy)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultEvent {
+    /// Whether the faults appeared or cleared
+    pub transition: FaultTransition,
+    /// Caller-provided timestamp of the observation, in caller-defined units
+    pub timestamp: u64,
+    /// The faults which changed state
+    pub faults: DiagnosticFlags,
+    /// Measurement taken when the faults appeared, if any. `None` for cleared events
+    pub measurement: Option<Measurement>,
+}
+
+/// Fixed-capacity ring buffer of fault onset and clear events,
+/// along with a saturating occurrence counter for every [`Fault`].
+/// When the buffer is full, the oldest event is overwritten and counted as dropped.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultLog<const N: usize = FAULT_LOG_CAPACITY> {
+    events: [Option<FaultEvent>; N],
+    // Index of the oldest event
+    head: usize,
+    len: usize,
+    dropped: u32,
+    // Faults which were latched at the last observation
+    active: DiagnosticFlags,
+    occurrences: [u16; Fault::COUNT],
+}
+
+impl<const N: usize> FaultLog<N> {
+    /// Create a new, empty fault log
+    pub const fn new() -> Self {
+        Self {
+            events: [None; N],
+            head: 0,
+            len: 0,
+            dropped: 0,
+            active: DiagnosticFlags::empty(),
+            occurrences: [0; Fault::COUNT],
+        }
+    }
+
+    /// Record a new observation of the diagnostic registers.
+    /// Flags which were not set at the previous observation are logged as an onset event along with `measurement`,
+    /// and flags which are no longer set are logged as a cleared event.
+    pub fn record(
+        &mut self,
+        timestamp: u64,
+        flags: DiagnosticFlags,
+        measurement: Option<&Measurement>,
+    ) {
+        let cleared = self.active.difference(&flags);
+        let onset = flags.difference(&self.active);
+        self.active = flags;
+
+        if !cleared.is_empty() {
+            self.push(FaultEvent {
+                transition: FaultTransition::Cleared,
+                timestamp,
+                faults: cleared,
+                measurement: None,
+            });
+        }
+        if !onset.is_empty() {
+            for fault in onset.faults() {
+                let count = &mut self.occurrences[fault.index()];
+                *count = count.saturating_add(1);
+            }
+            self.push(FaultEvent {
+                transition: FaultTransition::Onset,
+                timestamp,
+                faults: onset,
+                measurement: measurement.copied(),
+            });
+        }
+    }
+
+    /// Record a read of the diagnostic registers which cleared the `latched` flags, leaving `still_active` set.
+    /// The latched flags are recorded as by [`Self::record`], then those which did not survive the clear
+    /// are logged as cleared, so that a fault which has already gone away is not left active in the log.
+    pub fn record_cleared(
+        &mut self,
+        timestamp: u64,
+        latched: DiagnosticFlags,
+        still_active: DiagnosticFlags,
+        measurement: Option<&Measurement>,
+    ) {
+        self.record(timestamp, latched, measurement);
+        self.record(timestamp, still_active, None);
+    }
+
+    /// Faults which were latched at the last recorded observation
+    pub const fn active(&self) -> DiagnosticFlags {
+        self.active
+    }
+
+    /// Number of events currently stored
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Whether the log holds no events
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Maximum number of events the log can hold
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of events overwritten because the log was full
+    pub const fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    /// Iterate over the stored events, oldest first
+    pub fn iter(&self) -> impl Iterator<Item = &FaultEvent> {
+        (0..self.len).filter_map(move |i| self.events[(self.head + i) % N].as_ref())
+    }
+
+    /// Remove and return the oldest stored event
+    pub fn pop(&mut self) -> Option<FaultEvent> {
+        if self.len == 0 {
+            return None;
+        }
+        let event = self.events[self.head].take();
+        self.head = (self.head + 1) % N;
+        self.len -= 1;
+        event
+    }
+
+    /// Remove and return all stored events, oldest first
+    pub fn drain(&mut self) -> impl Iterator<Item = FaultEvent> + '_ {
+        core::iter::from_fn(move || self.pop())
+    }
+
+    /// Store an event taken from another log, e.g. to mirror the driver's log into a resource shared with other tasks.
+    /// The active faults and occurrence counters are updated as if this log had recorded the event.
+    pub fn replay(&mut self, event: FaultEvent) {
+        match event.transition {
+            FaultTransition::Onset => {
+                self.active = self.active.union(&event.faults);
+                for fault in event.faults.faults() {
+                    let count = &mut self.occurrences[fault.index()];
+                    *count = count.saturating_add(1);
+                }
+            }
+            FaultTransition::Cleared => self.active = self.active.difference(&event.faults),
+        }
+        self.push(event);
+    }
+
+    /// Number of times the given fault has been observed appearing
+    pub const fn occurrences(&self, fault: Fault) -> u16 {
+        self.occurrences[fault.index()]
+    }
+
+    /// Discard all stored events and reset the occurrence counters
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    fn push(&mut self, event: FaultEvent) {
+        if N == 0 {
+            self.dropped = self.dropped.saturating_add(1);
+            return;
+        }
+        if self.len == N {
+            self.head = (self.head + 1) % N;
+            self.len -= 1;
+            self.dropped = self.dropped.saturating_add(1);
+        }
+        self.events[(self.head + self.len) % N] = Some(event);
+        self.len += 1;
+    }
+}
+
+impl<const N: usize> Default for FaultLog<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,466 @@
+use crate::{
+    Registers,
+    registers::{
+        CurrMsk, DiagCurr, DiagOvOtUt, DiagUv, ToFaultnMsk, ToFuseRstMask, ToPrdrvBalMask,
+    },
+};
+
+use super::DiagnosticFlags;
+
+#[cfg(not(feature = "defmt"))]
+use bitflags::bitflags;
+#[cfg(feature = "defmt")]
+use defmt::bitflags;
+
+/// A cell monitored by the per-cell diagnostic flags
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
+pub enum Cell {
+    /// Cell 1, the bottom of the stack
+    Cell1 = 1,
+    /// Cell 2
+    Cell2 = 2,
+    /// Cell 3
+    Cell3 = 3,
+    /// Cell 4
+    Cell4 = 4,
+    /// Cell 5, the top of the stack
+    Cell5 = 5,
+}
+
+impl Cell {
+    /// The cell with the given 1-indexed number, if it is in `1..=5`
+    pub const fn new(cell: u8) -> Option<Self> {
+        match cell {
+            1 => Some(Cell::Cell1),
+            2 => Some(Cell::Cell2),
+            3 => Some(Cell::Cell3),
+            4 => Some(Cell::Cell4),
+            5 => Some(Cell::Cell5),
+            _ => None,
+        }
+    }
+
+    /// The 1-indexed cell number
+    pub const fn number(self) -> u8 {
+        self as u8
+    }
+}
+
+impl From<Cell> for u8 {
+    fn from(cell: Cell) -> Self {
+        cell.number()
+    }
+}
+
+impl core::fmt::Display for Cell {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "cell {}", self.number())
+    }
+}
+
+/// A single fault reported by the DIAG_OV_OT_UT, DIAG_UV or DIAG_CURR registers.
+/// Per-cell faults carry the cell they were reported for.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Fault {
+    /// Cell is over its configured over-voltage threshold (CELLx_OV)
+    CellOverVoltage(Cell),
+    /// Cell is over its configured severe over-voltage threshold (V_SEVERE_CELLx_OV)
+    CellSevereOverVoltage(Cell),
+    /// Pack is over its configured over-voltage threshold (VB_OV)
+    PackOverVoltage,
+    /// Mismatch between the sum of cell measurements and the pack voltage (VB_SUM_CHECK_FAIL)
+    VbSumCheck,
+    /// NTC is over its configured over-temperature threshold (NTC_OT)
+    NtcOverTemp,
+    /// NTC is over its configured severe over-temperature threshold (NTC_SEVERE_OT)
+    NtcSevereOverTemp,
+    /// NTC is under its configured under-temperature threshold (NTC_UT)
+    NtcUnderTemp,
+    /// BMS die over-temperature (DIE_OT)
+    DieOverTemp,
+    /// Cell is under its configured under-voltage threshold (CELLx_UV)
+    CellUnderVoltage(Cell),
+    /// Cell is too low to be balanced (BALx_UV)
+    CellBalancingUnderVoltage(Cell),
+    /// Pack is under its configured under-voltage threshold (VB_UV)
+    PackUnderVoltage,
+    /// Cell is under its configured severe under-voltage threshold (V_SEVERE_CELLx_UV)
+    CellSevereUnderVoltage(Cell),
+    /// Coulomb counter saturation (CC_SAT)
+    CoulombCounterSaturated,
+    /// Over-current during charge (OVC_CHG)
+    OverCurrentCharge,
+    /// Over-current during discharge (OVC_DCHG)
+    OverCurrentDischarge,
+    /// Persistent over-current during charge (PERSIST_OVC_CHG)
+    PersistentOverCurrentCharge,
+    /// Persistent over-current during discharge (PERSIST_OVC_DCHG)
+    PersistentOverCurrentDischarge,
+    /// Short circuit during discharge (SC_DCHG)
+    ShortCircuitDischarge,
+    /// Persistent short circuit during discharge (PERSIST_SC_DCHG)
+    PersistentShortCircuitDischarge,
+    /// External fuse fault (FUSE_EXT)
+    FuseExternal,
+    /// External FAULTN fault (FAULTN_EXT)
+    FaultnExternal,
+}
+
+/// The register bit which reports a fault
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultSource {
+    /// Diagnostic register containing the fault flag
+    pub register: Registers,
+    /// Mask of the fault flag within the register
+    pub mask: u16,
+}
+
+bitflags! {
+    /// Hardware reactions a fault triggers, as configured by the mask registers
+    pub struct FaultReactions:u8 {
+        /// The fault asserts the FAULTN output
+        const FAULTN = 0x01;
+        /// The fault opens the charge and discharge FETs via the PRDRV outputs
+        const PRDRV = 0x02;
+        /// The fault disables cell balancing
+        const BALANCING = 0x04;
+        /// The fault triggers the fuse
+        const FUSE = 0x08;
+        /// The fault triggers the reset reaction
+        const RESET = 0x10;
+    }
+}
+
+impl_serde_bits!(FaultReactions, u8);
+
+/// Contents of the four registers masking the hardware reactions to faults
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultMasks {
+    /// TO_PRDRV_BAL_MSK register
+    pub prdrv_bal: ToPrdrvBalMask,
+    /// TO_FUSE_RST_MSK register
+    pub fuse_rst: ToFuseRstMask,
+    /// TO_FAULTN_MSK register
+    pub faultn: ToFaultnMsk,
+    /// CURR_MSK register
+    pub curr: CurrMsk,
+}
+
+impl FaultMasks {
+    /// Masks with every reaction enabled, matching the power-on reset state
+    pub const fn none() -> Self {
+        Self {
+            prdrv_bal: ToPrdrvBalMask::empty(),
+            fuse_rst: ToFuseRstMask::empty(),
+            faultn: ToFaultnMsk::empty(),
+            curr: CurrMsk::empty(),
+        }
+    }
+}
+
+impl Default for FaultMasks {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+/// A fault observed in the diagnostic registers
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultRecord {
+    /// The fault
+    pub fault: Fault,
+    /// Whether the fault flag was still set after clearing it
+    pub still_active: bool,
+    /// Hardware reactions the fault triggered
+    pub reactions: FaultReactions,
+}
+
+impl Fault {
+    /// Number of distinct faults
+    pub const COUNT: usize = 41;
+
+    /// Every fault, in diagnostic register and bit order
+    pub const ALL: [Fault; Fault::COUNT] = [
+        Fault::CellOverVoltage(Cell::Cell1),
+        Fault::CellOverVoltage(Cell::Cell2),
+        Fault::CellOverVoltage(Cell::Cell3),
+        Fault::CellOverVoltage(Cell::Cell4),
+        Fault::CellOverVoltage(Cell::Cell5),
+        Fault::PackOverVoltage,
+        Fault::CellSevereOverVoltage(Cell::Cell1),
+        Fault::CellSevereOverVoltage(Cell::Cell2),
+        Fault::CellSevereOverVoltage(Cell::Cell3),
+        Fault::CellSevereOverVoltage(Cell::Cell4),
+        Fault::CellSevereOverVoltage(Cell::Cell5),
+        Fault::VbSumCheck,
+        Fault::NtcOverTemp,
+        Fault::NtcSevereOverTemp,
+        Fault::NtcUnderTemp,
+        Fault::DieOverTemp,
+        Fault::CellUnderVoltage(Cell::Cell1),
+        Fault::CellUnderVoltage(Cell::Cell2),
+        Fault::CellUnderVoltage(Cell::Cell3),
+        Fault::CellUnderVoltage(Cell::Cell4),
+        Fault::CellUnderVoltage(Cell::Cell5),
+        Fault::CellBalancingUnderVoltage(Cell::Cell1),
+        Fault::CellBalancingUnderVoltage(Cell::Cell2),
+        Fault::CellBalancingUnderVoltage(Cell::Cell3),
+        Fault::CellBalancingUnderVoltage(Cell::Cell4),
+        Fault::CellBalancingUnderVoltage(Cell::Cell5),
+        Fault::PackUnderVoltage,
+        Fault::CellSevereUnderVoltage(Cell::Cell1),
+        Fault::CellSevereUnderVoltage(Cell::Cell2),
+        Fault::CellSevereUnderVoltage(Cell::Cell3),
+        Fault::CellSevereUnderVoltage(Cell::Cell4),
+        Fault::CellSevereUnderVoltage(Cell::Cell5),
+        Fault::CoulombCounterSaturated,
+        Fault::OverCurrentCharge,
+        Fault::OverCurrentDischarge,
+        Fault::PersistentOverCurrentCharge,
+        Fault::PersistentOverCurrentDischarge,
+        Fault::ShortCircuitDischarge,
+        Fault::PersistentShortCircuitDischarge,
+        Fault::FuseExternal,
+        Fault::FaultnExternal,
+    ];
+
+    /// The register bit reporting this fault
+    pub const fn source(&self) -> FaultSource {
+        let (register, mask) = match *self {
+            Fault::CellOverVoltage(cell) => (Registers::DiagOvOtUt, cell_mask(cell, 0)),
+            Fault::PackOverVoltage => (Registers::DiagOvOtUt, DiagOvOtUt::PACK_OV.bits()),
+            Fault::CellSevereOverVoltage(cell) => (Registers::DiagOvOtUt, cell_mask(cell, 6)),
+            Fault::VbSumCheck => (Registers::DiagOvOtUt, DiagOvOtUt::VB_SUM_CHECK_FAIL.bits()),
+            Fault::NtcOverTemp => (Registers::DiagOvOtUt, DiagOvOtUt::NTC_OT.bits()),
+            Fault::NtcSevereOverTemp => (Registers::DiagOvOtUt, DiagOvOtUt::NTC_SEVERE_OT.bits()),
+            Fault::NtcUnderTemp => (Registers::DiagOvOtUt, DiagOvOtUt::NTC_UT.bits()),
+            Fault::DieOverTemp => (Registers::DiagOvOtUt, DiagOvOtUt::DIE_OT.bits()),
+            Fault::CellUnderVoltage(cell) => (Registers::DiagUv, cell_mask(cell, 0)),
+            Fault::CellBalancingUnderVoltage(cell) => (Registers::DiagUv, cell_mask(cell, 5)),
+            Fault::PackUnderVoltage => (Registers::DiagUv, DiagUv::VB_UV.bits()),
+            Fault::CellSevereUnderVoltage(cell) => (Registers::DiagUv, cell_mask(cell, 11)),
+            Fault::CoulombCounterSaturated => (Registers::DiagCurr, DiagCurr::CC_SAT.bits()),
+            Fault::OverCurrentCharge => (Registers::DiagCurr, DiagCurr::OVC_CHG.bits()),
+            Fault::OverCurrentDischarge => (Registers::DiagCurr, DiagCurr::OVC_DCHG.bits()),
+            Fault::PersistentOverCurrentCharge => {
+                (Registers::DiagCurr, DiagCurr::PERSIST_OVC_CHG.bits())
+            }
+            Fault::PersistentOverCurrentDischarge => {
+                (Registers::DiagCurr, DiagCurr::PERSIST_OVC_DCHG.bits())
+            }
+            Fault::ShortCircuitDischarge => (Registers::DiagCurr, DiagCurr::SC_DCHG.bits()),
+            Fault::PersistentShortCircuitDischarge => {
+                (Registers::DiagCurr, DiagCurr::PERSIST_SC_DCHG.bits())
+            }
+            Fault::FuseExternal => (Registers::DiagCurr, DiagCurr::FUSE_EXT.bits()),
+            Fault::FaultnExternal => (Registers::DiagCurr, DiagCurr::FAULTN_EXT.bits()),
+        };
+        FaultSource { register, mask }
+    }
+
+    /// Whether the fault flag is latched until cleared by the MCU (RLW),
+    /// as opposed to reflecting the live state of the fault (RO)
+    pub const fn is_latched(&self) -> bool {
+        !matches!(self, Fault::CoulombCounterSaturated | Fault::FuseExternal)
+    }
+
+    /// The cell of per-cell faults
+    pub const fn cell(&self) -> Option<Cell> {
+        match *self {
+            Fault::CellOverVoltage(cell)
+            | Fault::CellSevereOverVoltage(cell)
+            | Fault::CellUnderVoltage(cell)
+            | Fault::CellBalancingUnderVoltage(cell)
+            | Fault::CellSevereUnderVoltage(cell) => Some(cell),
+            _ => None,
+        }
+    }
+
+    /// Whether this fault is set in the given diagnostic flags
+    pub const fn is_set(&self, flags: &DiagnosticFlags) -> bool {
+        let source = self.source();
+        let bits = match source.register {
+            Registers::DiagOvOtUt => flags.ov_ot_ut.bits(),
+            Registers::DiagUv => flags.uv.bits(),
+            _ => flags.curr.bits(),
+        };
+        bits & source.mask != 0
+    }
+
+    /// Position of this fault in [`Fault::ALL`]
+    pub const fn index(&self) -> usize {
+        let source = self.source();
+        let offset = match source.register {
+            Registers::DiagOvOtUt => 0,
+            Registers::DiagUv => 16,
+            _ => 32,
+        };
+        offset + source.mask.trailing_zeros() as usize
+    }
+
+    /// The hardware reactions this fault triggers, given the contents of the mask registers.
+    /// A set mask bit disables the corresponding reaction.
+    pub fn reactions(&self, masks: &FaultMasks) -> FaultReactions {
+        use FaultReactions as R;
+        let unmasked = |masked: bool, reaction: R| match masked {
+            true => R::empty(),
+            false => reaction,
+        };
+        let prdrv_bal = |mask, reaction| unmasked(masks.prdrv_bal.contains(mask), reaction);
+        let fuse_rst = |mask, reaction| unmasked(masks.fuse_rst.contains(mask), reaction);
+        let faultn = |mask| unmasked(masks.faultn.contains(mask), R::FAULTN);
+        let curr = |mask, reaction| unmasked(masks.curr.contains(mask), reaction);
+
+        match self {
+            Fault::CellUnderVoltage(_) => {
+                prdrv_bal(ToPrdrvBalMask::CELL_UV_PRDRV_MSK, R::PRDRV)
+                    | faultn(ToFaultnMsk::CELL_UF_FAULTN_MSK)
+            }
+            Fault::CellSevereUnderVoltage(_) => {
+                prdrv_bal(ToPrdrvBalMask::CELL_SEVERE_UV_PRDRV_MSK, R::PRDRV)
+                    | fuse_rst(ToFuseRstMask::CELL_SEVERE_UV_FUSE_MSK, R::FUSE)
+                    | faultn(ToFaultnMsk::CELL_SEVERE_UV_FAULTN_MSK)
+            }
+            Fault::CellOverVoltage(_) => {
+                prdrv_bal(ToPrdrvBalMask::CELL_OV_PRDRV_MSK, R::PRDRV)
+                    | fuse_rst(ToFuseRstMask::CELL_OV_RST_MSK, R::RESET)
+                    | faultn(ToFaultnMsk::CELL_OV_FAULTN_MSK)
+            }
+            Fault::CellSevereOverVoltage(_) => {
+                prdrv_bal(ToPrdrvBalMask::CELL_SEVERE_OV_PRDRV_MSK, R::PRDRV)
+                    | fuse_rst(ToFuseRstMask::CELL_SEVERE_OV_FUSE_MSK, R::FUSE)
+                    | fuse_rst(ToFuseRstMask::CELL_SEVERE_OV_RST_MSK, R::RESET)
+                    | faultn(ToFaultnMsk::CELL_SEVERE_OV_FAULTN_MSK)
+            }
+            Fault::CellBalancingUnderVoltage(_) => {
+                prdrv_bal(ToPrdrvBalMask::BAL_UV_BAL_MSK, R::BALANCING)
+                    | faultn(ToFaultnMsk::BAL_UV_FAULTN_MSK)
+            }
+            Fault::PackUnderVoltage => {
+                prdrv_bal(ToPrdrvBalMask::VB_UV_PRDRV_MSK, R::PRDRV)
+                    | faultn(ToFaultnMsk::VB_UV_FAULTN_MSK)
+            }
+            Fault::PackOverVoltage => {
+                prdrv_bal(ToPrdrvBalMask::VB_OV_PRDRV_MSK, R::PRDRV)
+                    | fuse_rst(ToFuseRstMask::VB_OV_RST_MSK, R::RESET)
+                    | faultn(ToFaultnMsk::VB_OV_FAULTN_MSK)
+            }
+            Fault::VbSumCheck => {
+                prdrv_bal(ToPrdrvBalMask::VB_SUM_CHECK_PRDRV_MSK, R::PRDRV)
+                    | prdrv_bal(ToPrdrvBalMask::VB_SUM_CHECK_BAL_MSK, R::BALANCING)
+                    | fuse_rst(ToFuseRstMask::VB_SUM_CHECK_FUSE_MSK, R::FUSE)
+                    | faultn(ToFaultnMsk::VB_SUM_CHECK_FAULTN_MSK)
+            }
+            Fault::NtcOverTemp => {
+                prdrv_bal(ToPrdrvBalMask::NTC_OT_PRDRV_MSK, R::PRDRV)
+                    | faultn(ToFaultnMsk::NTC_OT_FAULTN_MSK)
+            }
+            Fault::NtcSevereOverTemp => {
+                prdrv_bal(ToPrdrvBalMask::NTC_SEVERE_OT_PRDRV_MSK, R::PRDRV)
+                    | prdrv_bal(ToPrdrvBalMask::NTC_SEVERE_OT_BAL_MSK, R::BALANCING)
+                    | fuse_rst(ToFuseRstMask::NTC_SEVERE_OT_FUSE_MSK, R::FUSE)
+                    | faultn(ToFaultnMsk::NTC_SEVERE_OT_FAULTN_MSK)
+            }
+            Fault::NtcUnderTemp => {
+                prdrv_bal(ToPrdrvBalMask::NTC_UT_PRDRV_MSK, R::PRDRV)
+                    | faultn(ToFaultnMsk::NTC_UT_FAULTN_MSK)
+            }
+            Fault::DieOverTemp => {
+                prdrv_bal(ToPrdrvBalMask::DIE_OT_PRDRV_MSK, R::PRDRV)
+                    | prdrv_bal(ToPrdrvBalMask::DIE_OT_BAL_MSK, R::BALANCING)
+                    | faultn(ToFaultnMsk::DIE_OT_FAULTN_MSK)
+            }
+            Fault::OverCurrentCharge => {
+                curr(CurrMsk::OVC_CHG_PRDRV_MSK, R::PRDRV)
+                    | curr(CurrMsk::OVC_CHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::OverCurrentDischarge => {
+                curr(CurrMsk::OVC_DCHG_PRDRV_MSK, R::PRDRV)
+                    | curr(CurrMsk::OV_DCHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::ShortCircuitDischarge => {
+                curr(CurrMsk::SC_DCHG_PRDRV_MSK, R::PRDRV)
+                    | curr(CurrMsk::SC_DCHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::PersistentOverCurrentCharge => {
+                curr(CurrMsk::PERSIST_OVC_CHG_FUSE_MSK, R::FUSE)
+                    | curr(CurrMsk::PERSIST_OVC_CHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::PersistentOverCurrentDischarge => {
+                curr(CurrMsk::PERSIST_OVC_DCHG_FUSE_MSK, R::FUSE)
+                    | curr(CurrMsk::PERSIST_OVC_DCHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::PersistentShortCircuitDischarge => {
+                curr(CurrMsk::PERSIST_SC_DCHG_FUSE_MSK, R::FUSE)
+                    | curr(CurrMsk::PERSIST_SC_DCHG_FAULTN_MSK, R::FAULTN)
+            }
+            Fault::CoulombCounterSaturated | Fault::FuseExternal | Fault::FaultnExternal => {
+                R::empty()
+            }
+        }
+    }
+}
+
+/// Mask of a per-cell flag, where cell 1 is at bit `first_bit`
+const fn cell_mask(cell: Cell, first_bit: u8) -> u16 {
+    1 << (first_bit + cell.number() - 1)
+}
+
+impl DiagnosticFlags {
+    /// Iterate over the faults set in these flags
+    pub fn faults(&self) -> impl Iterator<Item = Fault> + '_ {
+        Fault::ALL.into_iter().filter(|fault| fault.is_set(self))
+    }
+
+    /// Flags with only the given fault set
+    pub const fn from_fault(fault: Fault) -> Self {
+        let source = fault.source();
+        let mut flags = Self::empty();
+        match source.register {
+            Registers::DiagOvOtUt => flags.ov_ot_ut = DiagOvOtUt::from_bits_truncate(source.mask),
+            Registers::DiagUv => flags.uv = DiagUv::from_bits_truncate(source.mask),
+            _ => flags.curr = DiagCurr::from_bits_truncate(source.mask),
+        }
+        flags
+    }
+}
+
+/// Result of reading and clearing the diagnostic registers
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FaultReport {
+    /// Flags set when the diagnostic registers were read
+    pub latched: DiagnosticFlags,
+    /// Flags still set after the latched flags were cleared
+    pub still_active: DiagnosticFlags,
+    /// Mask registers at the time of the read
+    pub masks: FaultMasks,
+}
+
+impl FaultReport {
+    /// Whether no faults were latched
+    pub const fn is_empty(&self) -> bool {
+        self.latched.is_empty()
+    }
+
+    /// Iterate over the latched faults
+    pub fn iter(&self) -> impl Iterator<Item = FaultRecord> + '_ {
+        self.latched.faults().map(|fault| FaultRecord {
+            fault,
+            still_active: fault.is_set(&self.still_active),
+            reactions: fault.reactions(&self.masks),
+        })
+    }
+}
@@ -0,0 +1,104 @@
+use core::convert::Infallible;
+
+use embassy_futures::select::{Either, select};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::L9961;
+
+use super::FaultReport;
+
+/// Owner of the FAULTN pin, taken from the driver with [`L9961::take_fault_monitor`].
+///
+/// Waiting on the monitor does not borrow the driver,
+/// so a dedicated task can await FAULTN while another task runs the measurement loop,
+/// and only needs access to the driver once a fault has actually been signalled.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FaultMonitor<I> {
+    fault: I,
+}
+
+impl<I> FaultMonitor<I>
+where
+    I: Wait,
+{
+    /// Wait until the FAULTN pin is asserted
+    pub async fn wait_for_fault(&mut self) -> Result<(), I::Error> {
+        self.fault.wait_for_low().await
+    }
+
+    /// Wait until the FAULTN pin is released,
+    /// which happens once every fault which asserts it has been cleared
+    pub async fn wait_for_release(&mut self) -> Result<(), I::Error> {
+        self.fault.wait_for_high().await
+    }
+
+    /// Monitor FAULTN forever, calling `on_fault` every time it is asserted.
+    ///
+    /// `on_fault` is expected to access the driver, e.g. through a mutex,
+    /// and call [`L9961::read_faults`] or [`L9961::read_logged_faults`],
+    /// then report the result through a channel or otherwise.
+    /// FAULTN stays asserted while any fault is present, so a new fault raised in the meantime produces no edge.
+    /// `on_fault` is therefore called again every `poll_ms` until FAULTN is released,
+    /// which should usually be the measurement cycle period, as the device updates its diagnostics once per cycle.
+    /// Only returns if waiting on the pin fails.
+    pub async fn run(
+        &mut self,
+        delay: &mut impl DelayNs,
+        poll_ms: u32,
+        mut on_fault: impl AsyncFnMut(),
+    ) -> Result<Infallible, I::Error> {
+        loop {
+            self.wait_for_fault().await?;
+            loop {
+                on_fault().await;
+                match select(self.wait_for_release(), delay.delay_ms(poll_ms)).await {
+                    Either::First(released) => {
+                        released?;
+                        break;
+                    }
+                    Either::Second(()) => {}
+                }
+            }
+        }
+    }
+
+    /// Release the FAULTN pin
+    pub fn release(self) -> I {
+        self.fault
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Move the FAULTN pin into a [`FaultMonitor`], so faults can be handled independently of the measurement loop.
+    /// Once taken, [`L9961::make_measurement`] no longer reacts to FAULTN,
+    /// and the application is responsible for reading the faults when the monitor signals them.
+    /// Returns `None` if the monitor has already been taken, or FAULTN is not connected.
+    pub fn take_fault_monitor(&mut self) -> Option<FaultMonitor<FAULTN>> {
+        self.fault.take().map(|fault| FaultMonitor { fault })
+    }
+
+    /// Return the FAULTN pin of a monitor to the driver, so the measurement loop handles faults again
+    pub fn restore_fault_monitor(&mut self, monitor: FaultMonitor<FAULTN>) {
+        self.fault = Some(monitor.release());
+    }
+
+    /// Read and selectively clear the diagnostic registers as [`L9961::read_faults`] does,
+    /// and record the latched faults, and those which cleared, in the fault log.
+    /// `now` provides the timestamp for any logged events.
+    pub async fn read_logged_faults(
+        &mut self,
+        now: impl FnOnce() -> u64,
+    ) -> Result<FaultReport, I2C::Error> {
+        let report = self.read_faults().await?;
+        self.fault_log
+            .record_cleared(now(), report.latched, report.still_active, None);
+        Ok(report)
+    }
+}
@@ -0,0 +1,60 @@
+//! Logging and formatting macros shared by the driver.
+//!
+//! The logging macros forward to `defmt` when the `defmt` feature is enabled, otherwise to `log`
+//! when the `log` feature is enabled, and otherwise compile to nothing.
+//! Format strings must therefore only use the `{}` placeholders understood by both.
+#![macro_use]
+#![allow(unused_macros)]
+
+macro_rules! log_with {
+    ($level:ident, $s:literal $(, $x:expr)* $(,)?) => {{
+        #[cfg(feature = "defmt")]
+        ::defmt::$level!($s $(, $x)*);
+        #[cfg(all(feature = "log", not(feature = "defmt")))]
+        ::log::$level!($s $(, $x)*);
+        #[cfg(not(any(feature = "defmt", feature = "log")))]
+        let _ = ($(&$x),*);
+    }};
+}
+
+macro_rules! trace {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        log_with!(trace, $s $(, $x)*)
+    };
+}
+
+macro_rules! debug {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        log_with!(debug, $s $(, $x)*)
+    };
+}
+
+macro_rules! info {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        log_with!(info, $s $(, $x)*)
+    };
+}
+
+macro_rules! warn {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        log_with!(warn, $s $(, $x)*)
+    };
+}
+
+macro_rules! error {
+    ($s:literal $(, $x:expr)* $(,)?) => {
+        log_with!(error, $s $(, $x)*)
+    };
+}
+
+/// Implement `Display` for a bitflags type as the names of the set flags separated by `|`,
+/// matching the `defmt` output of `defmt::bitflags`
+macro_rules! impl_display_flags {
+    ($flags:ty) => {
+        impl core::fmt::Display for $flags {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                core::fmt::Debug::fmt(self, f)
+            }
+        }
+    };
+}
@@ -8,19 +8,41 @@
 #![deny(missing_docs)]
 #![no_std]
 
+#[cfg(feature = "std")]
+extern crate std;
+
+// Must come first, so the macros are visible to every other module
+mod fmt;
+
+#[cfg(feature = "can")]
+pub mod can;
 pub mod commands;
 pub mod config;
 pub mod conversions;
 pub mod faults;
+#[cfg(feature = "coulomb_counting")]
+pub mod low_power;
 pub mod measurement;
+pub mod pins;
+pub mod publish;
 pub mod registers;
+pub mod sbs;
+pub mod secondary_protection;
+#[cfg(feature = "shared")]
+pub mod shared;
+pub mod ship_mode;
+#[cfg(feature = "std")]
+pub mod simulator;
+pub mod telemetry;
+pub mod warnings;
 
 pub use config::Config;
+pub use faults::{FaultLog, FaultMonitor};
+pub use pins::NoPin;
 pub use registers::Registers;
 
-use registers::{
-    Cfg1FiltersCycles, DiagCurr, DiagOvOtUt, DiagUv, ToFaultnMsk, ToFuseRstMask, ToPrdrvBalMask,
-};
+use faults::DiagnosticFlags;
+use registers::{Cfg1FiltersCycles, TMeasCycle, ToFaultnMsk, ToFuseRstMask, ToPrdrvBalMask};
 
 use embassy_futures::select::select;
 use embedded_hal::digital::OutputPin;
@@ -28,12 +50,16 @@ use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
 
 /// L9961 Industrial BMS Driver
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
-pub struct L9961<I2C, I, O> {
+pub struct L9961<I2C, READY, FAULTN, WAKEUP> {
     i2c: I2C,
-    ready: I,
-    fault: I,
-    wake: O,
+    // READY and FAULTN are also absent while they are held by a shared handle or fault monitor
+    ready: Option<READY>,
+    fault: Option<FAULTN>,
+    wake: Option<WAKEUP>,
+    // FAULTN is not connected, so the diagnostic registers are read with every measurement
+    poll_faults: bool,
     config: Config,
+    fault_log: FaultLog,
     //keep a large enough buffer to read measurement run of 9 registers
     // write address + register + read address + (2 bytes + crc * each register)
     i2c_scratch_buffer: [u8; 30],
@@ -41,33 +67,59 @@ pub struct L9961<I2C, I, O> {
     i2c_results: [u16; 9],
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: I2c,
-    I: Wait,
-    O: OutputPin,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
 {
     /// Create a new instance of the ST L9961 driver for the given blocking I2C bus and address.
-    pub fn new(i2c: I2C, ready: I, fault: I, wake: O, config: Config) -> Self {
+    pub fn new(i2c: I2C, ready: READY, fault: FAULTN, wake: WAKEUP, config: Config) -> Self {
+        Self::new_with_optional_pins(i2c, Some(ready), Some(fault), Some(wake), config)
+    }
+
+    /// Create a new instance of the driver for a board which does not connect every pin to the MCU.
+    /// See the [`pins`] module for how the driver works without each pin.
+    pub fn new_with_optional_pins(
+        i2c: I2C,
+        ready: Option<READY>,
+        fault: Option<FAULTN>,
+        wake: Option<WAKEUP>,
+        config: Config,
+    ) -> Self {
         Self {
             i2c,
+            poll_faults: fault.is_none(),
             ready,
             fault,
             wake,
             config,
+            fault_log: FaultLog::new(),
             i2c_scratch_buffer: [0; 30],
             i2c_results: [0; 9],
         }
     }
 
-    /// Wake up the l9961 if it is asleep
+    /// The log of fault onset and clear events recorded by [`L9961::make_logged_measurement`]
+    pub fn fault_log(&self) -> &FaultLog {
+        &self.fault_log
+    }
+
+    /// Mutable access to the fault log, e.g. to drain events or reset the counters
+    pub fn fault_log_mut(&mut self) -> &mut FaultLog {
+        &mut self.fault_log
+    }
+
+    /// Wake up the l9961 if it is asleep.
+    /// Does nothing without WAKEUP.
     pub async fn wake_if_asleep(&mut self, delay: &mut impl DelayNs) {
-        self.wake.set_high().unwrap();
-        match select(self.ready.wait_for_any_edge(), delay.delay_ms(100)).await {
-            embassy_futures::select::Either::First(result) => result.unwrap(),
-            embassy_futures::select::Either::Second(_) => (),
-        }
-        self.wake.set_low().unwrap();
+        self.pulse_wakeup(delay).await;
+    }
+
+    /// Assert WAKEUP until READY toggles or 100 ms have passed, returning whether READY toggled
+    async fn pulse_wakeup(&mut self, delay: &mut impl DelayNs) -> bool {
+        pulse_wakeup(self.wake.as_mut(), self.ready.as_mut(), delay).await
     }
 
     /// Ensure that the device is in standby mode
@@ -83,11 +135,16 @@ where
             .await
     }
 
-    /// Clear all fault registers
-    pub async fn clear_all_faults(&mut self) -> Result<(), I2C::Error> {
-        self.write_diag_ov_ot_ut(DiagOvOtUt::all()).await?;
-        self.write_diag_curr(DiagCurr::all()).await?;
-        self.write_diag_uv(DiagUv::all()).await
+    /// Change the period of the measurement cycle, keeping the configured filters, and restart measurements at the new period
+    pub async fn set_measurement_cycle(&mut self, cycle: TMeasCycle) -> Result<(), I2C::Error> {
+        self.config.measurement_cycles.set_t_meas_cycle(cycle);
+        self.enable_measurements().await
+    }
+
+    /// Clear every latched fault, returning the faults which were still set afterwards
+    pub async fn clear_all_faults(&mut self) -> Result<DiagnosticFlags, I2C::Error> {
+        let flags = self.read_diagnostic_flags().await?;
+        self.clear_faults(flags).await
     }
 
     /// Mask all fault assertions for development purposes
@@ -97,3 +154,31 @@ where
         self.write_to_fuse_rst_msk(ToFuseRstMask::all()).await
     }
 }
+
+/// Assert WAKEUP until READY toggles or 100 ms have passed, returning whether READY toggled.
+/// Without READY the full 100 ms pulse is applied, and without WAKEUP no pulse is applied.
+async fn pulse_wakeup<READY: Wait, WAKEUP: OutputPin>(
+    wake: Option<&mut WAKEUP>,
+    ready: Option<&mut READY>,
+    delay: &mut impl DelayNs,
+) -> bool {
+    let Some(wake) = wake else {
+        return false;
+    };
+    let ready_toggled = async move {
+        match ready {
+            Some(ready) => ready.wait_for_any_edge().await,
+            None => core::future::pending().await,
+        }
+    };
+    wake.set_high().unwrap();
+    let ready = match select(ready_toggled, delay.delay_ms(100)).await {
+        embassy_futures::select::Either::First(result) => {
+            result.unwrap();
+            true
+        }
+        embassy_futures::select::Either::Second(_) => false,
+    };
+    wake.set_low().unwrap();
+    ready
+}
@@ -0,0 +1,337 @@
+//! # Low power monitoring
+//! Duty cycling of the L9961 for packs which spend long periods idle, e.g. stored in idle equipment.
+//!
+//! While current flows the device is measured at a fast measurement cycle, and once the current falls
+//! to the idle threshold the cycle is slowed down.
+//! After enough consecutive idle measurements the device is sent to standby, and the application decides
+//! how long to wait before the next call to [`L9961::make_low_power_measurement`] wakes it through the WAKEUP pin.
+//! Voltage and temperature protection stop while the device is in standby,
+//! so the standby period bounds how long a fault can go unnoticed.
+//!
+//! The coulomb counter is banked into a [`ChargeAccumulator`] after every measurement and before standby,
+//! so the accumulated charge stays coherent across changes of the measurement cycle and periods in standby.
+//! The device accumulator is not cleared while banking, as a sample made between the read and the clear
+//! would be lost. Instead the change since the previous read is banked, taken modulo the width of the
+//! 24 bit accumulator and 8 bit sample counter so that a wrap of either between two reads is accounted for.
+//! The accumulator is only cleared once the device reports it saturated (CC_SAT).
+//! Faults are read and logged before entering standby and after waking, and standby is not entered while
+//! a fault is still present.
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+//! # use embedded_hal::digital::OutputPin;
+//! use l9961::{
+//!     L9961,
+//!     low_power::{LowPowerConfig, LowPowerMode, PowerState},
+//! };
+//!
+//! # async fn run<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//! #     mut l9961: L9961<I2C, READY, FAULTN, WAKEUP>,
+//! #     mut delay: impl DelayNs,
+//! #     now: impl Fn() -> u64,
+//! # ) -> Result<(), I2C::Error> {
+//! let mut mode = LowPowerMode::new(LowPowerConfig::default());
+//! loop {
+//!     let _measurement = l9961
+//!         .make_low_power_measurement(&mut mode, &mut delay, &now)
+//!         .await?;
+//!     if mode.state() == PowerState::Standby {
+//!         // Check on the pack once a minute while it is in standby
+//!         delay.delay_ms(60_000).await;
+//!     }
+//! }
+//! # }
+//! ```
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961, Registers,
+    conversions::{charge_uah_from_accumulated_code, current_ma_from_signed_code},
+    faults::PackFaults,
+    measurement::Measurement,
+    registers::{CCAccLsbCntr, TCurFilter, TMeasCycle},
+};
+
+/// Writing 1 to every bit of the latched CC_ACC_MSB register clears the accumulator and sample counter
+const CC_ACC_CLEAR: u16 = 0xFFFF;
+
+/// Settings of the low power mode
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LowPowerConfig {
+    /// Measurement cycle used while current flows
+    pub active_cycle: TMeasCycle,
+    /// Measurement cycle used while the pack is idle
+    pub idle_cycle: TMeasCycle,
+    /// Magnitude of the current in mA at or below which the pack is idle
+    pub idle_current_ma: u16,
+    /// Number of consecutive idle measurements after which the device is sent to standby,
+    /// or `None` to never enter standby
+    pub standby_after_idle_measurements: Option<u16>,
+}
+
+impl LowPowerConfig {
+    /// Measure every 100 ms while more than 20 mA flows and every 300 ms otherwise,
+    /// entering standby after 30 s idle
+    pub const fn new() -> Self {
+        Self {
+            active_cycle: TMeasCycle::new_ms(100),
+            idle_cycle: TMeasCycle::new_ms(300),
+            idle_current_ma: 20,
+            standby_after_idle_measurements: Some(100),
+        }
+    }
+}
+
+impl Default for LowPowerConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Power state of the low power mode
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerState {
+    /// Measuring at the active cycle
+    Active,
+    /// Measuring at the idle cycle
+    Idle,
+    /// In standby, woken by the next measurement
+    Standby,
+}
+
+/// Charge accumulated by the coulomb counter, banked from the device accumulator
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ChargeAccumulator {
+    accumulated_code: i64,
+    samples: u32,
+}
+
+impl ChargeAccumulator {
+    /// Create an empty accumulator
+    pub const fn new() -> Self {
+        Self {
+            accumulated_code: 0,
+            samples: 0,
+        }
+    }
+
+    /// Add the contents of the device accumulator,
+    /// given the 24 bit accumulator value as read into [`Measurement::cc_acc`]
+    pub const fn bank(&mut self, cc_acc: i32, cc_samples: u8) {
+        // Sign extend the 24 bit accumulator
+        let cc_acc = (cc_acc << 8) >> 8;
+        self.accumulated_code += cc_acc as i64;
+        self.samples = self.samples.saturating_add(cc_samples as u32);
+    }
+
+    /// Sum of the instantaneous current codes of every banked sample
+    pub const fn accumulated_code(&self) -> i64 {
+        self.accumulated_code
+    }
+
+    /// Number of banked samples
+    pub const fn samples(&self) -> u32 {
+        self.samples
+    }
+
+    /// Net charge in uAh, positive while charging, given the current filter each sample was integrated over
+    pub const fn charge_uah(&self, current_filter: TCurFilter) -> i64 {
+        charge_uah_from_accumulated_code(self.accumulated_code, current_filter.duration_us())
+    }
+
+    /// Empty the accumulator, e.g. once the charge has been added to a state of charge estimate
+    pub const fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// State of the low power mode, advanced by [`L9961::make_low_power_measurement`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct LowPowerMode {
+    config: LowPowerConfig,
+    state: PowerState,
+    idle_measurements: u16,
+    // Whether the cycle of the current state has been written to the device
+    cycle_applied: bool,
+    charge: ChargeAccumulator,
+    // Device accumulator and sample counter at the previous read
+    last_cc_acc: i32,
+    last_cc_samples: u8,
+}
+
+impl LowPowerMode {
+    /// Create the mode in the active state.
+    /// The first measurement switches the device to the cycle of the state matching the measured current.
+    pub const fn new(config: LowPowerConfig) -> Self {
+        Self {
+            config,
+            state: PowerState::Active,
+            idle_measurements: 0,
+            cycle_applied: false,
+            charge: ChargeAccumulator::new(),
+            last_cc_acc: 0,
+            last_cc_samples: 0,
+        }
+    }
+
+    /// The settings of the mode
+    pub const fn config(&self) -> &LowPowerConfig {
+        &self.config
+    }
+
+    /// The current power state
+    pub const fn state(&self) -> PowerState {
+        self.state
+    }
+
+    /// Number of consecutive idle measurements
+    pub const fn idle_measurements(&self) -> u16 {
+        self.idle_measurements
+    }
+
+    /// Charge banked from the coulomb counter
+    pub const fn charge(&self) -> &ChargeAccumulator {
+        &self.charge
+    }
+
+    /// Mutable access to the banked charge, e.g. to reset it
+    pub const fn charge_mut(&mut self) -> &mut ChargeAccumulator {
+        &mut self.charge
+    }
+
+    /// Bank the change of the device accumulator and sample counter since the previous read
+    const fn bank_reading(&mut self, cc_acc: i32, cc_samples: u8) {
+        self.charge.bank(
+            cc_acc.wrapping_sub(self.last_cc_acc) & 0x00FF_FFFF,
+            cc_samples.wrapping_sub(self.last_cc_samples),
+        );
+        self.last_cc_acc = cc_acc;
+        self.last_cc_samples = cc_samples;
+    }
+
+    /// Whether enough consecutive idle measurements have been made to enter standby
+    const fn standby_due(&self) -> bool {
+        match self.config.standby_after_idle_measurements {
+            Some(threshold) => self.idle_measurements >= threshold,
+            None => false,
+        }
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Make a logged measurement in the low power mode, waking the device first if it is in standby.
+    /// The measurement cycle is switched according to the measured current, the coulomb counter is banked,
+    /// and the device is sent to standby once it has been idle for long enough.
+    /// `now` provides the timestamp for any logged fault events.
+    ///
+    /// The mode takes ownership of the measurement cycle and the coulomb counter accumulator,
+    /// which should not be changed or read elsewhere while it is in use.
+    pub async fn make_low_power_measurement(
+        &mut self,
+        mode: &mut LowPowerMode,
+        delay: &mut impl DelayNs,
+        mut now: impl FnMut() -> u64,
+    ) -> Result<Option<Measurement>, I2C::Error> {
+        if mode.state == PowerState::Standby {
+            self.wake_from_standby(mode, delay, &mut now).await?;
+        }
+
+        let Some(measurement) = self.make_logged_measurement(delay, &mut now).await? else {
+            return Ok(None);
+        };
+        mode.bank_reading(measurement.cc_acc, measurement.cc_samples);
+        if measurement
+            .pack_faults
+            .contains(PackFaults::COULOMB_COUNTER_SATURATED)
+        {
+            warn!("Coulomb counter saturated, clearing the accumulator");
+            self.write_cc_acc_msb(CC_ACC_CLEAR).await?;
+            mode.last_cc_acc = 0;
+            mode.last_cc_samples = 0;
+        }
+
+        let current_ma = current_ma_from_signed_code(measurement.cc_inst_meas).unsigned_abs();
+        let (state, cycle) = if current_ma > mode.config.idle_current_ma as u32 {
+            mode.idle_measurements = 0;
+            (PowerState::Active, mode.config.active_cycle)
+        } else {
+            mode.idle_measurements = mode.idle_measurements.saturating_add(1);
+            (PowerState::Idle, mode.config.idle_cycle)
+        };
+        if state != mode.state || !mode.cycle_applied {
+            debug!("Switching to a {}ms measurement cycle", cycle.period_ms());
+            self.set_measurement_cycle(cycle).await?;
+            mode.state = state;
+            mode.cycle_applied = true;
+        }
+        if mode.standby_due() {
+            self.enter_standby(mode, &mut now).await?;
+        }
+        Ok(Some(measurement))
+    }
+
+    /// Log any latched faults and send the device to standby, unless a fault is still present
+    async fn enter_standby(
+        &mut self,
+        mode: &mut LowPowerMode,
+        now: impl FnOnce() -> u64,
+    ) -> Result<(), I2C::Error> {
+        let report = self.read_logged_faults(now).await?;
+        if !report.still_active.is_empty() {
+            warn!("Standby postponed while faults are present");
+            return Ok(());
+        }
+        self.bank_coulomb_counter(mode).await?;
+        info!(
+            "Entering standby after {} idle measurements",
+            mode.idle_measurements
+        );
+        self.go_2_standby().await?;
+        mode.state = PowerState::Standby;
+        Ok(())
+    }
+
+    /// Wake the device, log any faults latched while waking and resume measuring at the idle cycle.
+    /// A single idle measurement afterwards returns the device to standby.
+    async fn wake_from_standby(
+        &mut self,
+        mode: &mut LowPowerMode,
+        delay: &mut impl DelayNs,
+        now: impl FnOnce() -> u64,
+    ) -> Result<(), I2C::Error> {
+        self.wake_if_asleep(delay).await;
+        self.read_logged_faults(now).await?;
+        // Anything counted while waking belongs to the period in standby
+        self.bank_coulomb_counter(mode).await?;
+        self.set_measurement_cycle(mode.config.idle_cycle).await?;
+        mode.state = PowerState::Idle;
+        mode.cycle_applied = true;
+        mode.idle_measurements = mode
+            .config
+            .standby_after_idle_measurements
+            .unwrap_or(0)
+            .saturating_sub(1);
+        Ok(())
+    }
+
+    /// Read the coulomb counter accumulator and bank its change since the previous read
+    async fn bank_coulomb_counter(&mut self, mode: &mut LowPowerMode) -> Result<(), I2C::Error> {
+        let cc_registers = self.read_registers(Registers::CCAccMsb, 2).await?;
+        let lsb_cntr = CCAccLsbCntr::from(cc_registers[1]);
+        let cc_acc = ((cc_registers[0] as u32) << 8 | lsb_cntr.get_cc_acc_lsb() as u32) as i32;
+        mode.bank_reading(cc_acc, lsb_cntr.get_cc_sample_cnt());
+        Ok(())
+    }
+}
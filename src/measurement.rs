@@ -6,21 +6,33 @@ use crate::registers::CCAccLsbCntr;
 #[cfg(feature = "ntc")]
 use crate::registers::NtcGpio;
 
+use core::fmt::{Display, Formatter};
+
 use crate::{
     L9961, Registers,
     conversions::{cell_voltage_measurement_mv_from_code, pack_voltage_measurement_mv_from_code},
-    faults::{CellFaults, PackFaults},
+    faults::{CellFaults, DiagnosticFlags, PackFaults},
     registers::{DieTemp, VB, VCell, VCellSum},
 };
 
-use defmt::info;
 use embassy_futures::select::select3;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
 
+/// Time to wait before checking again whether measurements have been enabled, while the measurement cycle is disabled
+pub(crate) const DISABLED_CYCLE_BACKOFF_MS: u32 = 100;
+
+/// Diagnostic flags read alongside a measurement, and those still set after they were cleared
+#[derive(Clone, Copy)]
+pub(crate) struct SignalledFaults {
+    pub(crate) latched: DiagnosticFlags,
+    pub(crate) still_active: DiagnosticFlags,
+}
+
 /// A single cell measurement
 #[derive(Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct CellMeasurement {
     /// Cell voltage in mV
     pub voltage_mv: u16,
@@ -38,7 +50,9 @@ impl Default for CellMeasurement {
 }
 
 /// Struct representing data collected from a single measurement cycle
+#[derive(Clone, Copy)]
 #[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Measurement {
     /// Cell 1 measurement
     pub cell_1: CellMeasurement,
@@ -60,6 +74,7 @@ pub struct Measurement {
     #[cfg(feature = "ntc")]
     pub ntc_mv: u16,
     /// Die temp in degrees Celsius
+    #[cfg_attr(feature = "serde", serde(rename = "die_temp_c"))]
     pub die_temp: u16,
     /// Instantaneous current measurement for coulomb counting
     #[cfg(feature = "coulomb_counting")]
@@ -100,50 +115,187 @@ impl Default for Measurement {
     }
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
+impl Measurement {
+    /// Get the measurement of a cell (1 indexed per device), if that cell is enabled
+    pub fn cell(&self, cell: u8) -> Option<&CellMeasurement> {
+        match cell {
+            1 => Some(&self.cell_1),
+            2 => Some(&self.cell_2),
+            3 => Some(&self.cell_3),
+            #[cfg(feature = "4_cells")]
+            4 => Some(&self.cell_4),
+            #[cfg(feature = "5_cells")]
+            5 => Some(&self.cell_5),
+            _ => None,
+        }
+    }
+
+    /// Get mutable access to the measurement of a cell (1 indexed per device), if that cell is enabled
+    pub fn cell_mut(&mut self, cell: u8) -> Option<&mut CellMeasurement> {
+        match cell {
+            1 => Some(&mut self.cell_1),
+            2 => Some(&mut self.cell_2),
+            3 => Some(&mut self.cell_3),
+            #[cfg(feature = "4_cells")]
+            4 => Some(&mut self.cell_4),
+            #[cfg(feature = "5_cells")]
+            5 => Some(&mut self.cell_5),
+            _ => None,
+        }
+    }
+}
+
+impl Display for CellMeasurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CellMeasurement {{ voltage_mv: {}, faults: {} }}",
+            self.voltage_mv, self.faults
+        )
+    }
+}
+
+impl Display for Measurement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Measurement {{ cell_1: {}, cell_2: {}, cell_3: {}",
+            self.cell_1, self.cell_2, self.cell_3
+        )?;
+        #[cfg(feature = "4_cells")]
+        write!(f, ", cell_4: {}", self.cell_4)?;
+        #[cfg(feature = "5_cells")]
+        write!(f, ", cell_5: {}", self.cell_5)?;
+        write!(
+            f,
+            ", cell_sum_mv: {}, vbat_mv: {}",
+            self.cell_sum_mv, self.vbat_mv
+        )?;
+        #[cfg(feature = "ntc")]
+        write!(f, ", ntc_mv: {}", self.ntc_mv)?;
+        write!(f, ", die_temp: {}", self.die_temp)?;
+        #[cfg(feature = "coulomb_counting")]
+        write!(
+            f,
+            ", cc_inst_meas: {}, cc_acc: {}, cc_samples: {}",
+            self.cc_inst_meas, self.cc_acc, self.cc_samples
+        )?;
+        write!(f, ", pack_faults: {} }}", self.pack_faults)
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: I2c,
-    I: Wait,
-    O: OutputPin,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
 {
-    /// Wait for the device to complete a measurement
+    /// Wait for the device to complete a measurement.
+    /// See the [`crate::pins`] module for how measurements are made when READY or FAULTN is not connected.
     pub async fn make_measurement(
         &mut self,
         delay: &mut impl DelayNs,
     ) -> Result<Option<Measurement>, I2C::Error> {
-        let cycle_time = self
-            .config
-            .measurement_cycles
-            .get_t_meas_cycle()
-            .period_ms();
+        Ok(self
+            .wait_for_measurement(delay, false)
+            .await?
+            .map(|(measurement, _)| measurement))
+    }
 
-        match select3(
-            self.ready.wait_for_any_edge(),
-            self.fault.wait_for_low(),
-            delay.delay_ms(cycle_time as u32),
-        )
-        .await
-        {
-            embassy_futures::select::Either3::First(result) => {
-                result.unwrap();
-                let mut measurement = Measurement::default();
-                self.read_measurement_registers(&mut measurement).await?;
-                Ok(Some(measurement))
-            }
-            embassy_futures::select::Either3::Second(result) => {
-                result.unwrap();
-                let mut measurement = Measurement::default();
-                self.read_fault_registers(&mut measurement).await?;
-                self.clear_fault_registers().await?;
-                self.read_measurement_registers(&mut measurement).await?;
-                self.ready.wait_for_any_edge().await.unwrap();
+    /// Read the most recent measurement without waiting for a new one.
+    /// The diagnostic registers are read into the measurement but not cleared,
+    /// so a device whose faults are handled elsewhere can be observed without disturbing it.
+    pub async fn read_measurement(&mut self) -> Result<Measurement, I2C::Error> {
+        let mut measurement = Measurement::default();
+        self.read_fault_registers(&mut measurement).await?;
+        self.read_measurement_registers(&mut measurement).await?;
+        Ok(measurement)
+    }
+
+    /// Wait for the device to complete a measurement, and record any fault onsets or clears in the fault log.
+    /// `now` is called once the measurement has been read, and provides the timestamp for any logged events.
+    /// While faults are active, the diagnostic registers are also read on regular measurement cycles,
+    /// so that faults which are no longer present are logged as cleared.
+    pub async fn make_logged_measurement(
+        &mut self,
+        delay: &mut impl DelayNs,
+        now: impl FnOnce() -> u64,
+    ) -> Result<Option<Measurement>, I2C::Error> {
+        let read_diagnostics = !self.fault_log.active().is_empty();
+        match self.wait_for_measurement(delay, read_diagnostics).await? {
+            Some((measurement, faults)) => {
+                if let Some(faults) = faults {
+                    self.fault_log.record_cleared(
+                        now(),
+                        faults.latched,
+                        faults.still_active,
+                        Some(&measurement),
+                    );
+                }
                 Ok(Some(measurement))
             }
-            embassy_futures::select::Either3::Third(()) => {
-                info!("Timed out after waiting for {}ms", cycle_time);
-                Ok(None)
-            }
+            None => Ok(None),
+        }
+    }
+
+    /// Wait for READY, FAULTN or a timeout of one measurement cycle.
+    /// The diagnostic registers are read when FAULTN asserts, and the flags which were read are cleared,
+    /// or on READY if `read_diagnostics` is set or FAULTN is not connected.
+    /// FAULTN is ignored once the [`crate::FaultMonitor`] has been taken.
+    async fn wait_for_measurement(
+        &mut self,
+        delay: &mut impl DelayNs,
+        read_diagnostics: bool,
+    ) -> Result<Option<(Measurement, Option<SignalledFaults>)>, I2C::Error> {
+        let cycle_time = self.measurement_cycle_ms();
+        let signal =
+            wait_for_signal(self.ready.as_mut(), self.fault.as_mut(), delay, cycle_time).await;
+        let result = self
+            .read_signalled_measurement(signal, read_diagnostics)
+            .await?;
+        if signal == MeasurementSignal::Fault {
+            wait_for_cycle_end(self.ready.as_mut(), delay, cycle_time).await;
         }
+        Ok(result)
+    }
+
+    /// Period of the configured measurement cycle in ms
+    pub(crate) const fn measurement_cycle_ms(&self) -> u16 {
+        self.config
+            .measurement_cycles
+            .get_t_meas_cycle()
+            .period_ms()
+    }
+
+    /// Read the measurement signalled by READY or FAULTN, as `wait_for_measurement` does.
+    /// When FAULTN signalled the measurement, the caller should wait for the following READY edge
+    /// before waiting for the next signal.
+    /// If the diagnostic registers were read, the latched flags are returned along with those still set after clearing.
+    pub(crate) async fn read_signalled_measurement(
+        &mut self,
+        signal: MeasurementSignal,
+        read_diagnostics: bool,
+    ) -> Result<Option<(Measurement, Option<SignalledFaults>)>, I2C::Error> {
+        let read_diagnostics = match signal {
+            MeasurementSignal::Ready => read_diagnostics || self.poll_faults,
+            MeasurementSignal::Fault => true,
+            MeasurementSignal::Timeout => return Ok(None),
+        };
+        let mut measurement = Measurement::default();
+        let faults = match read_diagnostics {
+            true => {
+                let latched = self.read_fault_registers(&mut measurement).await?;
+                let still_active = self.clear_faults(latched).await?;
+                Some(SignalledFaults {
+                    latched,
+                    still_active,
+                })
+            }
+            false => None,
+        };
+        self.read_measurement_registers(&mut measurement).await?;
+        Ok(Some((measurement, faults)))
     }
 
     async fn read_measurement_registers(
@@ -197,3 +349,80 @@ where
         Ok(())
     }
 }
+
+/// What ended the wait for a measurement
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) enum MeasurementSignal {
+    /// READY toggled, so a measurement completed
+    Ready,
+    /// FAULTN asserted
+    Fault,
+    /// Neither pin changed within one measurement cycle
+    Timeout,
+}
+
+/// Wait for the end of the measurement cycle in which FAULTN signalled a measurement.
+/// A fault which persists keeps FAULTN asserted, so without this the next wait would return immediately.
+/// Without READY, one measurement cycle is waited instead, or a short backoff while the cycle is disabled.
+pub(crate) async fn wait_for_cycle_end<READY: Wait>(
+    ready: Option<&mut READY>,
+    delay: &mut impl DelayNs,
+    cycle_time: u16,
+) {
+    match (ready, cycle_time) {
+        (Some(ready), _) => ready.wait_for_any_edge().await.unwrap(),
+        (None, 0) => delay.delay_ms(DISABLED_CYCLE_BACKOFF_MS).await,
+        (None, cycle_time) => delay.delay_ms(cycle_time as u32).await,
+    }
+}
+
+/// Wait for READY, FAULTN or a timeout of `cycle_time` ms.
+/// FAULTN is never signalled while it is missing.
+/// Without READY, the end of the measurement cycle signals the measurement instead of a timeout.
+pub(crate) async fn wait_for_signal<READY: Wait, FAULTN: Wait>(
+    ready: Option<&mut READY>,
+    fault: Option<&mut FAULTN>,
+    delay: &mut impl DelayNs,
+    cycle_time: u16,
+) -> MeasurementSignal {
+    // Nothing is measured while the measurement cycle is disabled
+    let period_elapsed = match ready.is_none() && cycle_time > 0 {
+        true => MeasurementSignal::Ready,
+        false => MeasurementSignal::Timeout,
+    };
+    let ready_toggled = async move {
+        match ready {
+            Some(ready) => ready.wait_for_any_edge().await,
+            None => core::future::pending().await,
+        }
+    };
+    let fault_asserted = async move {
+        match fault {
+            Some(fault) => fault.wait_for_low().await,
+            None => core::future::pending().await,
+        }
+    };
+
+    match select3(
+        ready_toggled,
+        fault_asserted,
+        delay.delay_ms(cycle_time as u32),
+    )
+    .await
+    {
+        embassy_futures::select::Either3::First(result) => {
+            result.unwrap();
+            MeasurementSignal::Ready
+        }
+        embassy_futures::select::Either3::Second(result) => {
+            result.unwrap();
+            MeasurementSignal::Fault
+        }
+        embassy_futures::select::Either3::Third(()) => {
+            if period_elapsed == MeasurementSignal::Timeout {
+                info!("Timed out after waiting for {}ms", cycle_time);
+            }
+            period_elapsed
+        }
+    }
+}
@@ -0,0 +1,80 @@
+//! # Pins
+//! The READY, FAULTN and WAKEUP pins are each optional, for boards which do not route them to the MCU.
+//! A driver created with [`L9961::new_with_optional_pins`] falls back as follows for each missing pin:
+//! - Without READY, a measurement is read once the measurement period has passed,
+//!   rather than as soon as the device signals that it completed.
+//!   The period is taken from the configuration, so measurements disabled with [`L9961::disable_measurements`]
+//!   keep being read, and hold the values of the last measurement.
+//!   After FAULTN signals a measurement, the rest of the measurement period is waited out before waiting
+//!   for the next signal, as a fault which persists keeps FAULTN asserted.
+//! - Without FAULTN, the diagnostic registers DIAG_OV_OT_UT, DIAG_UV and DIAG_CURR are polled with every measurement,
+//!   so faults are detected at the next measurement rather than as they occur.
+//! - Without WAKEUP, the device is not pulsed awake. It must be woken by other means, e.g. by connecting a charger.
+//!
+//! [`NoPin`] provides the type of a missing pin.
+//!
+//! ```no_run
+//! # use embedded_hal_async::{digital::Wait, i2c::I2c};
+//! use l9961::{Config, L9961, NoPin};
+//!
+//! # fn create<I2C: I2c, R: Wait>(i2c: I2C, ready: R) {
+//! // Only READY is routed to the MCU
+//! let l9961 = L9961::new_with_optional_pins(
+//!     i2c,
+//!     Some(ready),
+//!     None::<NoPin>,
+//!     None::<NoPin>,
+//!     Config::default(),
+//! );
+//! # }
+//! ```
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::{ErrorType, OutputPin};
+use embedded_hal_async::digital::Wait;
+
+#[cfg(doc)]
+use crate::L9961;
+
+/// Stands in for a pin which is not connected, as the type of `None` for an absent pin.
+/// Waiting on it never completes, and driving it does nothing.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct NoPin;
+
+impl ErrorType for NoPin {
+    type Error = Infallible;
+}
+
+impl Wait for NoPin {
+    async fn wait_for_high(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_low(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_rising_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_falling_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+
+    async fn wait_for_any_edge(&mut self) -> Result<(), Infallible> {
+        core::future::pending().await
+    }
+}
+
+impl OutputPin for NoPin {
+    fn set_low(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+
+    fn set_high(&mut self) -> Result<(), Infallible> {
+        Ok(())
+    }
+}
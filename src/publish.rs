@@ -0,0 +1,159 @@
+//! # Measurement publishing
+//! A reusable measurement loop which fans its results out to the rest of the application.
+//!
+//! [`L9961::run_measurement_loop`] makes logged measurements forever, and hands every result to a [`MeasurementPublisher`]:
+//! - every completed measurement
+//! - every fault event, taken out of the driver's [`crate::FaultLog`] once the measurement has been read
+//! - the total number of missed measurement cycles, each time a cycle passes without READY or FAULTN
+//!
+//! While the measurement cycle is disabled the loop checks back every 100 ms, and no cycles are counted as missed.
+//!
+//! Publishers are provided for the `embassy-sync` [`MeasurementChannels`] with the `shared` feature,
+//! and for RTIC 2 shared resources with the `rtic` feature through [`RticPublisher`].
+//! Applications can also implement [`MeasurementPublisher`] for their own channels.
+//! With the `shared` feature the loop can also be run through a [`crate::shared::SharedL9961`],
+//! so other tasks can still issue commands while it runs.
+
+#[cfg(feature = "shared")]
+mod embassy;
+#[cfg(feature = "rtic")]
+mod rtic;
+
+#[cfg(feature = "shared")]
+pub use embassy::MeasurementChannels;
+#[cfg(feature = "rtic")]
+pub use rtic::{PublishedMeasurements, RticPublisher};
+
+use core::convert::Infallible;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961,
+    faults::FaultEvent,
+    measurement::{DISABLED_CYCLE_BACKOFF_MS, Measurement},
+};
+
+/// Destination for the results of [`L9961::run_measurement_loop`].
+/// Publishing must not block, so the measurement loop is never held up by slow consumers.
+pub trait MeasurementPublisher {
+    /// Publish a completed measurement
+    fn publish_measurement(&mut self, measurement: &Measurement);
+
+    /// Publish a fault onset or clear event
+    fn publish_fault_event(&mut self, event: &FaultEvent);
+
+    /// Publish the total number of measurement cycles which passed without READY or FAULTN
+    fn publish_missed_cycles(&mut self, missed_cycles: u32);
+}
+
+impl<P: MeasurementPublisher + ?Sized> MeasurementPublisher for &mut P {
+    fn publish_measurement(&mut self, measurement: &Measurement) {
+        (**self).publish_measurement(measurement);
+    }
+
+    fn publish_fault_event(&mut self, event: &FaultEvent) {
+        (**self).publish_fault_event(event);
+    }
+
+    fn publish_missed_cycles(&mut self, missed_cycles: u32) {
+        (**self).publish_missed_cycles(missed_cycles);
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Make logged measurements forever, publishing every measurement, fault event and missed cycle.
+    /// `now` provides the timestamp of logged fault events.
+    /// Only returns if reading the device fails.
+    pub async fn run_measurement_loop(
+        &mut self,
+        delay: &mut impl DelayNs,
+        mut now: impl FnMut() -> u64,
+        mut publisher: impl MeasurementPublisher,
+    ) -> Result<Infallible, I2C::Error> {
+        let mut missed_cycles = 0u32;
+        loop {
+            let measurement = self.make_logged_measurement(delay, &mut now).await?;
+            let cycle_disabled = self.measurement_cycle_ms() == 0;
+            publish(
+                &mut publisher,
+                measurement.as_ref(),
+                cycle_disabled,
+                &mut missed_cycles,
+                self.fault_log.drain(),
+            );
+            if measurement.is_none() && cycle_disabled {
+                delay.delay_ms(DISABLED_CYCLE_BACKOFF_MS).await;
+            }
+        }
+    }
+}
+
+#[cfg(feature = "shared")]
+impl<M, I2C, READY, FAULTN, WAKEUP, const N: usize>
+    crate::shared::SharedL9961<M, I2C, READY, FAULTN, WAKEUP, N>
+where
+    M: embassy_sync::blocking_mutex::raw::RawMutex,
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Make logged measurements forever through the handle, as [`L9961::run_measurement_loop`] does.
+    /// The driver is only locked while a measurement is read and its fault events are taken out of the fault log.
+    pub async fn run_measurement_loop(
+        &self,
+        delay: &mut impl DelayNs,
+        mut now: impl FnMut() -> u64,
+        mut publisher: impl MeasurementPublisher,
+    ) -> Result<Infallible, I2C::Error> {
+        let mut missed_cycles = 0u32;
+        loop {
+            let measurement = self.make_logged_measurement(delay, &mut now).await?;
+            let cycle_disabled = {
+                let mut driver = self.lock().await;
+                let cycle_disabled = driver.measurement_cycle_ms() == 0;
+                publish(
+                    &mut publisher,
+                    measurement.as_ref(),
+                    cycle_disabled,
+                    &mut missed_cycles,
+                    driver.fault_log.drain(),
+                );
+                cycle_disabled
+            };
+            if measurement.is_none() && cycle_disabled {
+                delay.delay_ms(DISABLED_CYCLE_BACKOFF_MS).await;
+            }
+        }
+    }
+}
+
+/// Publish the outcome of one iteration of the measurement loop.
+/// No cycle is missed while the measurement cycle is disabled.
+fn publish(
+    publisher: &mut impl MeasurementPublisher,
+    measurement: Option<&Measurement>,
+    cycle_disabled: bool,
+    missed_cycles: &mut u32,
+    events: impl Iterator<Item = FaultEvent>,
+) {
+    match measurement {
+        Some(measurement) => publisher.publish_measurement(measurement),
+        None if cycle_disabled => (),
+        None => {
+            *missed_cycles = missed_cycles.saturating_add(1);
+            publisher.publish_missed_cycles(*missed_cycles);
+        }
+    }
+    for event in events {
+        publisher.publish_fault_event(&event);
+    }
+}
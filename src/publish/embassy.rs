@@ -0,0 +1,124 @@
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    pubsub::{PubSubChannel, Subscriber},
+    watch::{Receiver, Watch},
+};
+
+use crate::{faults::FaultEvent, measurement::Measurement};
+
+use super::MeasurementPublisher;
+
+/// `embassy-sync` channels carrying the results of the measurement loop, usually placed in a `static`.
+///
+/// The latest measurement and the missed cycle count are held in [`Watch`]es with up to `RECEIVERS` receivers each,
+/// so receivers which fall behind see the most recent value.
+/// Fault events are queued in a [`PubSubChannel`] holding `CAP` events for up to `SUBS` subscribers,
+/// where a subscriber which falls more than `CAP` events behind is told how many it missed.
+///
+/// ```no_run
+/// # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+/// # use embedded_hal::digital::OutputPin;
+/// use embassy_sync::{blocking_mutex::raw::CriticalSectionRawMutex, pubsub::WaitResult};
+/// use l9961::{L9961, publish::MeasurementChannels};
+///
+/// static CHANNELS: MeasurementChannels<CriticalSectionRawMutex, 2, 8, 2> = MeasurementChannels::new();
+///
+/// async fn measurement_task<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+///     mut l9961: L9961<I2C, READY, FAULTN, WAKEUP>,
+///     mut delay: impl DelayNs,
+///     now: impl FnMut() -> u64,
+/// ) -> Result<(), I2C::Error> {
+///     l9961.run_measurement_loop(&mut delay, now, &CHANNELS).await?;
+///     Ok(())
+/// }
+///
+/// async fn fault_task() {
+///     let mut events = CHANNELS.fault_event_subscriber().unwrap();
+///     loop {
+///         if let WaitResult::Message(event) = events.next_message().await {
+///             // Notify the user, store the event, ...
+///         }
+///     }
+/// }
+/// ```
+pub struct MeasurementChannels<M, const RECEIVERS: usize, const CAP: usize, const SUBS: usize>
+where
+    M: RawMutex,
+{
+    measurements: Watch<M, Measurement, RECEIVERS>,
+    missed_cycles: Watch<M, u32, RECEIVERS>,
+    fault_events: PubSubChannel<M, FaultEvent, CAP, SUBS, 0>,
+}
+
+impl<M, const RECEIVERS: usize, const CAP: usize, const SUBS: usize>
+    MeasurementChannels<M, RECEIVERS, CAP, SUBS>
+where
+    M: RawMutex,
+{
+    /// Create the channels, with no measurement published yet
+    pub const fn new() -> Self {
+        Self {
+            measurements: Watch::new(),
+            missed_cycles: Watch::new_with(0),
+            fault_events: PubSubChannel::new(),
+        }
+    }
+
+    /// A receiver of the latest measurement, or `None` if all `RECEIVERS` receivers have been handed out
+    pub fn measurement_receiver(&self) -> Option<Receiver<'_, M, Measurement, RECEIVERS>> {
+        self.measurements.receiver()
+    }
+
+    /// A receiver of the total number of missed measurement cycles,
+    /// or `None` if all `RECEIVERS` receivers have been handed out
+    pub fn missed_cycles_receiver(&self) -> Option<Receiver<'_, M, u32, RECEIVERS>> {
+        self.missed_cycles.receiver()
+    }
+
+    /// A subscriber to the fault events published after it is created,
+    /// or `None` if all `SUBS` subscribers have been handed out
+    pub fn fault_event_subscriber(&self) -> Option<Subscriber<'_, M, FaultEvent, CAP, SUBS, 0>> {
+        self.fault_events.subscriber().ok()
+    }
+
+    /// The latest measurement, if any has been published
+    pub fn latest_measurement(&self) -> Option<Measurement> {
+        self.measurements.try_get()
+    }
+
+    /// The total number of missed measurement cycles
+    pub fn missed_cycles(&self) -> u32 {
+        self.missed_cycles.try_get().unwrap_or(0)
+    }
+}
+
+impl<M, const RECEIVERS: usize, const CAP: usize, const SUBS: usize> Default
+    for MeasurementChannels<M, RECEIVERS, CAP, SUBS>
+where
+    M: RawMutex,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<M, const RECEIVERS: usize, const CAP: usize, const SUBS: usize> MeasurementPublisher
+    for &MeasurementChannels<M, RECEIVERS, CAP, SUBS>
+where
+    M: RawMutex,
+{
+    fn publish_measurement(&mut self, measurement: &Measurement) {
+        self.measurements.sender().send(*measurement);
+    }
+
+    fn publish_fault_event(&mut self, event: &FaultEvent) {
+        // Subscribers which have fallen behind lose the oldest event rather than holding up the loop
+        self.fault_events
+            .immediate_publisher()
+            .publish_immediate(*event);
+    }
+
+    fn publish_missed_cycles(&mut self, missed_cycles: u32) {
+        self.missed_cycles.sender().send(missed_cycles);
+    }
+}
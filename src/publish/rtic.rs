@@ -0,0 +1,127 @@
+use rtic_core::Mutex;
+
+use crate::{
+    faults::{FAULT_LOG_CAPACITY, FaultEvent, FaultLog},
+    measurement::Measurement,
+};
+
+use super::MeasurementPublisher;
+
+/// Results of the measurement loop, held in an RTIC 2 shared resource and updated by an [`RticPublisher`].
+/// Fault events are mirrored into a [`FaultLog`] holding the `N` most recent events,
+/// which other tasks read or drain while holding the lock.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PublishedMeasurements<const N: usize = FAULT_LOG_CAPACITY> {
+    latest: Option<Measurement>,
+    measurements: u32,
+    missed_cycles: u32,
+    fault_events: FaultLog<N>,
+}
+
+impl<const N: usize> PublishedMeasurements<N> {
+    /// Create the resource, with no measurement published yet
+    pub const fn new() -> Self {
+        Self {
+            latest: None,
+            measurements: 0,
+            missed_cycles: 0,
+            fault_events: FaultLog::new(),
+        }
+    }
+
+    /// The latest measurement, if any has been published
+    pub const fn latest_measurement(&self) -> Option<&Measurement> {
+        self.latest.as_ref()
+    }
+
+    /// The number of measurements published, wrapping on overflow.
+    /// Tasks can compare it against the value they last saw to tell whether a new measurement arrived.
+    pub const fn measurements(&self) -> u32 {
+        self.measurements
+    }
+
+    /// The total number of missed measurement cycles
+    pub const fn missed_cycles(&self) -> u32 {
+        self.missed_cycles
+    }
+
+    /// The published fault events
+    pub const fn fault_events(&self) -> &FaultLog<N> {
+        &self.fault_events
+    }
+
+    /// Mutable access to the published fault events, e.g. to drain them
+    pub const fn fault_events_mut(&mut self) -> &mut FaultLog<N> {
+        &mut self.fault_events
+    }
+}
+
+impl<const N: usize> Default for PublishedMeasurements<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Publisher writing the results of the measurement loop into an RTIC 2 shared resource
+/// holding [`PublishedMeasurements`]. Each result is written under a short lock of the resource.
+///
+/// ```ignore
+/// #[shared]
+/// struct Shared {
+///     published: PublishedMeasurements,
+/// }
+///
+/// #[task(shared = [published], local = [l9961, delay])]
+/// async fn measure(cx: measure::Context) {
+///     let publisher = RticPublisher::new(cx.shared.published);
+///     cx.local.l9961
+///         .run_measurement_loop(cx.local.delay, monotonic_now, publisher)
+///         .await
+///         .unwrap();
+/// }
+///
+/// #[task(shared = [published])]
+/// async fn report(mut cx: report::Context) {
+///     let events = cx.shared.published.lock(|published| published.fault_events().len());
+/// }
+/// ```
+pub struct RticPublisher<R> {
+    resource: R,
+}
+
+impl<R, const N: usize> RticPublisher<R>
+where
+    R: Mutex<T = PublishedMeasurements<N>>,
+{
+    /// Publish into the given shared resource
+    pub const fn new(resource: R) -> Self {
+        Self { resource }
+    }
+
+    /// Release the shared resource
+    pub fn release(self) -> R {
+        self.resource
+    }
+}
+
+impl<R, const N: usize> MeasurementPublisher for RticPublisher<R>
+where
+    R: Mutex<T = PublishedMeasurements<N>>,
+{
+    fn publish_measurement(&mut self, measurement: &Measurement) {
+        self.resource.lock(|published| {
+            published.latest = Some(*measurement);
+            published.measurements = published.measurements.wrapping_add(1);
+        });
+    }
+
+    fn publish_fault_event(&mut self, event: &FaultEvent) {
+        self.resource
+            .lock(|published| published.fault_events.replay(*event));
+    }
+
+    fn publish_missed_cycles(&mut self, missed_cycles: u32) {
+        self.resource
+            .lock(|published| published.missed_cycles = missed_cycles);
+    }
+}
@@ -1,6 +1,9 @@
 //! # Registers
 //!
 //! Definitions and read/write functions for the registers of the L9961 chip.
+//! Every typed register implements [`Register`], and can be accessed through the generic
+//! [`L9961::read`], [`L9961::write`] and [`L9961::modify`] functions,
+//! which only permit writes to registers whose access type allows them.
 
 mod cc_acc_lsb_cntr;
 mod cfg1_filters_cycles;
@@ -17,7 +20,10 @@ mod die_temp;
 mod ntc_gpio;
 mod ovc_thresholds;
 mod persistent_ovc_threshold;
+mod register;
+pub mod regmap;
 mod sc_threshold;
+mod snapshot;
 mod to_faultn_msk;
 mod to_fuse_rst_msk;
 mod to_prdrv_bal_mask;
@@ -38,8 +44,11 @@ mod vntc_ut_th;
 
 pub use self::{
     cc_acc_lsb_cntr::CCAccLsbCntr,
-    cfg1_filters_cycles::{Cfg1FiltersCycles, TCellFilter, TCurFilter, TMeasCycle, TSCFilter},
-    cfg2_enables::{Cfg2Enables, FetConfig},
+    cfg1_filters_cycles::{
+        Cfg1FiltersCycles, Cfg1FiltersCyclesBuilder, Filter, FilterLatencyError, TCellFilter,
+        TCurFilter, TMeasCycle, TSCFilter,
+    },
+    cfg2_enables::{Cfg2Enables, Cfg2EnablesBuilder, FetConfig},
     cfg3_act::Cfg3Act,
     chip_id::ChipID,
     csa_gain_factor::CsaGainFactor,
@@ -52,7 +61,17 @@ pub use self::{
     ntc_gpio::NtcGpio,
     ovc_thresholds::OvCThresholds,
     persistent_ovc_threshold::PersistentOvCThreshold,
+    register::{
+        Access, AccessType, DecodedRegister, Field, FieldInfo, ReadLatchedWrite, ReadOnly,
+        ReadWrite, Readable, Register, RegisterInfo, Writable, WriteOnly,
+    },
+    regmap::{
+        CCAccMsb, CCInstMeas, DeviceNameLsb, DeviceNameMsb, FirstUsageDate, ManufacturerNameLsb,
+        ManufacturerNameMsb, ManufacturingDate, Nvm1, Nvm2, REGISTERS, SerialNumberLsb,
+        SerialNumberMsb, VCell2, VCell3, VCell4, VCell5,
+    },
     sc_threshold::SCThreshold,
+    snapshot::{FieldChange, REGISTER_COUNT, RegisterSnapshot},
     to_faultn_msk::ToFaultnMsk,
     to_fuse_rst_msk::ToFuseRstMask,
     to_prdrv_bal_mask::ToPrdrvBalMask,
@@ -74,12 +93,13 @@ pub use self::{
 
 use crate::L9961;
 
-use defmt::Format;
 use embedded_hal::digital::OutputPin;
 use embedded_hal_async::{digital::Wait, i2c::I2c};
 
 /// The registers of the L9961 chip represented as their addresses
-#[derive(Clone, Copy, Debug, Format)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u8)]
 pub enum Registers {
     /// The chip ID register
@@ -180,11 +200,18 @@ pub enum Registers {
     DiagCurr = 0x2F,
 }
 
-impl<I2C, I, O> L9961<I2C, I, O>
+impl core::fmt::Display for Registers {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(RegisterInfo::of(*self).name)
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
 where
     I2C: I2c,
-    I: Wait,
-    O: OutputPin,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
 {
     /// Read one or more registers from the l9961
     pub async fn read_registers(
@@ -240,27 +267,61 @@ where
         Ok(())
     }
 
+    /// Read a typed register
+    pub async fn read<R>(&mut self) -> Result<R, I2C::Error>
+    where
+        R: Register,
+        R::Access: Readable,
+    {
+        Ok(R::from_raw(self.read_register(R::ADDRESS).await?))
+    }
+
+    /// Write a typed register.
+    /// Only the writable fields of the register are written, all other bits are written as 0.
+    /// Writing the device address register also updates the address used by the driver upon success.
+    pub async fn write<R>(&mut self, value: R) -> Result<(), I2C::Error>
+    where
+        R: Register,
+        R::Access: Writable,
+    {
+        let raw = value.raw() & R::WRITABLE_MASK;
+        self.write_register(R::ADDRESS, raw).await?;
+        if R::ADDRESS == Registers::DevAddr {
+            self.config.address = DevAddr::from(raw).get_device_address();
+        }
+        Ok(())
+    }
+
+    /// Read a RW register, update it with `f`, and write it back.
+    /// Returns the value which was written.
+    pub async fn modify<R>(&mut self, f: impl FnOnce(&mut R)) -> Result<R, I2C::Error>
+    where
+        R: Register<Access = ReadWrite>,
+    {
+        let mut value = self.read::<R>().await?;
+        f(&mut value);
+        self.write(value).await?;
+        Ok(value)
+    }
+
     /// Read the chip ID.
     pub async fn read_chip_id(&mut self) -> Result<ChipID, I2C::Error> {
-        Ok(self.read_register(Registers::ChipID).await?.into())
+        self.read().await
     }
 
     /// Read the Cfg3Act register
     pub async fn read_cfg3_act(&mut self) -> Result<Cfg3Act, I2C::Error> {
-        Ok(self.read_register(Registers::Cfg3Act).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the Cfg3Act register
     pub async fn write_cfg3_act(&mut self, new_config: Cfg3Act) -> Result<(), I2C::Error> {
-        self.write_register(Registers::Cfg3Act, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the Cfg1FiltersCycles register
     pub async fn read_cfg1_filters_cycles(&mut self) -> Result<Cfg1FiltersCycles, I2C::Error> {
-        Ok(self
-            .read_register(Registers::Cfg1FiltersCycles)
-            .await?
-            .into())
+        self.read().await
     }
 
     /// Write a new value to the Cfg1FiltersCycles register
@@ -268,37 +329,33 @@ where
         &mut self,
         new_config: Cfg1FiltersCycles,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::Cfg1FiltersCycles, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the Device Address Register
     pub async fn read_device_address(&mut self) -> Result<DevAddr, I2C::Error> {
-        Ok(self.read_register(Registers::DevAddr).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the Device Address Register
     /// Note that this will also update the internal I2C address of the `L9961` driver upon success
     pub async fn write_device_address(&mut self, new_config: DevAddr) -> Result<(), I2C::Error> {
-        self.write_register(Registers::DevAddr, *new_config).await?;
-        self.config.address = new_config.get_device_address();
-        Ok(())
+        self.write(new_config).await
     }
 
     /// Read the Cfg2Enables register
     pub async fn read_cfg2_enables(&mut self) -> Result<Cfg2Enables, I2C::Error> {
-        Ok(self.read_register(Registers::Cfg2Enables).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the Cfg2Enables register
     pub async fn write_cfg2_enables(&mut self, new_config: Cfg2Enables) -> Result<(), I2C::Error> {
-        self.write_register(Registers::Cfg2Enables, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the CSA (Current Sense ADC) gain factor register
     pub async fn read_csa_gain_factor(&mut self) -> Result<CsaGainFactor, I2C::Error> {
-        Ok(self.read_register(Registers::CsaGainFactor).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the CSA (Current Sense ADC) gain factor register
@@ -306,38 +363,34 @@ where
         &mut self,
         new_config: CsaGainFactor,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::CsaGainFactor, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the VCell Ov Threshold register
     pub async fn read_vcell_ov_th(&mut self) -> Result<VCellOvTh, I2C::Error> {
-        Ok(self.read_register(Registers::VCellOvTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VCellOv Threshold register
     pub async fn write_vcell_ov_th(&mut self, new_config: VCellOvTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VCellOvTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VCellUv Threshold register
     pub async fn read_vcell_uv_th(&mut self) -> Result<VCellUvTh, I2C::Error> {
-        Ok(self.read_register(Registers::VCellUvTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VCellUv Threshold register
     pub async fn write_vcell_uv_th(&mut self, new_config: VCellUvTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VCellUvTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VCellSevereDeltaThreshold register
     pub async fn read_vcell_severe_delta_thrs(
         &mut self,
     ) -> Result<VCellSevereDeltaThrs, I2C::Error> {
-        Ok(self
-            .read_register(Registers::VCellSevereDeltaThrs)
-            .await?
-            .into())
+        self.read().await
     }
 
     /// Write a new value to the VCellSevereDeltaThrs register
@@ -345,16 +398,12 @@ where
         &mut self,
         new_config: VCellSevereDeltaThrs,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VCellSevereDeltaThrs, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the VCellBalUvDeltaTh register
     pub async fn read_vcell_bal_uv_delta_th(&mut self) -> Result<VCellBalUvDeltaTh, I2C::Error> {
-        Ok(self
-            .read_register(Registers::VCellBalUvDeltaTh)
-            .await?
-            .into())
+        self.read().await
     }
 
     /// Write a new value to the VCellBalUvDeltaTh register
@@ -362,33 +411,32 @@ where
         &mut self,
         new_config: VCellBalUvDeltaTh,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VCellBalUvDeltaTh, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the VBOvTh register
     pub async fn read_vb_ov_th(&mut self) -> Result<VBOvTh, I2C::Error> {
-        Ok(self.read_register(Registers::VBOvTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VBOvTh register
     pub async fn write_vb_ov_th(&mut self, new_config: VBOvTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VBOvTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VBUvTh register
     pub async fn read_vb_uv_th(&mut self) -> Result<VBUvTh, I2C::Error> {
-        Ok(self.read_register(Registers::VBUvTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VBUvTh register
     pub async fn write_vb_uv_th(&mut self, new_config: VBUvTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VBUvTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VBSumMaxDiffTh register
     pub async fn read_vb_sum_max_diff_th(&mut self) -> Result<VBSumMaxDiffTh, I2C::Error> {
-        Ok(self.read_register(Registers::VBSumMaxDiffTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VBSumMaxDiffTh register
@@ -396,33 +444,32 @@ where
         &mut self,
         new_config: VBSumMaxDiffTh,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VBSumMaxDiffTh, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the VNTCOTTh register
     pub async fn read_vntc_ot_th(&mut self) -> Result<VNTCOTTh, I2C::Error> {
-        Ok(self.read_register(Registers::VNTCOTTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VNTCOTTh register
     pub async fn write_vntc_ot_th(&mut self, new_config: VNTCOTTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VNTCOTTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VNTCUTTh register
     pub async fn read_vntc_ut_th(&mut self) -> Result<VNTCUTTh, I2C::Error> {
-        Ok(self.read_register(Registers::VNTCUTTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VNTCUTTh register
     pub async fn write_vntc_ut_th(&mut self, new_config: VNTCUTTh) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VNTCUTTh, *new_config).await
+        self.write(new_config).await
     }
 
     /// Read the VNTCSevereOtTh register
     pub async fn read_vntc_severe_ot_th(&mut self) -> Result<VNTCSevereOTTh, I2C::Error> {
-        Ok(self.read_register(Registers::VNTCSevereOTTh).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the VNTCSevereOtTh register
@@ -430,13 +477,12 @@ where
         &mut self,
         new_config: VNTCSevereOTTh,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::VNTCSevereOTTh, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the OVC_THRESHOLDS register
     pub async fn read_ovc_thresholds(&mut self) -> Result<OvCThresholds, I2C::Error> {
-        Ok(self.read_register(Registers::OvCThresholds).await?.into())
+        self.read().await
     }
 
     /// Write a new value to the OVC_THRESHOLDS register
@@ -444,18 +490,14 @@ where
         &mut self,
         new_config: OvCThresholds,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::OvCThresholds, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the PERSISTENT_OVC_THRESHOLDS register
     pub async fn read_persistent_ovc_thresholds(
         &mut self,
     ) -> Result<PersistentOvCThreshold, I2C::Error> {
-        Ok(self
-            .read_register(Registers::PersistentOvCThresholds)
-            .await?
-            .into())
+        self.read().await
     }
 
     /// Write a new value to the PERSISTENT_OVC_THRESHOLDS register
@@ -463,26 +505,22 @@ where
         &mut self,
         new_config: PersistentOvCThreshold,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::PersistentOvCThresholds, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the SC_THRESHOLD register
     pub async fn read_sc_threshold(&mut self) -> Result<SCThreshold, I2C::Error> {
-        Ok(self.read_register(Registers::SCThreshold).await?.into())
+        self.read().await
     }
 
     /// Write the SC_THRESHOLD register
     pub async fn write_sc_threshold(&mut self, new_config: SCThreshold) -> Result<(), I2C::Error> {
-        self.write_register(Registers::SCThreshold, *new_config)
-            .await
+        self.write(new_config).await
     }
 
     /// Read the TO_PRDRV_BAL_MASK register
     pub async fn read_to_prdrv_bal_mask(&mut self) -> Result<ToPrdrvBalMask, I2C::Error> {
-        Ok(ToPrdrvBalMask::from_bits_truncate(
-            self.read_register(Registers::ToPrdrvBalMask).await?,
-        ))
+        self.read().await
     }
 
     /// Write the TO_PRDRV_BAL_MASK register
@@ -490,15 +528,12 @@ where
         &mut self,
         new_config: ToPrdrvBalMask,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::ToPrdrvBalMask, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the TO_FUSE_RST_MSK register
     pub async fn read_to_fuse_rst_msk(&mut self) -> Result<ToFuseRstMask, I2C::Error> {
-        Ok(ToFuseRstMask::from_bits_truncate(
-            self.read_register(Registers::ToFuseRstMask).await?,
-        ))
+        self.read().await
     }
 
     /// Write the TO_FUSE_RST_MSK register
@@ -506,34 +541,27 @@ where
         &mut self,
         new_config: ToFuseRstMask,
     ) -> Result<(), I2C::Error> {
-        self.write_register(Registers::ToFuseRstMask, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the TO_FAULTN_MSK register
     pub async fn read_to_faultn_msk(&mut self) -> Result<ToFaultnMsk, I2C::Error> {
-        Ok(ToFaultnMsk::from_bits_truncate(
-            self.read_register(Registers::ToFaultnMsk).await?,
-        ))
+        self.read().await
     }
 
     /// Write the TO_FAULTN_MSK register
     pub async fn write_to_faultn_msk(&mut self, new_config: ToFaultnMsk) -> Result<(), I2C::Error> {
-        self.write_register(Registers::ToFaultnMsk, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the CURR_MSK register
     pub async fn read_curr_msk(&mut self) -> Result<CurrMsk, I2C::Error> {
-        Ok(CurrMsk::from_bits_truncate(
-            self.read_register(Registers::CurrMsk).await?,
-        ))
+        self.read().await
     }
 
     /// Write the CURR_MSK register
     pub async fn write_curr_msk(&mut self, new_config: CurrMsk) -> Result<(), I2C::Error> {
-        self.write_register(Registers::CurrMsk, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the Manufacturer Name msb
@@ -621,7 +649,7 @@ where
 
     /// Read the faults from the VCell 1 register
     pub async fn read_vcell_1_faults(&mut self) -> Result<VCell1Faults, I2C::Error> {
-        Ok(self.read_register(Registers::VCell1).await?.into())
+        self.read().await
     }
 
     /// Read one of the 5 VCell registers (1 indexed per device)
@@ -639,48 +667,42 @@ where
 
     /// Read the VCellSum measurement register
     pub async fn read_vcellsum(&mut self) -> Result<VCellSum, I2C::Error> {
-        Ok(self.read_register(Registers::VCellSum).await?.into())
+        self.read().await
     }
 
     /// Read the VB measurement register
     pub async fn read_vb(&mut self) -> Result<VB, I2C::Error> {
-        Ok(self.read_register(Registers::VB).await?.into())
+        self.read().await
     }
 
     /// Read the NTC_GPIO register
     pub async fn read_ntc_gpio(&mut self) -> Result<NtcGpio, I2C::Error> {
-        Ok(self.read_register(Registers::NtcGpio).await?.into())
+        self.read().await
     }
 
     /// Read the Die Temperature register
     pub async fn read_die_temp(&mut self) -> Result<DieTemp, I2C::Error> {
-        Ok(self.read_register(Registers::DieTemp).await?.into())
+        self.read().await
     }
 
     /// Read the DIAG_OV_OT_UT register
     pub async fn read_diag_ov_ot_ut(&mut self) -> Result<DiagOvOtUt, I2C::Error> {
-        Ok(DiagOvOtUt::from_bits_truncate(
-            self.read_register(Registers::DiagOvOtUt).await?,
-        ))
+        self.read().await
     }
 
     /// Write a new value to the DIAG_OV_OT_UT register
     pub async fn write_diag_ov_ot_ut(&mut self, new_config: DiagOvOtUt) -> Result<(), I2C::Error> {
-        self.write_register(Registers::DiagOvOtUt, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the DIAG_UV register
     pub async fn read_diag_uv(&mut self) -> Result<DiagUv, I2C::Error> {
-        Ok(DiagUv::from_bits_truncate(
-            self.read_register(Registers::DiagUv).await?,
-        ))
+        self.read().await
     }
 
     /// Write to the DIAG_UV register
     pub async fn write_diag_uv(&mut self, new_config: DiagUv) -> Result<(), I2C::Error> {
-        self.write_register(Registers::DiagUv, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 
     /// Read the CC_INST_MEAS register
@@ -700,19 +722,16 @@ where
 
     /// Read the CC_ACC_LSB_CNTR register
     pub async fn read_cc_acc_lsb_cntr(&mut self) -> Result<CCAccLsbCntr, I2C::Error> {
-        Ok(self.read_register(Registers::CCAccLsbCntr).await?.into())
+        self.read().await
     }
 
     /// Read the DIAG_CURR register
     pub async fn read_diag_curr(&mut self) -> Result<DiagCurr, I2C::Error> {
-        Ok(DiagCurr::from_bits_truncate(
-            self.read_register(Registers::DiagCurr).await?,
-        ))
+        self.read().await
     }
 
     /// Write to the DIAG_CURR register
     pub async fn write_diag_curr(&mut self, new_config: DiagCurr) -> Result<(), I2C::Error> {
-        self.write_register(Registers::DiagCurr, new_config.bits())
-            .await
+        self.write(new_config).await
     }
 }
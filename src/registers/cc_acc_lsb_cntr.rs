@@ -1,18 +1,20 @@
 use core::ops::Deref;
 
+use super::regmap::cc_acc_lsb_cntr::{CC_ACC_LSB, CC_SAMPLE_CNT};
+
 /// Coulomb Counter accumulator LSB and counter register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct CCAccLsbCntr(u16);
 
 impl CCAccLsbCntr {
     /// Get the number of samples stored in the accumulator
     pub const fn get_cc_sample_cnt(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        CC_SAMPLE_CNT.get(self.0) as u8
     }
 
     /// Get the lsb of the cc accumulator
     pub const fn get_cc_acc_lsb(&self) -> u8 {
-        ((self.0 & 0xFF00) >> 8) as u8
+        CC_ACC_LSB.get(self.0) as u8
     }
 }
 
@@ -29,6 +31,17 @@ impl From<u16> for CCAccLsbCntr {
     }
 }
 
+impl core::fmt::Display for CCAccLsbCntr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "CC_ACC_LSB_CNTR: {{\n  CC_SAMPLE_CNT: {},\n  CC_ACC_LSB: {}\n}}",
+            self.get_cc_sample_cnt(),
+            self.get_cc_acc_lsb()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for CCAccLsbCntr {
     fn format(&self, f: defmt::Formatter) {
@@ -1,17 +1,25 @@
 use core::ops::Deref;
 
-use defmt::Format;
-
-const TCELL_FILTER_MASK: u16 = 0b11;
-const TCELL_FILTER_SHIFT: u16 = 0;
-const T_SC_FILTER_MASK: u16 = 0b111;
-const T_SC_FILTER_SHIFT: u16 = 2;
-const T_CUR_FILTER_MASK: u16 = 0b11;
-const T_CUR_FILTER_SHIFT: u16 = 5;
-const T_MEAS_CYCLE_MASK: u16 = 0b11111;
-const T_MEAS_CYCLE_SHIFT: u16 = 7;
+use super::regmap::cfg1_filters_cycles::{T_CUR_FILTER, T_MEAS_CYCLE, T_SC_FILTER, TCELL_FILTER};
+
+/// Find the last of a list of filters, sorted from shortest to longest, which fits within a latency
+macro_rules! longest_within {
+    ($filters:expr, $latency_us:expr) => {{
+        let filters = $filters;
+        let mut selected = None;
+        let mut i = 0;
+        while i < filters.len() {
+            if filters[i].duration_us() <= $latency_us {
+                selected = Some(filters[i]);
+            }
+            i += 1;
+        }
+        selected
+    }};
+}
 
 /// Programmable cell voltage sample acquisition time (2 bit)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TCellFilter {
     /// 0.8ms
     T0_8Ms = 0b00,
@@ -23,14 +31,55 @@ pub enum TCellFilter {
     T16_67Ms = 0b11,
 }
 
-impl From<u16> for TCellFilter {
-    fn from(value: u16) -> Self {
-        match value {
+impl TCellFilter {
+    /// Every filter, from shortest to longest
+    pub const ALL: [Self; 4] = [
+        TCellFilter::T0_8Ms,
+        TCellFilter::T1_31Ms,
+        TCellFilter::T4_38Ms,
+        TCellFilter::T16_67Ms,
+    ];
+
+    /// Decode the field value, ignoring bits outside the 2 bit field
+    pub const fn from_bits(value: u16) -> Self {
+        match value & 0b11 {
             0b00 => TCellFilter::T0_8Ms,
             0b01 => TCellFilter::T1_31Ms,
             0b10 => TCellFilter::T4_38Ms,
-            0b11 => TCellFilter::T16_67Ms,
-            _ => panic!("Invalid T_CELL_FILTER"),
+            _ => TCellFilter::T16_67Ms,
+        }
+    }
+
+    /// Acquisition time in us
+    pub const fn duration_us(self) -> u32 {
+        match self {
+            TCellFilter::T0_8Ms => 800,
+            TCellFilter::T1_31Ms => 1310,
+            TCellFilter::T4_38Ms => 4380,
+            TCellFilter::T16_67Ms => 16670,
+        }
+    }
+
+    /// The longest filter whose acquisition time does not exceed the given latency
+    pub const fn longest_within_us(latency_us: u32) -> Option<Self> {
+        longest_within!(Self::ALL, latency_us)
+    }
+}
+
+impl From<u16> for TCellFilter {
+    fn from(value: u16) -> Self {
+        Self::from_bits(value)
+    }
+}
+
+impl core::fmt::Display for TCellFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "T_CELL_FILTER: ")?;
+        match self {
+            TCellFilter::T0_8Ms => write!(f, "0.8 ms"),
+            TCellFilter::T1_31Ms => write!(f, "1.31 ms"),
+            TCellFilter::T4_38Ms => write!(f, "4.38 ms"),
+            TCellFilter::T16_67Ms => write!(f, "16.67 ms"),
         }
     }
 }
@@ -49,6 +98,7 @@ impl defmt::Format for TCellFilter {
 }
 
 /// Programmable short-circuit in discharge filter time (3 bit)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TSCFilter {
     /// 32 us
     T32us = 0b000,
@@ -62,15 +112,61 @@ pub enum TSCFilter {
     T512us = 0b100,
 }
 
-impl From<u16> for TSCFilter {
-    fn from(value: u16) -> Self {
-        match value {
+impl TSCFilter {
+    /// Every filter, from shortest to longest
+    pub const ALL: [Self; 5] = [
+        TSCFilter::T32us,
+        TSCFilter::T64us,
+        TSCFilter::T128us,
+        TSCFilter::T256us,
+        TSCFilter::T512us,
+    ];
+
+    /// Decode the field value, ignoring bits outside the 3 bit field.
+    /// The codes above 0b100 have no documented filter time, and decode as the longest filter,
+    /// so that timing derived from an unexpected register value is never optimistic.
+    pub const fn from_bits(value: u16) -> Self {
+        match value & 0b111 {
             0b000 => TSCFilter::T32us,
             0b001 => TSCFilter::T64us,
             0b010 => TSCFilter::T128us,
             0b011 => TSCFilter::T256us,
-            0b100 => TSCFilter::T512us,
-            _ => panic!("Invalid T_SC_FILTER"),
+            _ => TSCFilter::T512us,
+        }
+    }
+
+    /// Filter time in us
+    pub const fn duration_us(self) -> u32 {
+        match self {
+            TSCFilter::T32us => 32,
+            TSCFilter::T64us => 64,
+            TSCFilter::T128us => 128,
+            TSCFilter::T256us => 256,
+            TSCFilter::T512us => 512,
+        }
+    }
+
+    /// The longest filter whose filter time does not exceed the given latency
+    pub const fn longest_within_us(latency_us: u32) -> Option<Self> {
+        longest_within!(Self::ALL, latency_us)
+    }
+}
+
+impl From<u16> for TSCFilter {
+    fn from(value: u16) -> Self {
+        Self::from_bits(value)
+    }
+}
+
+impl core::fmt::Display for TSCFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "T_SC_FILTER: ")?;
+        match self {
+            TSCFilter::T32us => write!(f, "32 us"),
+            TSCFilter::T64us => write!(f, "64 us"),
+            TSCFilter::T128us => write!(f, "128 us"),
+            TSCFilter::T256us => write!(f, "256 us"),
+            TSCFilter::T512us => write!(f, "512 us"),
         }
     }
 }
@@ -90,6 +186,7 @@ impl defmt::Format for TSCFilter {
 }
 
 /// Programmable current sense sample acquisition time (2 bit)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub enum TCurFilter {
     /// 4.22 ms
     T4_22Ms = 0b00,
@@ -101,14 +198,55 @@ pub enum TCurFilter {
     T33_8Ms = 0b11,
 }
 
-impl From<u16> for TCurFilter {
-    fn from(value: u16) -> Self {
-        match value {
+impl TCurFilter {
+    /// Every filter, from shortest to longest
+    pub const ALL: [Self; 4] = [
+        TCurFilter::T4_22Ms,
+        TCurFilter::T8_44Ms,
+        TCurFilter::T16_9Ms,
+        TCurFilter::T33_8Ms,
+    ];
+
+    /// Decode the field value, ignoring bits outside the 2 bit field
+    pub const fn from_bits(value: u16) -> Self {
+        match value & 0b11 {
             0b00 => TCurFilter::T4_22Ms,
             0b01 => TCurFilter::T8_44Ms,
             0b10 => TCurFilter::T16_9Ms,
-            0b11 => TCurFilter::T33_8Ms,
-            _ => panic!("Invalid T_CUR_FILTER"),
+            _ => TCurFilter::T33_8Ms,
+        }
+    }
+
+    /// Acquisition time in us
+    pub const fn duration_us(self) -> u32 {
+        match self {
+            TCurFilter::T4_22Ms => 4220,
+            TCurFilter::T8_44Ms => 8440,
+            TCurFilter::T16_9Ms => 16900,
+            TCurFilter::T33_8Ms => 33800,
+        }
+    }
+
+    /// The longest filter whose acquisition time does not exceed the given latency
+    pub const fn longest_within_us(latency_us: u32) -> Option<Self> {
+        longest_within!(Self::ALL, latency_us)
+    }
+}
+
+impl From<u16> for TCurFilter {
+    fn from(value: u16) -> Self {
+        Self::from_bits(value)
+    }
+}
+
+impl core::fmt::Display for TCurFilter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "T_CUR_FILTER: ")?;
+        match self {
+            TCurFilter::T4_22Ms => write!(f, "4.22 ms"),
+            TCurFilter::T8_44Ms => write!(f, "8.44 ms"),
+            TCurFilter::T16_9Ms => write!(f, "16.9 ms"),
+            TCurFilter::T33_8Ms => write!(f, "33.8 ms"),
         }
     }
 }
@@ -128,6 +266,7 @@ impl defmt::Format for TCurFilter {
 
 /// Programmable voltage conversion routine execution period (5 bit)
 /// The period is equal to T_MEAS_CYCLE * 10 ms
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
 pub struct TMeasCycle(u8);
 
 impl TMeasCycle {
@@ -142,7 +281,7 @@ impl TMeasCycle {
     }
 
     /// Whether the cycle represents the disabled state
-    pub fn is_disabled(&self) -> bool {
+    pub const fn is_disabled(&self) -> bool {
         matches!(self.0, 0)
     }
 
@@ -166,13 +305,22 @@ impl From<TMeasCycle> for u16 {
 
 impl From<u8> for TMeasCycle {
     fn from(value: u8) -> Self {
-        debug_assert!(value & 0xC0 == 0, "Invalid T_MEAS_CYCLE");
+        debug_assert!(value & 0xE0 == 0, "Invalid T_MEAS_CYCLE");
         Self(value)
     }
 }
 
+impl core::fmt::Display for TMeasCycle {
+    fn fmt(&self, fmt: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.0 {
+            0 => write!(fmt, "T_MEAS_CYCLE: Disabled"),
+            _ => write!(fmt, "T_MEAS_CYCLE: {}ms", self.period_ms()),
+        }
+    }
+}
+
 #[cfg(feature = "defmt")]
-impl Format for TMeasCycle {
+impl defmt::Format for TMeasCycle {
     fn format(&self, fmt: defmt::Formatter) {
         match self.0 {
             0 => defmt::write!(fmt, "T_MEAS_CYCLE: Disabled"),
@@ -183,7 +331,7 @@ impl Format for TMeasCycle {
 
 /// Registers CFG1_FILTERS_CYCLES
 /// Used to configure the filter and cycle times for the voltage and current measurements
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Cfg1FiltersCycles(u16);
 
 impl Cfg1FiltersCycles {
@@ -195,10 +343,10 @@ impl Cfg1FiltersCycles {
         t_meas_cycle: TMeasCycle,
     ) -> Self {
         Self(
-            tcell_filter as u16 & TCELL_FILTER_MASK << TCELL_FILTER_SHIFT
-                | (t_sc_filter as u16 & T_SC_FILTER_MASK) << T_SC_FILTER_SHIFT
-                | (t_cur_filter as u16 & T_CUR_FILTER_MASK) << T_CUR_FILTER_SHIFT
-                | (t_meas_cycle.0 as u16 & T_MEAS_CYCLE_MASK) << T_MEAS_CYCLE_SHIFT,
+            TCELL_FILTER.set(0, tcell_filter as u16)
+                | T_SC_FILTER.set(0, t_sc_filter as u16)
+                | T_CUR_FILTER.set(0, t_cur_filter as u16)
+                | T_MEAS_CYCLE.set(0, t_meas_cycle.0 as u16),
         )
     }
 
@@ -212,56 +360,219 @@ impl Cfg1FiltersCycles {
         )
     }
 
+    /// Start building a register value from the maximum latencies of each filter
+    pub const fn builder() -> Cfg1FiltersCyclesBuilder {
+        Cfg1FiltersCyclesBuilder::new()
+    }
+
     /// Create a new Cfg1FiltersCycles register that deactivates measurements
     pub const fn deactivate() -> Self {
         Self(0)
     }
 
     /// Get the current cell voltage conversion time
-    pub fn get_t_cell_filter(&self) -> TCellFilter {
-        TCellFilter::from((self.0 >> TCELL_FILTER_SHIFT) & TCELL_FILTER_MASK)
+    pub const fn get_t_cell_filter(&self) -> TCellFilter {
+        TCellFilter::from_bits(TCELL_FILTER.get(self.0))
     }
 
     /// Set a new cell voltage conversion time
     pub const fn set_t_cell_filter(&mut self, filter: TCellFilter) {
-        self.0 = self.0 & !(TCELL_FILTER_MASK << TCELL_FILTER_SHIFT)
-            | (filter as u16) << TCELL_FILTER_SHIFT;
+        self.0 = TCELL_FILTER.set(self.0, filter as u16);
     }
 
     /// Get the current short circuit measurement time
-    pub fn get_t_sc_filter(&self) -> TSCFilter {
-        TSCFilter::from((self.0 >> T_SC_FILTER_SHIFT) & T_SC_FILTER_MASK)
+    pub const fn get_t_sc_filter(&self) -> TSCFilter {
+        TSCFilter::from_bits(T_SC_FILTER.get(self.0))
     }
 
     /// Set a new short circuit measurement time
     pub const fn set_t_sc_filter(&mut self, filter: TSCFilter) {
-        self.0 = self.0 & !(T_SC_FILTER_MASK << T_SC_FILTER_SHIFT)
-            | (filter as u16) << T_SC_FILTER_SHIFT;
+        self.0 = T_SC_FILTER.set(self.0, filter as u16);
     }
 
     /// Get the current current sense acquisition time
-    pub fn get_t_curr_filter(&self) -> TCurFilter {
-        TCurFilter::from((self.0 >> T_CUR_FILTER_SHIFT) & T_CUR_FILTER_MASK)
+    pub const fn get_t_curr_filter(&self) -> TCurFilter {
+        TCurFilter::from_bits(T_CUR_FILTER.get(self.0))
     }
 
     /// Set a new current sense acquisition time
     pub const fn set_t_curr_filter(&mut self, filter: TCurFilter) {
-        self.0 = self.0 & !(T_CUR_FILTER_MASK << T_CUR_FILTER_SHIFT)
-            | (filter as u16) << T_CUR_FILTER_SHIFT;
+        self.0 = T_CUR_FILTER.set(self.0, filter as u16);
     }
 
     /// Get the current measurement cycle period
-    pub fn get_t_meas_cycle(&self) -> TMeasCycle {
-        TMeasCycle::from(((self.0 >> T_MEAS_CYCLE_SHIFT) & T_MEAS_CYCLE_MASK) as u8)
+    pub const fn get_t_meas_cycle(&self) -> TMeasCycle {
+        TMeasCycle(T_MEAS_CYCLE.get(self.0) as u8)
     }
 
     /// Set a new measurement cycle period
-    pub fn set_t_meas_cycle(&mut self, filter: TMeasCycle) {
-        self.0 = self.0 & !(T_MEAS_CYCLE_MASK << T_MEAS_CYCLE_SHIFT)
-            | (filter.0 as u16) << T_MEAS_CYCLE_SHIFT;
+    pub const fn set_t_meas_cycle(&mut self, filter: TMeasCycle) {
+        self.0 = T_MEAS_CYCLE.set(self.0, filter.0 as u16);
+    }
+}
+
+/// Builder which selects the filters and measurement cycle of [`Cfg1FiltersCycles`] from latencies.
+/// Each filter is the longest, and so the best at rejecting noise, which fits within its latency,
+/// and the measurement cycle is the longest period which fits within its latency.
+/// Latencies which are not set select the values of [`Cfg1FiltersCycles::default`].
+///
+/// ```
+/// use l9961::registers::{Cfg1FiltersCycles, TCellFilter, TSCFilter};
+///
+/// let filters = Cfg1FiltersCycles::builder()
+///     .cell_filter_max_us(5_000)
+///     .short_circuit_filter_max_us(100)
+///     .measurement_cycle_max_ms(105)
+///     .build()
+///     .unwrap();
+/// assert_eq!(filters.get_t_cell_filter(), TCellFilter::T4_38Ms);
+/// assert_eq!(filters.get_t_sc_filter(), TSCFilter::T64us);
+/// assert_eq!(filters.get_t_meas_cycle().period_ms(), 100);
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct Cfg1FiltersCyclesBuilder {
+    cell_filter_max_us: u32,
+    short_circuit_filter_max_us: u32,
+    current_filter_max_us: u32,
+    measurement_cycle_max_ms: u16,
+}
+
+impl Cfg1FiltersCyclesBuilder {
+    /// Create a builder which selects the default filters and measurement cycle
+    pub const fn new() -> Self {
+        let defaults = Cfg1FiltersCycles::default();
+        Self {
+            cell_filter_max_us: defaults.get_t_cell_filter().duration_us(),
+            short_circuit_filter_max_us: defaults.get_t_sc_filter().duration_us(),
+            current_filter_max_us: defaults.get_t_curr_filter().duration_us(),
+            measurement_cycle_max_ms: defaults.get_t_meas_cycle().period_ms(),
+        }
+    }
+
+    /// Set the maximum cell voltage acquisition time
+    pub const fn cell_filter_max_us(mut self, latency_us: u32) -> Self {
+        self.cell_filter_max_us = latency_us;
+        self
+    }
+
+    /// Set the maximum short circuit filter time
+    pub const fn short_circuit_filter_max_us(mut self, latency_us: u32) -> Self {
+        self.short_circuit_filter_max_us = latency_us;
+        self
+    }
+
+    /// Set the maximum current sense acquisition time
+    pub const fn current_filter_max_us(mut self, latency_us: u32) -> Self {
+        self.current_filter_max_us = latency_us;
+        self
+    }
+
+    /// Set the maximum measurement cycle period
+    pub const fn measurement_cycle_max_ms(mut self, latency_ms: u16) -> Self {
+        self.measurement_cycle_max_ms = latency_ms;
+        self
+    }
+
+    /// Select the filters and measurement cycle, or report the first latency which is shorter than any available setting
+    pub const fn build(self) -> Result<Cfg1FiltersCycles, FilterLatencyError> {
+        let Some(cell_filter) = TCellFilter::longest_within_us(self.cell_filter_max_us) else {
+            return Err(FilterLatencyError {
+                filter: Filter::Cell,
+                requested_us: self.cell_filter_max_us,
+                shortest_us: TCellFilter::ALL[0].duration_us(),
+            });
+        };
+        let Some(sc_filter) = TSCFilter::longest_within_us(self.short_circuit_filter_max_us) else {
+            return Err(FilterLatencyError {
+                filter: Filter::ShortCircuit,
+                requested_us: self.short_circuit_filter_max_us,
+                shortest_us: TSCFilter::ALL[0].duration_us(),
+            });
+        };
+        let Some(current_filter) = TCurFilter::longest_within_us(self.current_filter_max_us) else {
+            return Err(FilterLatencyError {
+                filter: Filter::Current,
+                requested_us: self.current_filter_max_us,
+                shortest_us: TCurFilter::ALL[0].duration_us(),
+            });
+        };
+        if self.measurement_cycle_max_ms < 10 {
+            return Err(FilterLatencyError {
+                filter: Filter::MeasurementCycle,
+                requested_us: self.measurement_cycle_max_ms as u32 * 1000,
+                shortest_us: 10_000,
+            });
+        }
+        let cycle_ms = if self.measurement_cycle_max_ms > 300 {
+            300
+        } else {
+            self.measurement_cycle_max_ms / 10 * 10
+        };
+        Ok(Cfg1FiltersCycles::new(
+            cell_filter,
+            sc_filter,
+            current_filter,
+            TMeasCycle::new_ms(cycle_ms),
+        ))
     }
 }
 
+impl Default for Cfg1FiltersCyclesBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Filters and cycle of the CFG1_FILTERS_CYCLES register
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Filter {
+    /// TCELL_FILTER
+    Cell,
+    /// T_SC_FILTER
+    ShortCircuit,
+    /// T_CUR_FILTER
+    Current,
+    /// T_MEAS_CYCLE
+    MeasurementCycle,
+}
+
+impl core::fmt::Display for Filter {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.write_str(match self {
+            Filter::Cell => "cell voltage filter",
+            Filter::ShortCircuit => "short circuit filter",
+            Filter::Current => "current sense filter",
+            Filter::MeasurementCycle => "measurement cycle",
+        })
+    }
+}
+
+/// A requested latency which is shorter than every setting of a filter
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FilterLatencyError {
+    /// The filter whose latency could not be met
+    pub filter: Filter,
+    /// The requested latency in us
+    pub requested_us: u32,
+    /// The shortest latency the filter supports in us
+    pub shortest_us: u32,
+}
+
+impl core::fmt::Display for FilterLatencyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{} latency of {} us is shorter than the minimum of {} us",
+            self.filter, self.requested_us, self.shortest_us
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FilterLatencyError {}
+
 impl Deref for Cfg1FiltersCycles {
     type Target = u16;
     fn deref(&self) -> &u16 {
@@ -276,6 +587,19 @@ impl From<u16> for Cfg1FiltersCycles {
     }
 }
 
+impl core::fmt::Display for Cfg1FiltersCycles {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Config 1 Filters, Cycles: {{\n {},\n  {},\n  {},\n  {}\n}}",
+            self.get_t_cell_filter(),
+            self.get_t_sc_filter(),
+            self.get_t_curr_filter(),
+            self.get_t_meas_cycle()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Cfg1FiltersCycles {
     fn format(&self, f: defmt::Formatter) {
@@ -1,23 +1,14 @@
 use core::ops::Deref;
 
-use defmt::Format;
-
-const VCELL_EN_1_MASK: u16 = 0x0001;
-const VCELL_EN_2_MASK: u16 = 0x0002;
-const VCELL_EN_3_MASK: u16 = 0x0004;
-const VCELL_EN_4_MASK: u16 = 0x0008;
-const VCELL_EN_5_MASK: u16 = 0x0010;
-const VB_EN: u16 = 0x0020;
-const NTC_EN: u16 = 0x0040;
-const CSA_EN: u16 = 0x0080;
-const CC_ACC_EN: u16 = 0x0100;
-const OVC_EN: u16 = 0x0200;
-const SC_EN: u16 = 0x0400;
-const DCHG_HS_LS: u16 = 0x0800;
-const CHG_HS_LS: u16 = 0x1000;
-const CRC_EN: u16 = 0x2000;
+use super::regmap::cfg2_enables::{
+    CC_ACC_EN, CHG_HS_LS, CRC_EN, CSA_EN, DCHG_HS_LS, NTC_EN, OVC_EN, SC_EN, VB_EN, VCELL_EN_1,
+    VCELL_EN_2, VCELL_EN_3, VCELL_EN_4, VCELL_EN_5,
+};
 
 /// Configuration for high-side vs. low side FETs for charge and discharge
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
 pub enum FetConfig {
     /// High-side FET configuration
     HighSide,
@@ -34,7 +25,17 @@ impl From<FetConfig> for u16 {
     }
 }
 
-impl Format for FetConfig {
+impl core::fmt::Display for FetConfig {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            FetConfig::HighSide => write!(f, "High Side"),
+            FetConfig::LowSide => write!(f, "Low Side"),
+        }
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FetConfig {
     fn format(&self, f: defmt::Formatter) {
         match self {
             FetConfig::HighSide => defmt::write!(f, "High Side"),
@@ -50,11 +51,13 @@ impl Format for FetConfig {
 /// SC (Short Circuit Protection), and CRC (Cyclic Redundancy Check for I2C data integrity)
 /// Additionally, the register configures the charge and discharge FET drivers for use with
 /// high side or low side FETs
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Cfg2Enables(u16);
 
 impl Cfg2Enables {
-    /// Create a new Cfg2 Enables register value
+    /// Create a new Cfg2 Enables register value.
+    /// [`Cfg2Enables::builder`] sets the same fields by name.
+    #[allow(clippy::too_many_arguments)]
     pub const fn new(
         vcell_en_1: bool,
         vcell_en_2: bool,
@@ -72,138 +75,143 @@ impl Cfg2Enables {
         crc_en: bool,
     ) -> Self {
         Self(
-            (vcell_en_1 as u16)
-                | (vcell_en_2 as u16) << 1
-                | (vcell_en_3 as u16) << 2
-                | (vcell_en_4 as u16) << 3
-                | (vcell_en_5 as u16) << 4
-                | (vb_en as u16) << 5
-                | (ntc_en as u16) << 6
-                | (csa_en as u16) << 7
-                | (cc_acc_en as u16) << 8
-                | (ovc_en as u16) << 9
-                | (sc_en as u16) << 10
-                | (dchg_hs_ls as u16) << 11
-                | (chg_hs_ls as u16) << 12
-                | (crc_en as u16) << 13,
+            VCELL_EN_1.set(0, vcell_en_1 as u16)
+                | VCELL_EN_2.set(0, vcell_en_2 as u16)
+                | VCELL_EN_3.set(0, vcell_en_3 as u16)
+                | VCELL_EN_4.set(0, vcell_en_4 as u16)
+                | VCELL_EN_5.set(0, vcell_en_5 as u16)
+                | VB_EN.set(0, vb_en as u16)
+                | NTC_EN.set(0, ntc_en as u16)
+                | CSA_EN.set(0, csa_en as u16)
+                | CC_ACC_EN.set(0, cc_acc_en as u16)
+                | OVC_EN.set(0, ovc_en as u16)
+                | SC_EN.set(0, sc_en as u16)
+                | DCHG_HS_LS.set(0, dchg_hs_ls as u16)
+                | CHG_HS_LS.set(0, chg_hs_ls as u16)
+                | CRC_EN.set(0, crc_en as u16),
         )
     }
 
+    /// Start building a register value with every conversion and protection disabled, and high-side FETs
+    pub const fn builder() -> Cfg2EnablesBuilder {
+        Cfg2EnablesBuilder(Self(0))
+    }
+
     /// Get the VCELL_EN_1 flag
     pub const fn get_vcell_en_1(&self) -> bool {
-        (self.0 & VCELL_EN_1_MASK) != 0
+        VCELL_EN_1.get(self.0) != 0
     }
 
     /// Set the VCELL_EN_1 flag
     pub const fn set_vcell_en_1(&mut self, value: bool) {
-        self.0 = self.0 & !VCELL_EN_1_MASK | value as u16;
+        self.0 = VCELL_EN_1.set(self.0, value as u16);
     }
 
     /// Get the VCELL_EN_2 flag
     pub const fn get_vcell_en_2(&self) -> bool {
-        (self.0 & VCELL_EN_2_MASK) != 0
+        VCELL_EN_2.get(self.0) != 0
     }
 
     /// Set the VCELL_EN_2 flag
     pub const fn set_vcell_en_2(&mut self, value: bool) {
-        self.0 = self.0 & !VCELL_EN_2_MASK | ((value as u16) << 1);
+        self.0 = VCELL_EN_2.set(self.0, value as u16);
     }
 
     /// Get the VCELL_EN_3 flag
     pub const fn get_vcell_en_3(&self) -> bool {
-        (self.0 & VCELL_EN_3_MASK) != 0
+        VCELL_EN_3.get(self.0) != 0
     }
 
     /// Set the VCELL_EN_3 flag
     pub const fn set_vcell_en_3(&mut self, value: bool) {
-        self.0 = self.0 & !VCELL_EN_3_MASK | ((value as u16) << 2);
+        self.0 = VCELL_EN_3.set(self.0, value as u16);
     }
 
     /// Get the VCELL_EN_4 flag
     pub const fn get_vcell_en_4(&self) -> bool {
-        (self.0 & VCELL_EN_4_MASK) != 0
+        VCELL_EN_4.get(self.0) != 0
     }
 
     /// Set the VCELL_EN_4 flag
     pub const fn set_vcell_en_4(&mut self, value: bool) {
-        self.0 = self.0 & !VCELL_EN_4_MASK | ((value as u16) << 3);
+        self.0 = VCELL_EN_4.set(self.0, value as u16);
     }
 
     /// Get the VCELL_EN_5 flag
     pub const fn get_vcell_en_5(&self) -> bool {
-        (self.0 & VCELL_EN_5_MASK) != 0
+        VCELL_EN_5.get(self.0) != 0
     }
 
     /// Set the VCELL_EN_5 flag
     pub const fn set_vcell_en_5(&mut self, value: bool) {
-        self.0 = self.0 & !VCELL_EN_5_MASK | ((value as u16) << 4);
+        self.0 = VCELL_EN_5.set(self.0, value as u16);
     }
 
     /// Get the VB_EN flag
     pub const fn get_vb_en(&self) -> bool {
-        (self.0 & VB_EN) != 0
+        VB_EN.get(self.0) != 0
     }
 
     /// Set the VB_EN flag
     pub const fn set_vb_en(&mut self, value: bool) {
-        self.0 = self.0 & !VB_EN | ((value as u16) << 5);
+        self.0 = VB_EN.set(self.0, value as u16);
     }
 
     /// Get the NTC_EN flag
     pub const fn get_ntc_en(&self) -> bool {
-        (self.0 & NTC_EN) != 0
+        NTC_EN.get(self.0) != 0
     }
 
     /// Set the cell temperature  flag
     pub const fn set_ntc_en(&mut self, value: bool) {
-        self.0 = self.0 & !NTC_EN | ((value as u16) << 6);
+        self.0 = NTC_EN.set(self.0, value as u16);
     }
 
     /// Get the current sense adc enable status
     pub const fn get_csa_en(&self) -> bool {
-        (self.0 & CSA_EN) != 0
+        CSA_EN.get(self.0) != 0
     }
 
     /// Set the current sense adc enable status
     pub const fn set_csa_en(&mut self, value: bool) {
-        self.0 = self.0 & !CSA_EN | ((value as u16) << 7);
+        self.0 = CSA_EN.set(self.0, value as u16);
     }
 
     /// Get the couloumb counting enable status
     pub const fn get_cc_acc_en(&self) -> bool {
-        (self.0 & CC_ACC_EN) != 0
+        CC_ACC_EN.get(self.0) != 0
     }
 
     /// Set the couloumb counting enable status
     pub const fn set_cc_acc_en(&mut self, value: bool) {
-        self.0 = self.0 & !CC_ACC_EN | ((value as u16) << 8);
+        self.0 = CC_ACC_EN.set(self.0, value as u16);
     }
 
     /// Get the overcurrent protection enable status
     pub const fn get_ovc_en(&self) -> bool {
-        (self.0 & OVC_EN) != 0
+        OVC_EN.get(self.0) != 0
     }
 
     /// Set the overcurrent protection enable statua
     pub const fn set_ovc_en(&mut self, value: bool) {
-        self.0 = self.0 & !OVC_EN | ((value as u16) << 9);
+        self.0 = OVC_EN.set(self.0, value as u16);
     }
 
     /// Get the enable status of the short circuit protection
     pub const fn get_sc_en(&self) -> bool {
-        (self.0 & SC_EN) != 0
+        SC_EN.get(self.0) != 0
     }
 
     /// Enable or disable short circuit protection
     pub const fn set_sc_en(&mut self, value: bool) {
-        self.0 = self.0 & !SC_EN | ((value as u16) << 10);
+        self.0 = SC_EN.set(self.0, value as u16);
     }
 
     /// Get the Discahrge FET configuration
     /// - False configured for High Side FET
     /// - False configured for Low Side FET
     pub const fn get_dchg_hs_ls(&self) -> FetConfig {
-        match (self.0 & DCHG_HS_LS) == 0 {
+        match DCHG_HS_LS.get(self.0) == 0 {
             true => FetConfig::HighSide,
             false => FetConfig::LowSide,
         }
@@ -213,14 +221,14 @@ impl Cfg2Enables {
     /// - True configured for High Side FET
     /// - False configured for Low Side FET
     pub const fn set_dchg_hs_ls(&mut self, value: FetConfig) {
-        self.0 = self.0 & !DCHG_HS_LS | ((value as u16) << 11);
+        self.0 = DCHG_HS_LS.set(self.0, value as u16);
     }
 
     /// Get the charge FET configuration
     /// - True configured for High Side FET
     /// - False configured for Low Side FET
     pub const fn get_chg_hs_ls(&self) -> FetConfig {
-        match (self.0 & CHG_HS_LS) == 0 {
+        match CHG_HS_LS.get(self.0) == 0 {
             true => FetConfig::HighSide,
             false => FetConfig::LowSide,
         }
@@ -230,17 +238,105 @@ impl Cfg2Enables {
     /// - True configured for High Side FET
     /// - False configured for Low Side FET
     pub const fn set_chg_hs_ls(&mut self, value: FetConfig) {
-        self.0 = self.0 & !CHG_HS_LS | ((value as u16) << 12);
+        self.0 = CHG_HS_LS.set(self.0, value as u16);
     }
 
     /// Get the CRC_EN flag
     pub const fn get_crc_en(&self) -> bool {
-        (self.0 & CRC_EN) != 0
+        CRC_EN.get(self.0) != 0
     }
 
     /// Set the CRC_EN flag
     pub const fn set_crc_en(&mut self, value: bool) {
-        self.0 = self.0 & !CRC_EN | ((value as u16) << 13);
+        self.0 = CRC_EN.set(self.0, value as u16);
+    }
+}
+
+/// Builder for [`Cfg2Enables`] with named settings
+///
+/// ```
+/// use l9961::registers::{Cfg2Enables, FetConfig};
+///
+/// let enables = Cfg2Enables::builder()
+///     .cell_channels(4)
+///     .pack_voltage(true)
+///     .current_sense(true)
+///     .short_circuit(true)
+///     .discharge_fet(FetConfig::LowSide)
+///     .build();
+/// assert!(enables.get_vcell_en_4() && !enables.get_vcell_en_5());
+/// ```
+#[derive(Clone, Copy)]
+pub struct Cfg2EnablesBuilder(Cfg2Enables);
+
+impl Cfg2EnablesBuilder {
+    /// Enable the voltage conversion of cells 1 to `cells`, and disable the others
+    pub const fn cell_channels(mut self, cells: u8) -> Self {
+        self.0.set_vcell_en_1(cells >= 1);
+        self.0.set_vcell_en_2(cells >= 2);
+        self.0.set_vcell_en_3(cells >= 3);
+        self.0.set_vcell_en_4(cells >= 4);
+        self.0.set_vcell_en_5(cells >= 5);
+        self
+    }
+
+    /// Enable the battery pack voltage conversion
+    pub const fn pack_voltage(mut self, enabled: bool) -> Self {
+        self.0.set_vb_en(enabled);
+        self
+    }
+
+    /// Enable the NTC temperature conversion
+    pub const fn ntc(mut self, enabled: bool) -> Self {
+        self.0.set_ntc_en(enabled);
+        self
+    }
+
+    /// Enable the current sense ADC
+    pub const fn current_sense(mut self, enabled: bool) -> Self {
+        self.0.set_csa_en(enabled);
+        self
+    }
+
+    /// Enable the coulomb counter accumulator
+    pub const fn coulomb_counter(mut self, enabled: bool) -> Self {
+        self.0.set_cc_acc_en(enabled);
+        self
+    }
+
+    /// Enable over-current protection
+    pub const fn over_current(mut self, enabled: bool) -> Self {
+        self.0.set_ovc_en(enabled);
+        self
+    }
+
+    /// Enable short circuit protection
+    pub const fn short_circuit(mut self, enabled: bool) -> Self {
+        self.0.set_sc_en(enabled);
+        self
+    }
+
+    /// Set the discharge FET configuration
+    pub const fn discharge_fet(mut self, fet: FetConfig) -> Self {
+        self.0.set_dchg_hs_ls(fet);
+        self
+    }
+
+    /// Set the charge FET configuration
+    pub const fn charge_fet(mut self, fet: FetConfig) -> Self {
+        self.0.set_chg_hs_ls(fet);
+        self
+    }
+
+    /// Enable the CRC of I2C transfers
+    pub const fn crc(mut self, enabled: bool) -> Self {
+        self.0.set_crc_en(enabled);
+        self
+    }
+
+    /// Get the register value
+    pub const fn build(self) -> Cfg2Enables {
+        self.0
     }
 }
 
@@ -258,6 +354,29 @@ impl From<u16> for Cfg2Enables {
     }
 }
 
+impl core::fmt::Display for Cfg2Enables {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Config 2 Enables: {{\n  Cell 1 voltage conversion enabled: {},\n  Cell 2 voltage conversion enabled: {},\n  Cell 3 voltage conversion enabled: {},\n  Cell 4 voltage conversion enabled: {},\n  Cell 5 voltage conversion enabled: {},\n  Battery voltage conversion enabled: {},\n  Temperature conversion enabled: {},\n  Current sense enabled: {},\n  Coulomb counter accumulator enabled: {},\n  Overcurrent protection enabled: {},\n  Short circuit monitor enabled: {},\n  Discharge high side low side: {},\n  Charge high side low side: {},\n  CRC enabled: {}\n}}",
+            self.get_vcell_en_1(),
+            self.get_vcell_en_2(),
+            self.get_vcell_en_3(),
+            self.get_vcell_en_4(),
+            self.get_vcell_en_5(),
+            self.get_vb_en(),
+            self.get_ntc_en(),
+            self.get_csa_en(),
+            self.get_cc_acc_en(),
+            self.get_ovc_en(),
+            self.get_sc_en(),
+            self.get_dchg_hs_ls(),
+            self.get_chg_hs_ls(),
+            self.get_crc_en()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Cfg2Enables {
     fn format(&self, f: defmt::Formatter) {
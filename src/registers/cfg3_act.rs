@@ -1,17 +1,11 @@
 use core::ops::Deref;
 
-const CELL_1_BAL_EN: u16 = 1 << 0;
-const CELL_2_BAL_EN: u16 = 1 << 1;
-const CELL_3_BAL_EN: u16 = 1 << 2;
-const CELL_4_BAL_EN: u16 = 1 << 3;
-const CELL_5_BAL_EN: u16 = 1 << 4;
-const CHG_EN: u16 = 1 << 5;
-const DISCHG_EN: u16 = 1 << 6;
+use super::regmap::cfg3_act::{BAL1_ON, BAL2_ON, BAL3_ON, BAL4_ON, BAL5_ON, CHG_ON, DCHG_ON};
 
 /// Configuration 3 Activation register
 /// Contains the configuration for the activation of the balancing,
 /// charge, and discharge FETs
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct Cfg3Act(u16);
 
 impl Cfg3Act {
@@ -26,112 +20,96 @@ impl Cfg3Act {
         dischg_en: bool,
     ) -> Self {
         Self(
-            (cell_1_bal_en as u16)
-                | (cell_2_bal_en as u16) << 1
-                | (cell_3_bal_en as u16) << 2
-                | (cell_4_bal_en as u16) << 3
-                | (cell_5_bal_en as u16) << 4
-                | (chg_en as u16) << 5
-                | (dischg_en as u16) << 6,
+            BAL1_ON.set(0, cell_1_bal_en as u16)
+                | BAL2_ON.set(0, cell_2_bal_en as u16)
+                | BAL3_ON.set(0, cell_3_bal_en as u16)
+                | BAL4_ON.set(0, cell_4_bal_en as u16)
+                | BAL5_ON.set(0, cell_5_bal_en as u16)
+                | CHG_ON.set(0, chg_en as u16)
+                | DCHG_ON.set(0, dischg_en as u16),
         )
     }
 
     /// Cell 1 Charge balance enabled
     pub fn get_cell_1_balance_enabled(&self) -> bool {
-        self.0 & CELL_1_BAL_EN != 0
+        BAL1_ON.get(self.0) != 0
     }
 
     /// Set cell 1 charge balance enable
     pub fn set_cell_1_balance_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CELL_1_BAL_EN;
-        } else {
-            self.0 &= !CELL_1_BAL_EN;
-        }
+        self.0 = BAL1_ON.set(self.0, enabled as u16);
     }
 
     /// Cell 2 Charge balance enable
     pub fn get_cell_2_balance_enabled(&self) -> bool {
-        self.0 & CELL_2_BAL_EN != 0
+        BAL2_ON.get(self.0) != 0
     }
 
     /// Set cell 2 charge balance enable
     pub fn set_cell_2_balance_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CELL_2_BAL_EN;
-        } else {
-            self.0 &= !CELL_2_BAL_EN;
-        }
+        self.0 = BAL2_ON.set(self.0, enabled as u16);
     }
 
     /// Cell 3 Charge balance enable
     pub fn get_cell_3_balance_enabled(&self) -> bool {
-        self.0 & CELL_3_BAL_EN != 0
+        BAL3_ON.get(self.0) != 0
     }
 
     /// Set cell 3 charge balance enable
     pub fn set_cell_3_balance_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CELL_3_BAL_EN;
-        } else {
-            self.0 &= !CELL_3_BAL_EN;
-        }
+        self.0 = BAL3_ON.set(self.0, enabled as u16);
     }
 
     /// Cell 4 Charge balance enable
     pub fn get_cell_4_balance_enabled(&self) -> bool {
-        self.0 & CELL_4_BAL_EN != 0
+        BAL4_ON.get(self.0) != 0
     }
 
     /// Set cell 4 charge balance enable
     pub fn set_cell_4_balance_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CELL_4_BAL_EN;
-        } else {
-            self.0 &= !CELL_4_BAL_EN;
-        }
+        self.0 = BAL4_ON.set(self.0, enabled as u16);
     }
 
     /// Cell 5 Charge balance enable
     pub fn get_cell_5_balance_enabled(&self) -> bool {
-        self.0 & CELL_5_BAL_EN != 0
+        BAL5_ON.get(self.0) != 0
     }
 
     /// Set cell 5 charge balance enable
     pub fn set_cell_5_balance_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CELL_5_BAL_EN;
-        } else {
-            self.0 &= !CELL_5_BAL_EN;
-        }
+        self.0 = BAL5_ON.set(self.0, enabled as u16);
     }
 
     /// Charge FET enable
     pub fn get_charge_enabled(&self) -> bool {
-        self.0 & CHG_EN != 0
+        CHG_ON.get(self.0) != 0
     }
 
     /// Set FET charge enable
     pub fn set_charge_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= CHG_EN;
-        } else {
-            self.0 &= !CHG_EN;
-        }
+        self.0 = CHG_ON.set(self.0, enabled as u16);
     }
 
     /// Discharge FET enable
     pub fn get_discharge_enabled(&self) -> bool {
-        self.0 & DISCHG_EN != 0
+        DCHG_ON.get(self.0) != 0
     }
 
     /// Set FET discharge enable
     pub fn set_discharge_enabled(&mut self, enabled: bool) {
-        if enabled {
-            self.0 |= DISCHG_EN;
-        } else {
-            self.0 &= !DISCHG_EN;
-        }
+        self.0 = DCHG_ON.set(self.0, enabled as u16);
+    }
+
+    /// Whether both FETs are off and no cell is being balanced
+    pub fn is_all_off(&self) -> bool {
+        let active = BAL1_ON.mask()
+            | BAL2_ON.mask()
+            | BAL3_ON.mask()
+            | BAL4_ON.mask()
+            | BAL5_ON.mask()
+            | CHG_ON.mask()
+            | DCHG_ON.mask();
+        self.0 & active == 0
     }
 }
 
@@ -144,11 +122,27 @@ impl Deref for Cfg3Act {
 
 impl From<u16> for Cfg3Act {
     fn from(id: u16) -> Self {
-        debug_assert!(id & 0b1111111110000000 == 0, "Invalid CFG3_ACT value");
+        debug_assert!(id & 0b1111111100000000 == 0, "Invalid CFG3_ACT value");
         Cfg3Act(id)
     }
 }
 
+impl core::fmt::Display for Cfg3Act {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "Config 3 Active: {{\n  Cell 1 balancing active: {},\n  Cell 2 balancing active: {},\n  Cell 3 balancing active: {},\n  Cell 4 balancing active: {},\n  Cell 5 balancing active: {},\n  Charge FET active: {},\n  Discharge FET active: {}\n}}",
+            self.get_cell_1_balance_enabled(),
+            self.get_cell_2_balance_enabled(),
+            self.get_cell_3_balance_enabled(),
+            self.get_cell_4_balance_enabled(),
+            self.get_cell_5_balance_enabled(),
+            self.get_charge_enabled(),
+            self.get_discharge_enabled()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for Cfg3Act {
     fn format(&self, f: defmt::Formatter) {
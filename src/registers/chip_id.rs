@@ -1,18 +1,20 @@
 use core::ops::Deref;
 
+use super::regmap::chipid::{METAL_ID, SILICON_ID};
+
 /// The Chip ID register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct ChipID(u16);
 
 impl ChipID {
     /// Get the Metal ID of the chip
     pub fn metal_id(&self) -> u8 {
-        (self.0 & 0x000F) as u8
+        METAL_ID.get(self.0) as u8
     }
 
     /// Get the Silicon ID of the chip
     pub fn silicon_id(&self) -> u8 {
-        ((self.0 & 0x00F0) >> 4) as u8
+        SILICON_ID.get(self.0) as u8
     }
 }
 
@@ -30,6 +32,17 @@ impl From<u16> for ChipID {
     }
 }
 
+impl core::fmt::Display for ChipID {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "ChipID: {{\n  metal_id: {},\n  silicon_id: {}\n}}",
+            self.metal_id(),
+            self.silicon_id()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for ChipID {
     fn format(&self, f: defmt::Formatter) {
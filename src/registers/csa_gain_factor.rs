@@ -1,7 +1,7 @@
 use core::ops::Deref;
 
 /// Current sense ADC gain factor configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct CsaGainFactor(u16);
 
 impl Deref for CsaGainFactor {
@@ -17,6 +17,12 @@ impl From<u16> for CsaGainFactor {
     }
 }
 
+impl core::fmt::Display for CsaGainFactor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "CSA Gain Factor : {:#04x}", self.0,)
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for CsaGainFactor {
     fn format(&self, f: defmt::Formatter) {
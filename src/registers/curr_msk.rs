@@ -3,36 +3,40 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::curr_msk;
+
 bitflags! {
     /// Programmable mask register for whether current related faults assert the on the PRDRV FET and FAULTN outputs
     pub struct CurrMsk : u16 {
         /// Masks Over Current in Charge fault asserting on the charge pin
-        const OVC_CHG_PRDRV_MSK = 0x0001;
+        const OVC_CHG_PRDRV_MSK = curr_msk::OVC_CHG_PRDRV_MSK.mask();
         /// Masks Over Current in Discharge fault asserting on the discharge pin
-        const OVC_DCHG_PRDRV_MSK = 0x0002;
+        const OVC_DCHG_PRDRV_MSK = curr_msk::OVC_DCHG_PRDRV_MSK.mask();
         /// Masks Short Circuit in Discharge fault asserting on the discharge pin
-        const SC_DCHG_PRDRV_MSK = 0x0004;
+        const SC_DCHG_PRDRV_MSK = curr_msk::SC_DCHG_PRDRV_MSK.mask();
         /// Masks Persistent Over Current in Charge fault asserting on the fuse pin
-        const PERSIST_OVC_CHG_FUSE_MSK = 0x0008;
+        const PERSIST_OVC_CHG_FUSE_MSK = curr_msk::PERSIST_OVC_CHG_FUSE_MSK.mask();
         /// Masks Persistent Over Current in Discharge fault asserting on the fuse pin
-        const PERSIST_OVC_DCHG_FUSE_MSK = 0x0010;
+        const PERSIST_OVC_DCHG_FUSE_MSK = curr_msk::PERSIST_OVC_DCHG_FUSE_MSK.mask();
         /// Masks Persistent Short Circuit in Discharge fault asserting on the fuse pin
-        const PERSIST_SC_DCHG_FUSE_MSK = 0x0020;
+        const PERSIST_SC_DCHG_FUSE_MSK = curr_msk::PERSIST_SC_DCHG_FUSE_MSK.mask();
         /// Masks Over Current in Charge fault asserting on the FAULTN pin
-        const OVC_CHG_FAULTN_MSK = 0x0040;
+        const OVC_CHG_FAULTN_MSK = curr_msk::OVC_CHG_FAULTN_MSK.mask();
         /// Masks Over Current in Discharge fault asserting on the FAULTN pin
-        const OV_DCHG_FAULTN_MSK = 0x0080;
+        const OV_DCHG_FAULTN_MSK = curr_msk::OVC_DCHG_FAULTN_MSK.mask();
         /// Masks Short Circuit in Discharge fault asserting on the FAULTN pin
-        const SC_DCHG_FAULTN_MSK = 0x0100;
+        const SC_DCHG_FAULTN_MSK = curr_msk::SC_DCHG_FAULTN_MSK.mask();
         /// Masks Persistent Short Cirtcuit in Discharge fault asserting on the FAULTN pin
-        const PERSIST_SC_DCHG_FAULTN_MSK = 0x0200;
+        const PERSIST_SC_DCHG_FAULTN_MSK = curr_msk::PERSIST_SC_DCHG_FAULTN_MSK.mask();
         /// Masks Persistent Over Current in Charge fault asserting on the FAULTN pin
-        const PERSIST_OVC_CHG_FAULTN_MSK = 0x0400;
+        const PERSIST_OVC_CHG_FAULTN_MSK = curr_msk::PERSIST_OVC_CHG_FAULTN_MSK.mask();
         /// Masks Persistent Over Current in Discharge fault asserting on the FAULTN pin
-        const PERSIST_OVC_DCHG_FAULTN_MSK = 0x0800;
+        const PERSIST_OVC_DCHG_FAULTN_MSK = curr_msk::PERSIST_OVC_DCHG_FAULTN_MSK.mask();
 
         // Ensure that the reserved bit is always 0
         // TODO: This requires Bitflags 2.x
         // const _ = 0x7FFF;
     }
 }
+
+impl_display_flags!(CurrMsk);
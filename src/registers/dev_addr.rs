@@ -1,24 +1,27 @@
 use core::ops::Deref;
 
+#[cfg(feature = "defmt")]
 use defmt::debug_assert;
 
+use super::regmap::dev_addr::DEV_ADDR_ID;
+
 /// The device address register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct DevAddr(u16);
 
 impl DevAddr {
     /// Get the configured device_address
     pub fn get_device_address(&self) -> u8 {
-        (self.0 & 0b01111111) as u8
+        DEV_ADDR_ID.get(self.0) as u8
     }
 
     /// Set the device_address
     pub fn set_device_address(&mut self, device_address: u8) {
         debug_assert!(
-            device_address & 0b01111111 == device_address,
+            DEV_ADDR_ID.get(device_address as u16) == device_address as u16,
             "Invalid device address"
         );
-        self.0 = device_address as u16;
+        self.0 = DEV_ADDR_ID.set(self.0, device_address as u16);
     }
 }
 
@@ -31,11 +34,17 @@ impl Deref for DevAddr {
 
 impl From<u16> for DevAddr {
     fn from(id: u16) -> Self {
-        debug_assert!(id & 0b01111111 == id, "Invalid device address");
+        debug_assert!(id & DEV_ADDR_ID.mask() == id, "Invalid device address");
         Self(id)
     }
 }
 
+impl core::fmt::Display for DevAddr {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "Device Address: {:#02x}", self.get_device_address(),)
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for DevAddr {
     fn format(&self, f: defmt::Formatter) {
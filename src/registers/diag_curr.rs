@@ -3,39 +3,43 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::diag_curr;
+
 bitflags! {
     /// Diagnostic fault flags
     /// These flags have different behavior by bit position
     pub struct DiagCurr:u16 {
         /// Mask for the CC SAT bit
         /// Read Only
-        const CC_SAT = 0x0001;
+        const CC_SAT = diag_curr::CC_SAT.mask();
         /// Mask for the OVC CHG bit
         /// Clear on Write
-        const OVC_CHG = 0x0002;
+        const OVC_CHG = diag_curr::OVC_CHG.mask();
         /// Mask for the OVC DCHG bit
         /// Clear on Write
-        const OVC_DCHG = 0x0004;
+        const OVC_DCHG = diag_curr::OVC_DCHG.mask();
         /// Mask for the PERSIST_OVC_CHG bit
         /// Clear on Write
-        const PERSIST_OVC_CHG = 0x0008;
+        const PERSIST_OVC_CHG = diag_curr::PERSIST_OVC_CHG.mask();
         /// Mask for the PERSIST_OVC_DCHG bit
         /// Clear on Write
-        const PERSIST_OVC_DCHG = 0x0010;
+        const PERSIST_OVC_DCHG = diag_curr::PERSIST_OVC_DCHG.mask();
         /// Mask for the SC_DCHG bit
         /// Clear on Write
-        const SC_DCHG = 0x0020;
+        const SC_DCHG = diag_curr::SC_DCHG.mask();
         /// Mask for the PERSIST_SC_DCHG bit
         /// Clear on Write
-        const PERSIST_SC_DCHG = 0x0040;
+        const PERSIST_SC_DCHG = diag_curr::PERSIST_SC_DCHG.mask();
         /// Mask for the FUSE_EXT bit
         /// Read Only
-        const FUSE_EXT = 0x0080;
+        const FUSE_EXT = diag_curr::FUSE_EXT.mask();
         /// Mask for the FAULTN_EXT bit
         /// Clear on Write
-        const FAULTN_EXT = 0x0100;
+        const FAULTN_EXT = diag_curr::FAULTN_EXT.mask();
         // Ensure that the reserved bit is always 0
         // TODO: This requires Bitflags 2.x
         // const _ = 0x0EFF;
     }
 }
+
+impl_display_flags!(DiagCurr);
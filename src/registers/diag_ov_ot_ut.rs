@@ -3,41 +3,45 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::diag_ov_ot_ut;
+
 bitflags! {
     /// Diagnostic Over-Voltage, Over-Temperature, and Under-Temperature flags
     /// All are clear-on-write, and must be cleared by the mcu to re-enable masked behavior
     pub struct DiagOvOtUt:u16 {
         /// Cell 1 Over-Voltage flag
-        const CELL1_OV = 0x0001;
+        const CELL1_OV = diag_ov_ot_ut::CELL1_OV.mask();
         /// Cell 2 Over-Voltage flag
-        const CELL2_OV = 0x0002;
+        const CELL2_OV = diag_ov_ot_ut::CELL2_OV.mask();
         /// Cell 3 Over-Voltage flag
-        const CELL3_OV = 0x0004;
+        const CELL3_OV = diag_ov_ot_ut::CELL3_OV.mask();
         /// Cell 4 Over-Voltage flag
-        const CELL4_OV = 0x0008;
+        const CELL4_OV = diag_ov_ot_ut::CELL4_OV.mask();
         /// Cell 5 Over-Voltage flag
-        const CELL5_OV = 0x0010;
+        const CELL5_OV = diag_ov_ot_ut::CELL5_OV.mask();
         /// Pack Over-Voltage flag
-        const PACK_OV = 0x0020;
+        const PACK_OV = diag_ov_ot_ut::VB_OV.mask();
         /// Cell 1 Severe Over-Voltage flag
-        const CELL1_SEVERE_OV = 0x0040;
+        const CELL1_SEVERE_OV = diag_ov_ot_ut::V_SEVERE_CELL1_OV.mask();
         /// Cell 2 Severe Over-Voltage flag
-        const CELL2_SEVERE_OV = 0x0080;
+        const CELL2_SEVERE_OV = diag_ov_ot_ut::V_SEVERE_CELL2_OV.mask();
         /// Cell 3 Severe Over-Voltage flag
-        const CELL3_SEVERE_OV = 0x0100;
+        const CELL3_SEVERE_OV = diag_ov_ot_ut::V_SEVERE_CELL3_OV.mask();
         /// Cell 4 Severe Over-Voltage flag
-        const CELL4_SEVERE_OV = 0x0200;
+        const CELL4_SEVERE_OV = diag_ov_ot_ut::V_SEVERE_CELL4_OV.mask();
         /// Cell 5 Severe Over-Voltage flag
-        const CELL5_SEVERE_OV = 0x0400;
+        const CELL5_SEVERE_OV = diag_ov_ot_ut::V_SEVERE_CELL5_OV.mask();
         /// Cell voltage sum and pack measurement comparison check failed
-        const VB_SUM_CHECK_FAIL = 0x0800;
+        const VB_SUM_CHECK_FAIL = diag_ov_ot_ut::VB_SUM_CHECK_FAIL.mask();
         /// NTC Over-Temperature flag
-        const NTC_OT = 0x1000;
+        const NTC_OT = diag_ov_ot_ut::NTC_OT.mask();
         /// NTC Severe Over-Temperature flag
-        const NTC_SEVERE_OT = 0x2000;
+        const NTC_SEVERE_OT = diag_ov_ot_ut::NTC_SEVERE_OT.mask();
         /// NTC Under-Temperature flag
-        const NTC_UT = 0x4000;
+        const NTC_UT = diag_ov_ot_ut::NTC_UT.mask();
         /// Die Over-Temperature flag
-        const DIE_OT = 0x8000;
+        const DIE_OT = diag_ov_ot_ut::DIE_OT.mask();
     }
 }
+
+impl_display_flags!(DiagOvOtUt);
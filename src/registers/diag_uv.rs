@@ -3,41 +3,45 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::diag_uv;
+
 bitflags! {
     /// Diagnostic Under Voltage flags
     /// All are clear-on-write, and must be cleared by the mcu to re-enable masked behavior
     pub struct DiagUv:u16 {
         /// Cell 1 Under-Voltage flag
-        const CELL1_UV = 0x0001;
+        const CELL1_UV = diag_uv::CELL1_UV.mask();
         /// Cell 2 Under-Voltage flag
-        const CELL2_UV = 0x0002;
+        const CELL2_UV = diag_uv::CELL2_UV.mask();
         /// Cell 3 Under-Voltage flag
-        const CELL3_UV = 0x0004;
+        const CELL3_UV = diag_uv::CELL3_UV.mask();
         /// Cell 4 Under-Voltage flag
-        const CELL4_UV = 0x0008;
+        const CELL4_UV = diag_uv::CELL4_UV.mask();
         /// Cell 5 Under-Voltage flag
-        const CELL5_UV = 0x0010;
+        const CELL5_UV = diag_uv::CELL5_UV.mask();
         /// Cell 1 under balance voltage flag
-        const BAL1_UV = 0x0020;
+        const BAL1_UV = diag_uv::BAL1_UV.mask();
         /// Cell 2 under balance voltage flag
-        const BAL2_UV = 0x0040;
+        const BAL2_UV = diag_uv::BAL2_UV.mask();
         /// Cell 3 under balance voltage flag
-        const BAL3_UV = 0x0080;
+        const BAL3_UV = diag_uv::BAL3_UV.mask();
         /// Cell 4 under balance voltage flag
-        const BAL4_UV = 0x0100;
+        const BAL4_UV = diag_uv::BAL4_UV.mask();
         /// Cell 5 under balance voltage flag
-        const BAL5_UV = 0x0200;
+        const BAL5_UV = diag_uv::BAL5_UV.mask();
         /// Pack Under-Voltage flag
-        const VB_UV = 0x0400;
+        const VB_UV = diag_uv::VB_UV.mask();
         /// Cell 1 Severe Under-Voltage flag
-        const V_SEVERE_CELL1_UV = 0x0800;
+        const V_SEVERE_CELL1_UV = diag_uv::V_SEVERE_CELL1_UV.mask();
         /// Cell 2 Severe Under-Voltage flag
-        const V_SEVERE_CELL2_UV = 0x1000;
+        const V_SEVERE_CELL2_UV = diag_uv::V_SEVERE_CELL2_UV.mask();
         /// Cell 3 Severe Under-Voltage flag
-        const V_SEVERE_CELL3_UV = 0x2000;
+        const V_SEVERE_CELL3_UV = diag_uv::V_SEVERE_CELL3_UV.mask();
         /// Cell 4 Severe Under-Voltage flag
-        const V_SEVERE_CELL4_UV = 0x4000;
+        const V_SEVERE_CELL4_UV = diag_uv::V_SEVERE_CELL4_UV.mask();
         /// Cell 5 Severe Under-Voltage flag
-        const V_SEVERE_CELL5_UV = 0x8000;
+        const V_SEVERE_CELL5_UV = diag_uv::V_SEVERE_CELL5_UV.mask();
     }
 }
+
+impl_display_flags!(DiagUv);
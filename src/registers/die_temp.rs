@@ -1,15 +1,18 @@
 use core::ops::Deref;
 
+#[cfg(feature = "defmt")]
 use defmt::debug_assert;
 
+use super::regmap::die_temp::DIE_TEMP_MEAS;
+
 /// Die temp  Measurement Register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct DieTemp(u16);
 
 impl DieTemp {
     /// Get the measurement code of the DIE_TEMP_MEAS value
     pub const fn get_die_temp(&self) -> u16 {
-        self.0 & 0x0FFF
+        DIE_TEMP_MEAS.get(self.0)
     }
     //TODO: move conversion function to conversions module
     /// Get the temperature of the l9961 in Celsius
@@ -32,6 +35,12 @@ impl From<u16> for DieTemp {
     }
 }
 
+impl core::fmt::Display for DieTemp {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "DIE_TEMP : {} C,", self.get_die_temp_celsius())
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for DieTemp {
     fn format(&self, f: defmt::Formatter) {
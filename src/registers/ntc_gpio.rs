@@ -1,15 +1,18 @@
 use core::ops::Deref;
 
+#[cfg(feature = "defmt")]
 use defmt::debug_assert;
 
+use super::regmap::ntc_gpio::NTC_MEAS;
+
 /// NTC GPIO Measurement Register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct NtcGpio(u16);
 
 impl NtcGpio {
     /// Get the measurement code of the NTC_MEAS value
     pub const fn get_ntc_meas(&self) -> u16 {
-        self.0 & 0x0FFF
+        NTC_MEAS.get(self.0)
     }
 
     /// Get the converted NTC_MEAS value in mV
@@ -32,6 +35,12 @@ impl From<u16> for NtcGpio {
     }
 }
 
+impl core::fmt::Display for NtcGpio {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "NTC_MEAS : {} mV", self.get_ntc_meas_mv())
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for NtcGpio {
     fn format(&self, f: defmt::Formatter) {
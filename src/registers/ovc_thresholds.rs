@@ -1,28 +1,35 @@
 use core::ops::Deref;
 
+use super::regmap::ovc_thresholds::{OVC_CHG_TH, OVC_DCHG_TH};
+
 /// Overcurrent monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct OvCThresholds(u16);
 
 impl OvCThresholds {
+    /// Create a new register value from the discharge and charge threshold codes
+    pub const fn new(ovc_dchg_th: u8, ovc_chg_th: u8) -> Self {
+        Self(OVC_DCHG_TH.set(0, ovc_dchg_th as u16) | OVC_CHG_TH.set(0, ovc_chg_th as u16))
+    }
+
     /// Get the programmable discharge overcurrent fault threshold (8bit)
     pub const fn get_ovc_dchg_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        OVC_DCHG_TH.get(self.0) as u8
     }
 
     /// Set the programmable discharge overcurrent fault threshold (8bit)
     pub const fn set_ovc_dchg_th(&mut self, ovc_dchg_th: u8) {
-        self.0 = self.0 & 0xFF00 | (ovc_dchg_th as u16);
+        self.0 = OVC_DCHG_TH.set(self.0, ovc_dchg_th as u16);
     }
 
     /// Get the programmable charge overcurrent fault threshold (8bit)
     pub const fn get_ovc_chg_th(&self) -> u8 {
-        ((self.0 & 0xFF00) >> 8) as u8
+        OVC_CHG_TH.get(self.0) as u8
     }
 
     /// Set the programmable charge overcurrent fault threshold (8bit)
     pub const fn set_ovc_chg_th(&mut self, ovc_chg_th: u8) {
-        self.0 = self.0 & 0x00FF | ((ovc_chg_th as u16) << 8);
+        self.0 = OVC_CHG_TH.set(self.0, ovc_chg_th as u16);
     }
 }
 
@@ -35,11 +42,21 @@ impl Deref for OvCThresholds {
 
 impl From<u16> for OvCThresholds {
     fn from(id: u16) -> Self {
-        debug_assert!(id & 0xF000 == 0, "Invalid OvCThresholds value");
         OvCThresholds(id)
     }
 }
 
+impl core::fmt::Display for OvCThresholds {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "OVC_THRESHOLDS: {{\n  OVC_DCHG_TH: {},\n  OVC_CHG_TH: {}\n}}",
+            self.get_ovc_dchg_th(),
+            self.get_ovc_chg_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for OvCThresholds {
     fn format(&self, f: defmt::Formatter) {
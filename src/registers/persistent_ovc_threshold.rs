@@ -1,18 +1,25 @@
 use core::ops::Deref;
 
+use super::regmap::persistent_ovc_thresholds::PERSIST_OVC_TH;
+
 /// Overcurrent monitoring persistent covercurrent threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct PersistentOvCThreshold(u16);
 
 impl PersistentOvCThreshold {
+    /// Create a new register value from the persistent overcurrent threshold
+    pub const fn new(persistent_ovc_th: u8) -> Self {
+        Self(PERSIST_OVC_TH.set(0, persistent_ovc_th as u16))
+    }
+
     /// Get the programmable persistent overcurrent fault threshold (8bit)
     pub const fn get_persistent_ovc_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        PERSIST_OVC_TH.get(self.0) as u8
     }
 
     /// Set the programmable persistent overcurrent fault threshold (8bit)
     pub const fn set_persistent_ovc_th(&mut self, peristent_ovc_th: u8) {
-        self.0 = self.0 & 0xFF00 | (peristent_ovc_th as u16);
+        self.0 = PERSIST_OVC_TH.set(self.0, peristent_ovc_th as u16);
     }
 }
 
@@ -30,6 +37,16 @@ impl From<u16> for PersistentOvCThreshold {
     }
 }
 
+impl core::fmt::Display for PersistentOvCThreshold {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "PERSISTENT_OVC_THRESHOLD: {}",
+            self.get_persistent_ovc_th(),
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for PersistentOvCThreshold {
     fn format(&self, f: defmt::Formatter) {
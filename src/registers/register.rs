@@ -0,0 +1,227 @@
+use super::{Registers, regmap::REGISTERS};
+
+/// Access type of a register, as listed in the register map
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Access {
+    /// Read only (RO)
+    ReadOnly,
+    /// Read and write (RW)
+    ReadWrite,
+    /// Read, latched until the MCU writes 1 to clear (RLW)
+    ReadLatchedWrite,
+    /// Write only (WO)
+    WriteOnly,
+}
+
+/// Type level marker of a register's [`Access`]
+pub trait AccessType {
+    /// The access type this marker represents
+    const ACCESS: Access;
+}
+
+/// Marker for access types which can be read
+pub trait Readable: AccessType {}
+
+/// Marker for access types which can be written
+pub trait Writable: AccessType {}
+
+/// Read only (RO) register marker
+pub struct ReadOnly;
+
+/// Read and write (RW) register marker
+pub struct ReadWrite;
+
+/// Latched, write 1 to clear (RLW) register marker
+pub struct ReadLatchedWrite;
+
+/// Write only (WO) register marker
+pub struct WriteOnly;
+
+impl AccessType for ReadOnly {
+    const ACCESS: Access = Access::ReadOnly;
+}
+impl AccessType for ReadWrite {
+    const ACCESS: Access = Access::ReadWrite;
+}
+impl AccessType for ReadLatchedWrite {
+    const ACCESS: Access = Access::ReadLatchedWrite;
+}
+impl AccessType for WriteOnly {
+    const ACCESS: Access = Access::WriteOnly;
+}
+
+impl Readable for ReadOnly {}
+impl Readable for ReadWrite {}
+impl Readable for ReadLatchedWrite {}
+impl Writable for ReadWrite {}
+impl Writable for ReadLatchedWrite {}
+impl Writable for WriteOnly {}
+
+/// A typed L9961 register, enabling the generic [`crate::L9961::read`],
+/// [`crate::L9961::write`] and [`crate::L9961::modify`] accessors
+pub trait Register: Copy {
+    /// Address of the register
+    const ADDRESS: Registers;
+    /// Value of the register after power-on reset
+    const RESET_VALUE: u16;
+    /// Bits of the register which hold writable (RW, RLW or WO) fields
+    const WRITABLE_MASK: u16;
+    /// Access type of the register
+    type Access: AccessType;
+
+    /// Create the register from its raw value
+    fn from_raw(value: u16) -> Self;
+
+    /// The raw value of the register
+    fn raw(&self) -> u16;
+}
+
+/// A field of a register: a run of `width` bits starting at bit `offset`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Field {
+    /// Position of the least significant bit of the field
+    pub offset: u8,
+    /// Number of bits in the field
+    pub width: u8,
+    /// Access type of the field
+    pub access: Access,
+}
+
+impl Field {
+    /// Describe a field
+    pub const fn new(offset: u8, width: u8, access: Access) -> Self {
+        Self {
+            offset,
+            width,
+            access,
+        }
+    }
+
+    /// Bits of the register occupied by the field
+    pub const fn mask(&self) -> u16 {
+        (((1u32 << self.width) - 1) << self.offset) as u16
+    }
+
+    /// Extract the field from a raw register value
+    pub const fn get(&self, raw: u16) -> u16 {
+        (raw & self.mask()) >> self.offset
+    }
+
+    /// Replace the field in a raw register value.
+    /// Bits of `value` which do not fit in the field are discarded.
+    pub const fn set(&self, raw: u16, value: u16) -> u16 {
+        (raw & !self.mask()) | (((value as u32) << self.offset) as u16 & self.mask())
+    }
+}
+
+/// A named field in the register map
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct FieldInfo {
+    /// Name of the field in the register map
+    pub name: &'static str,
+    /// Layout of the field
+    pub field: Field,
+}
+
+/// Layout of a register in the register map
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RegisterInfo {
+    /// Name of the register in the register map
+    pub name: &'static str,
+    /// Address of the register
+    pub address: Registers,
+    /// Access type of the register
+    pub access: Access,
+    /// Value of the register after power-on reset
+    pub reset_value: u16,
+    /// Bits of the register which hold writable fields
+    pub writable_mask: u16,
+    /// Named fields of the register, most significant first
+    pub fields: &'static [FieldInfo],
+}
+
+impl RegisterInfo {
+    /// Layout of the register at the given address
+    pub const fn of(register: Registers) -> &'static RegisterInfo {
+        &REGISTERS[register as usize]
+    }
+
+    /// Find a field by its register map name
+    pub fn field(&self, name: &str) -> Option<Field> {
+        self.fields
+            .iter()
+            .find(|info| info.name == name)
+            .map(|info| info.field)
+    }
+
+    /// Split a raw value of this register into its named fields
+    pub const fn decode(&'static self, raw: u16) -> DecodedRegister {
+        DecodedRegister { info: self, raw }
+    }
+}
+
+/// A raw register value along with the layout to interpret it.
+/// `Debug` formats as the register name followed by the value of each field,
+/// `Display` and `defmt` additionally include the address and raw value.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct DecodedRegister {
+    /// Layout of the register
+    pub info: &'static RegisterInfo,
+    /// Raw value of the register
+    pub raw: u16,
+}
+
+impl DecodedRegister {
+    /// Iterate over the name and value of each field
+    pub fn fields(&self) -> impl Iterator<Item = (&'static str, u16)> + '_ {
+        self.info
+            .fields
+            .iter()
+            .map(|info| (info.name, info.field.get(self.raw)))
+    }
+}
+
+impl core::fmt::Debug for DecodedRegister {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut out = f.debug_struct(self.info.name);
+        for (name, value) in self.fields() {
+            out.field(name, &format_args!("{value:#X}"));
+        }
+        out.finish()
+    }
+}
+
+impl core::fmt::Display for DecodedRegister {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "{:#04X} {} = {:#06X} {{",
+            self.info.address as u8, self.info.name, self.raw
+        )?;
+        for (name, value) in self.fields() {
+            write!(f, " {name}: {value:#X}")?;
+        }
+        write!(f, " }}")
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for DecodedRegister {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=u8:#04X} {=str} = {=u16:#06X} {{",
+            self.info.address as u8,
+            self.info.name,
+            self.raw
+        );
+        for (name, value) in self.fields() {
+            defmt::write!(f, " {=str}: {=u16:#X}", name, value);
+        }
+        defmt::write!(f, " }}");
+    }
+}
@@ -0,0 +1,16 @@
+//! Register layouts generated from `l9961_regmap.csv` by the build script.
+//!
+//! Each register has a module holding its address, reset value, access type and the [`Field`] layout of each
+//! of its named fields. Registers without a hand-written type get a generated one with getters and setters
+//! for each field.
+
+use super::{
+    Access, CCAccLsbCntr, Cfg1FiltersCycles, Cfg2Enables, Cfg3Act, ChipID, CsaGainFactor, CurrMsk,
+    DevAddr, DiagCurr, DiagOvOtUt, DiagUv, DieTemp, Field, FieldInfo, NtcGpio, OvCThresholds,
+    PersistentOvCThreshold, Register, RegisterInfo, Registers, SCThreshold, ToFaultnMsk,
+    ToFuseRstMask, ToPrdrvBalMask, VB, VBOvTh, VBSumMaxDiffTh, VBUvTh, VCell1Faults,
+    VCellBalUvDeltaTh, VCellOvTh, VCellSevereDeltaThrs, VCellSum, VCellUvTh, VNTCOTTh,
+    VNTCSevereOTTh, VNTCUTTh,
+};
+
+include!(concat!(env!("OUT_DIR"), "/regmap.rs"));
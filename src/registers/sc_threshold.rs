@@ -1,31 +1,47 @@
 use core::ops::Deref;
-use defmt::{write, Format, Formatter};
+
+use super::regmap::sc_threshold::{SC_PERSIST_TH, SC_TH, WRITABLE_MASK};
 
 /// Programmable short circuit protection threshold register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct SCThreshold(u16);
 
 impl SCThreshold {
+    /// Create a new register value from the short circuit and persistent short circuit thresholds (4 bit each)
+    pub const fn new(sc_th: u8, sc_persist_th: u8) -> Self {
+        let mut threshold = Self(0);
+        threshold.set_sc_th(sc_th);
+        threshold.set_sc_persist_th(sc_persist_th);
+        threshold
+    }
+
     /// Get the programmable short circuit threshold (4 bit)
     pub const fn get_sc_th(&self) -> u8 {
-        (self.0 & 0x000F) as u8
+        SC_TH.get(self.0) as u8
     }
 
     /// Set the the programmable short circuit threshold (4 bit)
     pub const fn set_sc_th(&mut self, sc_th: u8) {
-        debug_assert!(sc_th & 0x000F == sc_th, "Invalid SC_TH value");
-        self.0 = self.0 & 0xFFF0 | (sc_th as u16);
+        debug_assert!(
+            SC_TH.get(sc_th as u16) == sc_th as u16,
+            "Invalid SC_TH value"
+        );
+        self.0 = SC_TH.set(self.0, sc_th as u16);
     }
 
     /// Get the programmable persistent short circuit threshold (4 bit)
     pub const fn get_sc_persist_th(&self) -> u8 {
-        (self.0 & 0x00F0 >> 4) as u8
+        SC_PERSIST_TH.get(self.0) as u8
     }
 
     /// Set the the programmable persistent short circuit threshold (4 bit)
-    pub const fn set_sc_persist_th(&mut self, sc_th: u8) {
-        debug_assert!(sc_th & 0x000F == sc_th, "Invalid SC_PERSIST_TH value");
-        self.0 = self.0 & 0xFF0F | ((sc_th as u16) << 4);
+    pub const fn set_sc_persist_th(&mut self, sc_persist_th: u8) {
+        // The field is not at bit 0, so the value is checked after placing it in the field
+        debug_assert!(
+            SC_PERSIST_TH.get(SC_PERSIST_TH.set(0, sc_persist_th as u16)) == sc_persist_th as u16,
+            "Invalid SC_PERSIST_TH value"
+        );
+        self.0 = SC_PERSIST_TH.set(self.0, sc_persist_th as u16);
     }
 }
 
@@ -38,14 +54,13 @@ impl Deref for SCThreshold {
 
 impl From<u16> for SCThreshold {
     fn from(sc_th: u16) -> Self {
-        debug_assert!(sc_th & 0x000F == sc_th, "Invalid SC_TH value");
+        debug_assert!(sc_th & WRITABLE_MASK == sc_th, "Invalid SC_THRESHOLD value");
         Self(sc_th)
     }
 }
 
-#[cfg(feature = "defmt")]
-impl Format for SCThreshold {
-    fn format(&self, f: Formatter) {
+impl core::fmt::Display for SCThreshold {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "SC_THRESHOLD: {{\n  SC_TH: {},\n  SC_PERSIST_TH: {}\n}}",
@@ -54,3 +69,15 @@ impl Format for SCThreshold {
         )
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for SCThreshold {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "SC_THRESHOLD: {{\n  SC_TH: {},\n  SC_PERSIST_TH: {}\n}}",
+            self.get_sc_th(),
+            self.get_sc_persist_th(),
+        )
+    }
+}
@@ -0,0 +1,188 @@
+use core::fmt;
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, i2c::I2c};
+
+use crate::L9961;
+
+use super::{
+    Access, DecodedRegister, FieldInfo, Readable, Register, RegisterInfo, Registers, regmap,
+    regmap::REGISTERS,
+};
+
+/// Number of registers in the register map
+pub const REGISTER_COUNT: usize = REGISTERS.len();
+
+/// Registers captured by each burst read of a snapshot, as (first register, count).
+/// The write only NVM_2 register splits the map into two bursts.
+const BURSTS: [(Registers, usize); 2] = [
+    (Registers::ChipID, regmap::nvm_2::ADDRESS as usize),
+    (
+        Registers::VCell1,
+        REGISTER_COUNT - regmap::vcell1::ADDRESS as usize,
+    ),
+];
+
+/// The raw value of every readable register of the L9961, captured in burst reads.
+/// Write only registers read as 0.
+#[derive(Clone, Copy, Eq, PartialEq)]
+pub struct RegisterSnapshot {
+    raw: [u16; REGISTER_COUNT],
+}
+
+impl RegisterSnapshot {
+    /// Create a snapshot from raw register values, indexed by address
+    pub const fn from_raw(raw: [u16; REGISTER_COUNT]) -> Self {
+        Self { raw }
+    }
+
+    /// Create a snapshot holding the power-on reset value of every register
+    pub const fn reset_values() -> Self {
+        let mut raw = [0; REGISTER_COUNT];
+        let mut i = 0;
+        while i < REGISTER_COUNT {
+            raw[i] = REGISTERS[i].reset_value;
+            i += 1;
+        }
+        Self { raw }
+    }
+
+    /// The raw values of every register, indexed by address
+    pub const fn raw_values(&self) -> &[u16; REGISTER_COUNT] {
+        &self.raw
+    }
+
+    /// The raw value of a register, or `None` if it is write only
+    pub const fn raw(&self, register: Registers) -> Option<u16> {
+        match RegisterInfo::of(register).access {
+            Access::WriteOnly => None,
+            _ => Some(self.raw[register as usize]),
+        }
+    }
+
+    /// The typed value of a register
+    pub fn get<R>(&self) -> R
+    where
+        R: Register,
+        R::Access: Readable,
+    {
+        R::from_raw(self.raw[R::ADDRESS as usize])
+    }
+
+    /// Iterate over every readable register, decoded into its fields
+    pub fn iter(&self) -> impl Iterator<Item = DecodedRegister> + '_ {
+        REGISTERS
+            .iter()
+            .zip(self.raw.iter())
+            .filter(|(info, _)| info.access != Access::WriteOnly)
+            .map(|(info, raw)| info.decode(*raw))
+    }
+
+    /// Compare every field of every readable register against an earlier snapshot,
+    /// yielding the fields whose value differs
+    pub fn diff<'a>(&'a self, before: &'a Self) -> impl Iterator<Item = FieldChange> + 'a {
+        self.iter()
+            .zip(before.iter())
+            .filter(|(after, before)| after.raw != before.raw)
+            .flat_map(|(after, before)| {
+                after.info.fields.iter().filter_map(move |field| {
+                    let change = FieldChange {
+                        register: after.info,
+                        field,
+                        before: field.field.get(before.raw),
+                        after: field.field.get(after.raw),
+                    };
+                    (change.before != change.after).then_some(change)
+                })
+            })
+    }
+}
+
+impl fmt::Display for RegisterSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for register in self.iter() {
+            writeln!(f, "{register}")?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for RegisterSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for RegisterSnapshot {
+    fn format(&self, f: defmt::Formatter) {
+        for register in self.iter() {
+            defmt::write!(f, "{}\n", register);
+        }
+    }
+}
+
+/// A field whose value differs between two register snapshots
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FieldChange {
+    /// The register holding the field
+    pub register: &'static RegisterInfo,
+    /// The field which changed
+    pub field: &'static FieldInfo,
+    /// Value of the field in the earlier snapshot
+    pub before: u16,
+    /// Value of the field in the later snapshot
+    pub after: u16,
+}
+
+impl fmt::Display for FieldChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}.{}: {:#X} -> {:#X}",
+            self.register.name, self.field.name, self.before, self.after
+        )
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for FieldChange {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "{=str}.{=str}: {=u16:#X} -> {=u16:#X}",
+            self.register.name,
+            self.field.name,
+            self.before,
+            self.after
+        )
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Capture every readable register in burst reads.
+    /// Latched diagnostic registers are only read, not cleared.
+    pub async fn read_register_snapshot(&mut self) -> Result<RegisterSnapshot, I2C::Error> {
+        let mut raw = [0; REGISTER_COUNT];
+        let mut bytes = [0u8; 2 * REGISTER_COUNT];
+        for (first, count) in BURSTS {
+            let bytes = &mut bytes[..2 * count];
+            self.i2c
+                .write_read(self.config.address, &[first as u8], bytes)
+                .await?;
+            for (value, chunk) in raw[first as usize..first as usize + count]
+                .iter_mut()
+                .zip(bytes.chunks_exact(2))
+            {
+                *value = u16::from_be_bytes([chunk[0], chunk[1]]);
+            }
+        }
+        Ok(RegisterSnapshot { raw })
+    }
+}
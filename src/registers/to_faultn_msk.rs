@@ -3,35 +3,39 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::to_faultn_msk;
+
 bitflags! {
     /// Programmable mask register for whether faults assert the on the FAULTN output
     pub struct ToFaultnMsk:u16 {
         /// Mask for the CELL_UF_FAULTN_MSK bit
-        const CELL_UF_FAULTN_MSK = 0x0001;
+        const CELL_UF_FAULTN_MSK = to_faultn_msk::CELL_UV_FAULTN_MSK.mask();
         /// Mask for the CELL_SEVERE_UV_FAULTN_MSK bit
-        const CELL_SEVERE_UV_FAULTN_MSK = 0x0002;
+        const CELL_SEVERE_UV_FAULTN_MSK = to_faultn_msk::CELL_SEVERE_UV_FAULTN_MSK.mask();
         /// Mask for the CELL_OV_FAULTN_MSK bit
-        const CELL_OV_FAULTN_MSK = 0x0004;
+        const CELL_OV_FAULTN_MSK = to_faultn_msk::CELL_OV_FAULTN_MSK.mask();
         /// Mask for the CELL_SEVERE_OV_FAULTN_MSK bit
-        const CELL_SEVERE_OV_FAULTN_MSK = 0x0008;
+        const CELL_SEVERE_OV_FAULTN_MSK = to_faultn_msk::CELL_SEVERE_OV_FAULTN_MSK.mask();
         /// Mask for the BAL_UV_FAULTN_MSK bit
-        const BAL_UV_FAULTN_MSK = 0x0010;
+        const BAL_UV_FAULTN_MSK = to_faultn_msk::BAL_UV_FAULTN_MSK.mask();
         /// Mask for the VB_UV_FAULTN_MSK bit
-        const VB_UV_FAULTN_MSK = 0x0020;
+        const VB_UV_FAULTN_MSK = to_faultn_msk::VB_UV_FAULTN_MSK.mask();
         /// Mask for the VB_OV_FAULTN_MSK bit
-        const VB_OV_FAULTN_MSK = 0x0040;
+        const VB_OV_FAULTN_MSK = to_faultn_msk::VB_OV_FAULTN_MSK.mask();
         /// Mask for the VB_SUM_CHECK_FAULTN_MSK bit
-        const VB_SUM_CHECK_FAULTN_MSK = 0x0080;
+        const VB_SUM_CHECK_FAULTN_MSK = to_faultn_msk::VB_SUM_CHECK_FAULTN_MSK.mask();
         /// Mask for the NTC_OT_FAULTN_MSK bit
-        const NTC_OT_FAULTN_MSK = 0x0100;
+        const NTC_OT_FAULTN_MSK = to_faultn_msk::NTC_OT_FAULTN_MSK.mask();
         /// Mask for the NTC_SEVERE_OT_FAULTN_MSK bit
-        const NTC_SEVERE_OT_FAULTN_MSK = 0x0200;
+        const NTC_SEVERE_OT_FAULTN_MSK = to_faultn_msk::NTC_SEVERE_OT_FAULTN_MSK.mask();
         /// Mask for the NTC_UT_FAULTN_MSK bit
-        const NTC_UT_FAULTN_MSK = 0x0400;
+        const NTC_UT_FAULTN_MSK = to_faultn_msk::NTC_UT_FAULTN_MSK.mask();
         /// Mask for the DIE_OT_FAULTN_MSK bit
-        const DIE_OT_FAULTN_MSK = 0x0800;
+        const DIE_OT_FAULTN_MSK = to_faultn_msk::DIE_OT_FAULTN_MSK.mask();
         // Ensure that the reserved bits are always 0
         // TODO: This requires Bitflags 2.x
         // const _ = 0x0FFF;
     }
 }
+
+impl_display_flags!(ToFaultnMsk);
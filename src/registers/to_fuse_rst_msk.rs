@@ -3,25 +3,29 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::to_fuse_rst_msk;
+
 bitflags! {
     /// Programmable mask register for whether faults assert the on the PRDRV and balance FET outputs
     pub struct ToFuseRstMask:u16 {
         /// Mask for the CELL_SEVERE_UV_FUSE bit
-        const CELL_SEVERE_UV_FUSE_MSK = 0x0001;
+        const CELL_SEVERE_UV_FUSE_MSK = to_fuse_rst_msk::CELL_SEVERE_UV_FUSE_MSK.mask();
         /// Mask for the CELL_SEVERE_OV_FUSE bit
-        const CELL_SEVERE_OV_FUSE_MSK = 0x0002;
+        const CELL_SEVERE_OV_FUSE_MSK = to_fuse_rst_msk::CELL_SEVERE_OV_FUSE_MSK.mask();
         /// Mask for the VB_SUM_CHECK_FUSE bit
-        const VB_SUM_CHECK_FUSE_MSK = 0x0004;
+        const VB_SUM_CHECK_FUSE_MSK = to_fuse_rst_msk::VB_SUM_CHECK_FUSE_MSK.mask();
         /// Mask for the NTC_SEVERE_OT_FUSE bit
-        const NTC_SEVERE_OT_FUSE_MSK = 0x0008;
+        const NTC_SEVERE_OT_FUSE_MSK = to_fuse_rst_msk::NTC_SEVERE_OT_FUSE_MSK.mask();
         /// Mask for the CELL_OV_RST bit
-        const CELL_OV_RST_MSK = 0x0010;
+        const CELL_OV_RST_MSK = to_fuse_rst_msk::CELL_OV_RST_MSK.mask();
         /// Mask for the CELL_SEVERE_OV_RST bit
-        const CELL_SEVERE_OV_RST_MSK = 0x0020;
+        const CELL_SEVERE_OV_RST_MSK = to_fuse_rst_msk::CELL_SEVERE_OV_RST_MSK.mask();
         /// Mask for the VB_OV_RST bit
-        const VB_OV_RST_MSK = 0x0040;
+        const VB_OV_RST_MSK = to_fuse_rst_msk::VB_OV_RST_MSK.mask();
         // Ensure that the reserved bits are always 0
         // TODO: This requires Bitflags 2.x
         // const _ = 0x007F;
     }
 }
+
+impl_display_flags!(ToFuseRstMask);
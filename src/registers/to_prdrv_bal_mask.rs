@@ -3,41 +3,45 @@ use bitflags::bitflags;
 #[cfg(feature = "defmt")]
 use defmt::bitflags;
 
+use super::regmap::to_prdrv_bal_msk;
+
 bitflags! {
     /// Programmable mask register for whether faults assert the on the PRDRV and balance FET outputs
     pub struct ToPrdrvBalMask:u16 {
         /// Mask for the CELL_UV_PRDRV bit
-        const CELL_UV_PRDRV_MSK = 0x0001;
+        const CELL_UV_PRDRV_MSK = to_prdrv_bal_msk::CELL_UV_PRDRV_MSK.mask();
         /// Mask for the CELL_SEVERE_UV_PRDRV bit
-        const CELL_SEVERE_UV_PRDRV_MSK = 0x0002;
+        const CELL_SEVERE_UV_PRDRV_MSK = to_prdrv_bal_msk::CELL_SEVERE_UV_PRDRV_MSK.mask();
         /// Mask for the CELL_OV_PRDRV bit
-        const CELL_OV_PRDRV_MSK = 0x0004;
+        const CELL_OV_PRDRV_MSK = to_prdrv_bal_msk::CELL_OV_PRDRV_MSK.mask();
         /// Mask for the CELL_SEVERE_OV_PRDRV bit
-        const CELL_SEVERE_OV_PRDRV_MSK = 0x0008;
+        const CELL_SEVERE_OV_PRDRV_MSK = to_prdrv_bal_msk::CELL_SEVERE_OV_PRDRV_MSK.mask();
         /// Mask for the VB_UV_PRDRV bit
-        const VB_UV_PRDRV_MSK = 0x0010;
+        const VB_UV_PRDRV_MSK = to_prdrv_bal_msk::VB_UV_PRDRV_MSK.mask();
         /// Mask for the VB_OV_PRDRV bit
-        const VB_OV_PRDRV_MSK = 0x0020;
+        const VB_OV_PRDRV_MSK = to_prdrv_bal_msk::VB_OV_PRDRV_MSK.mask();
         /// Mask for the VB_SUM_CHECK_PRDRV bit
-        const VB_SUM_CHECK_PRDRV_MSK = 0x0040;
+        const VB_SUM_CHECK_PRDRV_MSK = to_prdrv_bal_msk::VB_SUM_CHECK_PRDRV_MSK.mask();
         /// Mask for the NTC_OT_PRDRV bit
-        const NTC_OT_PRDRV_MSK = 0x0080;
+        const NTC_OT_PRDRV_MSK = to_prdrv_bal_msk::NTC_OT_PRDRV_MSK.mask();
         /// Mask for the NTC_SEVERE_OT_PRDRV bit
-        const NTC_SEVERE_OT_PRDRV_MSK = 0x0100;
+        const NTC_SEVERE_OT_PRDRV_MSK = to_prdrv_bal_msk::NTC_SEVERE_OT_PRDRV_MSK.mask();
         /// Mask for the NTC_UT_PRDRV bit
-        const NTC_UT_PRDRV_MSK = 0x0200;
+        const NTC_UT_PRDRV_MSK = to_prdrv_bal_msk::NTC_UT_PRDRV_MSK.mask();
         /// Mask for the DIE_OT_PRDRV bit
-        const DIE_OT_PRDRV_MSK = 0x0400;
+        const DIE_OT_PRDRV_MSK = to_prdrv_bal_msk::DIE_OT_PRDRV_MSK.mask();
         /// Mask for the BAL_UV_BAL bit
-        const BAL_UV_BAL_MSK = 0x0800;
+        const BAL_UV_BAL_MSK = to_prdrv_bal_msk::BAL_UV_BAL_MSK.mask();
         /// Mask for the NTC_SEVERE_OT_BAL bit
-        const NTC_SEVERE_OT_BAL_MSK = 0x1000;
+        const NTC_SEVERE_OT_BAL_MSK = to_prdrv_bal_msk::NTC_SEVERE_OT_BAL_MSK.mask();
         /// Mask for the DIE_OT_BAL bit
-        const DIE_OT_BAL_MSK = 0x2000;
+        const DIE_OT_BAL_MSK = to_prdrv_bal_msk::DIE_OT_BAL_MSK.mask();
         /// Mask for the VB_SUM_CHECK_BAL bit
-        const VB_SUM_CHECK_BAL_MSK = 0x4000;
+        const VB_SUM_CHECK_BAL_MSK = to_prdrv_bal_msk::VB_SUM_CHECK_BAL_MSK.mask();
         // Ensure that the reserved bit is always 0
         // TODO: This requires Bitflags 2.x
         // const _ = 0x7FFF;
     }
 }
+
+impl_display_flags!(ToPrdrvBalMask);
@@ -1,17 +1,20 @@
 use core::ops::Deref;
 
+#[cfg(feature = "defmt")]
 use defmt::debug_assert;
 
 use crate::conversions::pack_voltage_measurement_mv_from_code;
 
+use super::regmap::vb::VB_MEAS;
+
 /// Battery Pack Voltage Measurement Register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VB(u16);
 
 impl VB {
     /// Get the measurement code of the battery pack
     pub const fn get_vb_meas_code(&self) -> u16 {
-        self.0 & 0x7FFF
+        VB_MEAS.get(self.0)
     }
 }
 
@@ -29,6 +32,16 @@ impl From<u16> for VB {
     }
 }
 
+impl core::fmt::Display for VB {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VB : {}mv",
+            pack_voltage_measurement_mv_from_code(self.get_vb_meas_code())
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VB {
     fn format(&self, f: defmt::Formatter) {
@@ -1,33 +1,35 @@
 use core::ops::Deref;
 
+use super::regmap::vb_ov_th::{NVB_OV_CNT_TH, VB_OV_TH};
+
 /// Battery pack monitoring over-voltage threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VBOvTh(u16);
 
 impl VBOvTh {
     /// Create a new VBOvTh register value
     pub const fn new(vb_ov_th: u8, nvb_ov_cnt_th: u8) -> Self {
-        VBOvTh((vb_ov_th as u16) | ((nvb_ov_cnt_th as u16) << 8))
+        VBOvTh(NVB_OV_CNT_TH.set(VB_OV_TH.set(0, vb_ov_th as u16), nvb_ov_cnt_th as u16))
     }
 
     /// Get the programmable VB OV fault threshold (8 bit)
     pub const fn get_vb_ov_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VB_OV_TH.get(self.0) as u8
     }
 
     /// Set the the Get the programmable VB OV fault threshold (8 bit)
     pub const fn set_vb_ov_th(&mut self, vcell_ov_th: u8) {
-        self.0 = self.0 & 0xFF00 | (vcell_ov_th as u16);
+        self.0 = VB_OV_TH.set(self.0, vcell_ov_th as u16);
     }
 
     /// Get the programmable VB OV event counter threshold (4 bit)
     pub const fn get_nvb_ov_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 8) as u8
+        NVB_OV_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable VB OV event counter threshold (4 bit)
     pub const fn set_ncvb_ov_cnt_th(&mut self, ncell_ov_cnt_th: u8) {
-        self.0 = self.0 & 0xF0FF | ((ncell_ov_cnt_th as u16) << 8);
+        self.0 = NVB_OV_CNT_TH.set(self.0, ncell_ov_cnt_th as u16);
     }
 }
 
@@ -45,6 +47,17 @@ impl From<u16> for VBOvTh {
     }
 }
 
+impl core::fmt::Display for VBOvTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VB_OV_TH: {{\n  VB_OV_TH: {},\n  NVB_OV_CNT_TH: {}\n}}",
+            self.get_vb_ov_th(),
+            self.get_nvb_ov_cnt_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VBOvTh {
     fn format(&self, f: defmt::Formatter) {
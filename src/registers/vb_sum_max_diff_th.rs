@@ -1,19 +1,21 @@
 use core::ops::Deref;
 
+use super::regmap::vb_sum_max_diff_th::VB_SUM_MAX_DIFF_TH;
+
 /// Programmable plausibility check threshold between VB and sum of cells register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VBSumMaxDiffTh(u16);
 
 impl VBSumMaxDiffTh {
     /// Get the programmable plausibility check threshold between VB and sum of cells in volts (8 bit)
     pub const fn get_vb_sum_max_diff_th_volts(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VB_SUM_MAX_DIFF_TH.get(self.0) as u8
     }
 
     /// Set the the Get the programmable VB OV fault threshold (8 bit)
     pub const fn set_vb_sum_max_diff_th_volts(&mut self, vb_sum_max_diff: u8) {
         debug_assert!(vb_sum_max_diff < 25, "Invalid VB_SUM_MAX_DIFF_TH value");
-        self.0 = self.0 & 0xFF00 | (vb_sum_max_diff as u16);
+        self.0 = VB_SUM_MAX_DIFF_TH.set(self.0, vb_sum_max_diff as u16);
     }
 }
 
@@ -31,6 +33,16 @@ impl From<u16> for VBSumMaxDiffTh {
     }
 }
 
+impl core::fmt::Display for VBSumMaxDiffTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VB_SUM_MAX_DIFF_TH: {}",
+            self.get_vb_sum_max_diff_th_volts(),
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VBSumMaxDiffTh {
     fn format(&self, f: defmt::Formatter) {
@@ -1,33 +1,35 @@
 use core::ops::Deref;
 
+use super::regmap::vb_uv_th::{NVB_UV_CNT_TH, VB_UV_TH};
+
 /// Battery pack monitoring under-voltage threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VBUvTh(u16);
 
 impl VBUvTh {
     /// Create a new VBUvTh register value
     pub const fn new(vb_uv_th: u8, nvb_uv_cnt_th: u8) -> Self {
-        VBUvTh((vb_uv_th as u16) | ((nvb_uv_cnt_th as u16) << 8))
+        VBUvTh(NVB_UV_CNT_TH.set(VB_UV_TH.set(0, vb_uv_th as u16), nvb_uv_cnt_th as u16))
     }
 
     /// Get the programmable VB UV fault threshold (8 bit)
     pub const fn get_vb_uv_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VB_UV_TH.get(self.0) as u8
     }
 
     /// Set the the Get the programmable VB UV fault threshold (8 bit)
     pub const fn set_vb_uv_th(&mut self, vcell_uv_th: u8) {
-        self.0 = self.0 & 0xFF00 | (vcell_uv_th as u16);
+        self.0 = VB_UV_TH.set(self.0, vcell_uv_th as u16);
     }
 
     /// Get the programmable VB UV event counter threshold (4 bit)
     pub const fn get_nvb_uv_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 8) as u8
+        NVB_UV_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable VB UV event counter threshold (4 bit)
     pub const fn set_ncvb_uv_cnt_th(&mut self, ncell_uv_cnt_th: u8) {
-        self.0 = self.0 & 0xF0FF | ((ncell_uv_cnt_th as u16) << 8);
+        self.0 = NVB_UV_CNT_TH.set(self.0, ncell_uv_cnt_th as u16);
     }
 }
 
@@ -45,6 +47,17 @@ impl From<u16> for VBUvTh {
     }
 }
 
+impl core::fmt::Display for VBUvTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VB_UV_TH: {{\n  VB_UV_TH: {},\n  NVB_UV_CNT_TH: {}\n}}",
+            self.get_vb_uv_th(),
+            self.get_nvb_uv_cnt_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VBUvTh {
     fn format(&self, f: defmt::Formatter) {
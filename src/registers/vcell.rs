@@ -32,6 +32,18 @@ impl Deref for VCell {
     }
 }
 
+impl core::fmt::Display for VCell {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let cell = self.get_cell();
+        write!(
+            f,
+            "VCELL{}: {}mv",
+            cell,
+            cell_voltage_measurement_mv_from_code(self.get_vcell_meas_code()),
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VCell {
     fn format(&self, f: defmt::Formatter) {
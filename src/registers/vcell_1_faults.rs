@@ -1,22 +1,24 @@
 use core::ops::Deref;
 
+use super::regmap::vcell1::{CRC_CFG_FAIL, CRC_TRIM_CAL_FAIL, GO2SHIP};
+
 /// VCell1 Measurement Register packed faults
 /// VCell1 also contains the crc_cfg_fail and crc_trim_cal_fail flags
 /// This ignores the measurement and command bits, and simply returns the faults
 /// Measurement should be disabled when working with the NVM,
 /// so reads to this register should be for one purpose only
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCell1Faults(u16);
 
 impl VCell1Faults {
     /// Get the Programmable cell overvoltage event counter threshold (4 bit)
     pub const fn get_crc_trim_cal_fail(&self) -> bool {
-        self.0 & 0x4000 != 0
+        CRC_TRIM_CAL_FAIL.get(self.0) != 0
     }
 
     /// Get the Programmable cell overvoltage event counter threshold (4 bit)
     pub const fn get_crc_cfg_fail(&self) -> bool {
-        self.0 & 0x8000 != 0
+        CRC_CFG_FAIL.get(self.0) != 0
     }
 }
 
@@ -30,11 +32,22 @@ impl Deref for VCell1Faults {
 impl From<u16> for VCell1Faults {
     fn from(val: u16) -> Self {
         // Make sure the GO2SHIP bits are not set on accident
-        debug_assert!(val & 0x3000 == 0);
+        debug_assert!(GO2SHIP.get(val) == 0);
         VCell1Faults(val)
     }
 }
 
+impl core::fmt::Display for VCell1Faults {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VCELL1_FAULTS: {{\n  CRC_TRIM_CAL_FAIL: {},\n  CRC_CFG_FAIL: {}\n}}",
+            self.get_crc_trim_cal_fail(),
+            self.get_crc_cfg_fail()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VCell1Faults {
     fn format(&self, f: defmt::Formatter) {
@@ -1,34 +1,39 @@
 use core::ops::Deref;
 
+use super::regmap::vcell_bal_uv_delta_th::{NCELL_BAL_UV_CNT_TH, VCELL_BAL_UV_DELTA_TH};
+
 /// Cell under-voltage during balancing monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCellBalUvDeltaTh(u16);
 
 impl VCellBalUvDeltaTh {
     /// Create a new VCellBalUvDeltaTh register value
     pub const fn new(vcell_bal_uv_delta_th: u8, ncell_uv_cnt_th: u8) -> Self {
         debug_assert!(ncell_uv_cnt_th < 16, "Invalid VCellBalUvDeltaTh value");
-        VCellBalUvDeltaTh((vcell_bal_uv_delta_th as u16) | ((ncell_uv_cnt_th as u16) << 8))
+        VCellBalUvDeltaTh(NCELL_BAL_UV_CNT_TH.set(
+            VCELL_BAL_UV_DELTA_TH.set(0, vcell_bal_uv_delta_th as u16),
+            ncell_uv_cnt_th as u16,
+        ))
     }
 
     /// Get the programmable balancing UV threshold (positive delta in respect to cell UV threshold, 8 bit)
     pub const fn get_vcell_bal_uv_delta_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VCELL_BAL_UV_DELTA_TH.get(self.0) as u8
     }
 
     /// Set the the programmable cell balancing under-voltage fault threshold (8bit)
     pub const fn set_vcell_bal_uv_th(&mut self, vcell_bal_uv_th: u8) {
-        self.0 = self.0 & 0xFF00 | (vcell_bal_uv_th as u16);
+        self.0 = VCELL_BAL_UV_DELTA_TH.set(self.0, vcell_bal_uv_th as u16);
     }
 
     /// Get the programmable cell under-voltage event counter threshold (4 bit)
     pub const fn get_ncell_uv_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 8) as u8
+        NCELL_BAL_UV_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable cell under-voltage event counter threshold (4 bit)
     pub const fn set_ncell_uv_cnt_th(&mut self, ncell_uv_cnt_th: u8) {
-        self.0 = self.0 & 0xF0FF | ((ncell_uv_cnt_th as u16) << 8);
+        self.0 = NCELL_BAL_UV_CNT_TH.set(self.0, ncell_uv_cnt_th as u16);
     }
 }
 
@@ -46,6 +51,17 @@ impl From<u16> for VCellBalUvDeltaTh {
     }
 }
 
+impl core::fmt::Display for VCellBalUvDeltaTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VCELL_BAL_UV_DELTA_TH: {{\n  VCELL_BAL_UV_DELTA_TH: {},\n  NCELL_BAL_UV_CNT_TH: {}\n}}",
+            self.get_vcell_bal_uv_delta_th(),
+            self.get_ncell_uv_cnt_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VCellBalUvDeltaTh {
     fn format(&self, f: defmt::Formatter) {
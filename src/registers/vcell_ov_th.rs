@@ -1,35 +1,39 @@
 use core::ops::Deref;
-use defmt::{write, Format, Formatter};
+
+use super::regmap::vcell_ov_th::{NCELL_OV_CNT_TH, VCELL_OV_TH};
 
 /// Cell over-voltage monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCellOvTh(u16);
 
 impl VCellOvTh {
     /// Create a new VCellOvTh register value
     pub const fn new(vcell_ov_th: u8, ncell_ov_cnt_th: u8) -> Self {
         debug_assert!(ncell_ov_cnt_th < 16, "Invalid ncell_ov_cnt_th value");
-        VCellOvTh((vcell_ov_th as u16) | ((ncell_ov_cnt_th as u16) << 8))
+        VCellOvTh(NCELL_OV_CNT_TH.set(
+            VCELL_OV_TH.set(0, vcell_ov_th as u16),
+            ncell_ov_cnt_th as u16,
+        ))
     }
 
     /// Get the Programmable cell over-voltage fault threshold (8bit)
     pub const fn get_vcell_ov_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VCELL_OV_TH.get(self.0) as u8
     }
 
     /// Set the Programmable cell overvoltage fault threshold (8bit)
     pub const fn set_vcell_ov_th(&mut self, vcell_ov_th: u8) {
-        self.0 = self.0 & 0xFF00 | (vcell_ov_th as u16);
+        self.0 = VCELL_OV_TH.set(self.0, vcell_ov_th as u16);
     }
 
     /// Get the Programmable cell overvoltage event counter threshold (4 bit)
     pub const fn get_ncell_ov_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 8) as u8
+        NCELL_OV_CNT_TH.get(self.0) as u8
     }
 
     /// Set the Programmable cell over-voltage event counter threshold (4 bit)
     pub const fn set_ncell_ov_cnt_th(&mut self, ncell_ov_cnt_th: u8) {
-        self.0 = self.0 & 0xF0FF | ((ncell_ov_cnt_th as u16) << 8);
+        self.0 = NCELL_OV_CNT_TH.set(self.0, ncell_ov_cnt_th as u16);
     }
 }
 
@@ -47,9 +51,8 @@ impl From<u16> for VCellOvTh {
     }
 }
 
-#[cfg(feature = "defmt")]
-impl Format for VCellOvTh {
-    fn format(&self, f: Formatter) {
+impl core::fmt::Display for VCellOvTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "VCELL_OV_TH: {{\n  CELL_OV_TH: {},\n  NCELL_OV_CNT_TH: {}\n}}",
@@ -58,3 +61,15 @@ impl Format for VCellOvTh {
         )
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for VCellOvTh {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "VCELL_OV_TH: {{\n  CELL_OV_TH: {},\n  NCELL_OV_CNT_TH: {}\n}}",
+            self.get_vcell_ov_th(),
+            self.get_ncell_ov_cnt_th()
+        )
+    }
+}
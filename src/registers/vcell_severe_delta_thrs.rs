@@ -1,7 +1,9 @@
 use core::ops::Deref;
 
+use super::regmap::vcell_severe_delta_thrs::{VCELL_SEVERE_OV_DELTA_TH, VCELL_SEVERE_UV_DELTA_TH};
+
 /// Cell voltage severe undervoltage and overvoltage monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCellSevereDeltaThrs(u16);
 
 impl VCellSevereDeltaThrs {
@@ -10,30 +12,30 @@ impl VCellSevereDeltaThrs {
         cell_severe_over_voltage_delta_threshold: u8,
         cell_severe_under_voltage_delta_threshold: u8,
     ) -> Self {
-        VCellSevereDeltaThrs(
-            (cell_severe_under_voltage_delta_threshold as u16) << 8
-                | cell_severe_over_voltage_delta_threshold as u16,
-        )
+        VCellSevereDeltaThrs(VCELL_SEVERE_UV_DELTA_TH.set(
+            VCELL_SEVERE_OV_DELTA_TH.set(0, cell_severe_over_voltage_delta_threshold as u16),
+            cell_severe_under_voltage_delta_threshold as u16,
+        ))
     }
 
     /// Get the programmable cell severe OV threshold (positive delta in respect to cell OV threshold, 8 bit)
     pub const fn get_vcell_severe_ov_delta_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VCELL_SEVERE_OV_DELTA_TH.get(self.0) as u8
     }
 
     /// Set the programmable cell severe OV threshold (positive delta in respect to cell OV threshold, 8 bit)
     pub const fn set_vcell_severe_ov_delta_th(&mut self, severe_ov_threshold: u8) {
-        self.0 = self.0 & 0xFF00 | (severe_ov_threshold as u16);
+        self.0 = VCELL_SEVERE_OV_DELTA_TH.set(self.0, severe_ov_threshold as u16);
     }
 
     /// Get the programmable cell severe UV threshold (negative delta in respect to cell UV threshold, 8 bit)
     pub const fn get_vcell_severe_uv_delta_th(&self) -> u8 {
-        ((self.0 & 0xFF00) >> 8) as u8
+        VCELL_SEVERE_UV_DELTA_TH.get(self.0) as u8
     }
 
     /// Set the programmable cell severe UV threshold (negative delta in respect to cell UV threshold, 8 bit)
     pub const fn set_vcell_severe_uv_delta_th(&mut self, severe_uv_threshold: u8) {
-        self.0 = self.0 & 0x00FF | ((severe_uv_threshold as u16) << 8);
+        self.0 = VCELL_SEVERE_UV_DELTA_TH.set(self.0, severe_uv_threshold as u16);
     }
 }
 
@@ -50,6 +52,17 @@ impl From<u16> for VCellSevereDeltaThrs {
     }
 }
 
+impl core::fmt::Display for VCellSevereDeltaThrs {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VCELL_SEVERE_DELTA_THRS: {{\n  VCELL_SEVERE_UV_DELTA_TH: {},\n  VCELL_SEVERE_OV_DELTA_TH: {}\n}}",
+            self.get_vcell_severe_uv_delta_th(),
+            self.get_vcell_severe_ov_delta_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VCellSevereDeltaThrs {
     fn format(&self, f: defmt::Formatter) {
@@ -1,35 +1,39 @@
 use core::ops::Deref;
-use defmt::{write, Format, Formatter};
+
+use super::regmap::vcell_uv_th::{NCELL_UV_CNT_TH, VCELL_UV_TH};
 
 /// Cell under-voltage monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCellUvTh(u16);
 
 impl VCellUvTh {
     /// Create a new VCellUvTh register value
     pub const fn new(vcell_uv_th: u8, ncell_uv_cnt_th: u8) -> Self {
         debug_assert!(ncell_uv_cnt_th < 16, "Invalid VCellUvTh value");
-        VCellUvTh((vcell_uv_th as u16) | ((ncell_uv_cnt_th as u16) << 8))
+        VCellUvTh(NCELL_UV_CNT_TH.set(
+            VCELL_UV_TH.set(0, vcell_uv_th as u16),
+            ncell_uv_cnt_th as u16,
+        ))
     }
 
     /// Get the programmable cell under-voltage fault threshold (8bit)
     pub const fn get_vcell_uv_th(&self) -> u8 {
-        (self.0 & 0x00FF) as u8
+        VCELL_UV_TH.get(self.0) as u8
     }
 
     /// Set the the programmable cell under-voltage fault threshold (8bit)
     pub const fn set_vcell_uv_th(&mut self, vcell_uv_th: u8) {
-        self.0 = self.0 & 0xFF00 | (vcell_uv_th as u16);
+        self.0 = VCELL_UV_TH.set(self.0, vcell_uv_th as u16);
     }
 
     /// Get the programmable cell under-voltage event counter threshold (4 bit)
     pub const fn get_ncell_uv_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 8) as u8
+        NCELL_UV_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable cell under-voltage event counter threshold (4 bit)
     pub const fn set_ncell_uv_cnt_th(&mut self, ncell_uv_cnt_th: u8) {
-        self.0 = self.0 & 0xF0FF | ((ncell_uv_cnt_th as u16) << 8);
+        self.0 = NCELL_UV_CNT_TH.set(self.0, ncell_uv_cnt_th as u16);
     }
 }
 
@@ -47,9 +51,8 @@ impl From<u16> for VCellUvTh {
     }
 }
 
-#[cfg(feature = "defmt")]
-impl Format for VCellUvTh {
-    fn format(&self, f: Formatter) {
+impl core::fmt::Display for VCellUvTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
         write!(
             f,
             "VCELL_UV_TH: {{\n  VCELL_UV_TH: {},\n  NCELL_UV_CNT_TH: {}\n}}",
@@ -58,3 +61,15 @@ impl Format for VCellUvTh {
         )
     }
 }
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for VCellUvTh {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(
+            f,
+            "VCELL_UV_TH: {{\n  VCELL_UV_TH: {},\n  NCELL_UV_CNT_TH: {}\n}}",
+            self.get_vcell_uv_th(),
+            self.get_ncell_uv_cnt_th()
+        )
+    }
+}
@@ -1,13 +1,15 @@
 use crate::conversions::cell_voltage_measurement_mv_from_code;
 
+use super::regmap::vcellsum::VCELLSUM_MEAS;
+
 /// VCellSum Measurement Register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VCellSum(u16);
 
 impl VCellSum {
     /// Get the sum of cell voltages measurement code
     pub const fn get_vcellsum_meas(&self) -> u16 {
-        self.0 & 0x7FFF
+        VCELLSUM_MEAS.get(self.0)
     }
 }
 
@@ -25,6 +27,16 @@ impl From<u16> for VCellSum {
     }
 }
 
+impl core::fmt::Display for VCellSum {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VCELLSUM:  {}mv",
+            cell_voltage_measurement_mv_from_code(self.get_vcellsum_meas())
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VCellSum {
     fn format(&self, f: defmt::Formatter) {
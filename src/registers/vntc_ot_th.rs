@@ -1,7 +1,9 @@
 use core::ops::Deref;
 
+use super::regmap::vntc_ot_th::{NNTC_OT_CNT_TH, NTC_OT_TH};
+
 /// NTC over temp monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VNTCOTTh(u16);
 
 impl VNTCOTTh {
@@ -15,12 +17,12 @@ impl VNTCOTTh {
             nntc_ot_cnt_th & 0x0F == nntc_ot_cnt_th,
             "Invalid ntc overtemp counter threshold value"
         );
-        Self((ntc_ot_th & 0x0FFF) | ((nntc_ot_cnt_th as u16) << 12))
+        Self(NNTC_OT_CNT_TH.set(NTC_OT_TH.set(0, ntc_ot_th), nntc_ot_cnt_th as u16))
     }
 
     /// Get the programmable over temp fault threshold (12bit)
     pub const fn get_ntc_ot_th(&self) -> u16 {
-        self.0 & 0x0FFF
+        NTC_OT_TH.get(self.0)
     }
 
     /// Set the the programmable over temp fault threshold (12bit)
@@ -29,17 +31,17 @@ impl VNTCOTTh {
             ntc_ot_th & 0x0FFF == ntc_ot_th,
             "Invalid ntc overtemp threshold value"
         );
-        self.0 = self.0 & 0xF000 | ntc_ot_th;
+        self.0 = NTC_OT_TH.set(self.0, ntc_ot_th);
     }
 
     /// Get the programmable over temp event counter threshold (4 bit)
     pub const fn get_nntc_ot_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 12) as u8
+        NNTC_OT_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable over temp event counter threshold (4 bit)
     pub const fn set_nntc_ot_cnt_th(&mut self, nntc_ot_cnt_th: u8) {
-        self.0 = self.0 & 0x0FFF | ((nntc_ot_cnt_th as u16) << 12);
+        self.0 = NNTC_OT_CNT_TH.set(self.0, nntc_ot_cnt_th as u16);
     }
 }
 
@@ -56,6 +58,17 @@ impl From<u16> for VNTCOTTh {
     }
 }
 
+impl core::fmt::Display for VNTCOTTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VNTC_OT_TH: {{\n  NTC_OT_TH: {},\n  NNTC_OT_CNT_TH: {}\n}}",
+            self.get_ntc_ot_th(),
+            self.get_nntc_ot_cnt_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VNTCOTTh {
     fn format(&self, f: defmt::Formatter) {
@@ -1,7 +1,9 @@
 use core::ops::Deref;
 
+use super::regmap::vntc_severe_ot_delta_th::NTC_SEVERE_OT_DELTA_TH;
+
 /// NTC severe over temp monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VNTCSevereOTTh(u16);
 
 impl VNTCSevereOTTh {
@@ -16,7 +18,7 @@ impl VNTCSevereOTTh {
 
     /// Get the programmable severe over temp delta threshold (12bit)
     pub const fn get_ntc_severe_ot_th(&self) -> u16 {
-        self.0 & 0x0FFF
+        NTC_SEVERE_OT_DELTA_TH.get(self.0)
     }
 
     /// Set the the programmable severe over temp delta threshold (12bit)
@@ -25,7 +27,7 @@ impl VNTCSevereOTTh {
             ntc_ot_th & 0x0FFF == ntc_ot_th,
             "Invalid ntc severe over-temp threshold value"
         );
-        self.0 = self.0 & 0xF000 | ntc_ot_th;
+        self.0 = NTC_SEVERE_OT_DELTA_TH.set(self.0, ntc_ot_th);
     }
 }
 
@@ -42,6 +44,12 @@ impl From<u16> for VNTCSevereOTTh {
     }
 }
 
+impl core::fmt::Display for VNTCSevereOTTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "VNTC_SEVERE_OT_TH: {}", self.get_ntc_severe_ot_th(),)
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VNTCSevereOTTh {
     fn format(&self, f: defmt::Formatter) {
@@ -1,7 +1,9 @@
 use core::ops::Deref;
 
+use super::regmap::vntc_ut_th::{NNTC_UT_CNT_TH, NTC_UT_TH};
+
 /// NTC under temp monitoring threshold configuration register
-#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[derive(Clone, Copy, Eq, PartialEq)]
 pub struct VNTCUTTh(u16);
 
 impl VNTCUTTh {
@@ -15,11 +17,11 @@ impl VNTCUTTh {
             fault_count & 0x0F == fault_count,
             "Invalid ntc under temp counter threshold value"
         );
-        Self((threshold & 0x0FFF) | ((fault_count as u16) << 12))
+        Self(NNTC_UT_CNT_TH.set(NTC_UT_TH.set(0, threshold), fault_count as u16))
     }
     /// Get the programmable under temp fault threshold (12bit)
     pub const fn get_ntc_ut_th(&self) -> u16 {
-        self.0 & 0x0FFF
+        NTC_UT_TH.get(self.0)
     }
 
     /// Set the the programmable under temp fault threshold (12bit)
@@ -28,17 +30,17 @@ impl VNTCUTTh {
             ntc_ut_th & 0x0FFF == ntc_ut_th,
             "Invalid ntc under temp threshold value"
         );
-        self.0 = self.0 & 0xF000 | ntc_ut_th;
+        self.0 = NTC_UT_TH.set(self.0, ntc_ut_th);
     }
 
     /// Get the programmable under temp event counter threshold (4 bit)
     pub const fn get_ntc_ut_cnt_th(&self) -> u8 {
-        ((self.0 & 0x0F00) >> 12) as u8
+        NNTC_UT_CNT_TH.get(self.0) as u8
     }
 
     /// Set the programmable under temp event counter threshold (4 bit)
     pub const fn set_ntc_ut_cnt_th(&mut self, nntc_ut_cnt_th: u8) {
-        self.0 = self.0 & 0x0FFF | ((nntc_ut_cnt_th as u16) << 12);
+        self.0 = NNTC_UT_CNT_TH.set(self.0, nntc_ut_cnt_th as u16);
     }
 }
 
@@ -55,6 +57,17 @@ impl From<u16> for VNTCUTTh {
     }
 }
 
+impl core::fmt::Display for VNTCUTTh {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "VNTC_UT_TH: {{\n  NTC_UT_TH: {},\n  NNTC_UT_CNT_TH: {}\n}}",
+            self.get_ntc_ut_th(),
+            self.get_ntc_ut_cnt_th()
+        )
+    }
+}
+
 #[cfg(feature = "defmt")]
 impl defmt::Format for VNTCUTTh {
     fn format(&self, f: defmt::Formatter) {
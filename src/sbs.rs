@@ -0,0 +1,724 @@
+//! # Smart Battery Data
+//! Emulation of the Smart Battery Data Specification (SBS 1.1), so that a firmware acting as an SMBus target
+//! can answer host requests from the state of the driver.
+//!
+//! The [`SmartBattery`] combines three sources of data:
+//! - The latest [`Measurement`], providing voltages, current, temperature and the alarms derived from [`PackFaults`]
+//! - The [`Identity`] stored in the L9961's manufacturer, date, serial number and device name registers
+//! - The [`GaugeState`] computed by the application, as the L9961 does not estimate state of charge or capacity
+//!
+//! SMBus transport is left to the firmware: it passes each received command code to [`SmartBattery::read`]
+//! or [`SmartBattery::write_word`] and transmits the returned bytes, optionally followed by the [`pec`].
+//!
+//! In addition to the standard commands, the cell voltages are reported on the manufacturer specific codes
+//! 0x3B (cell 5) to 0x3F (cell 1), following the convention of common fuel gauges.
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961, Registers,
+    faults::{CellFaults, PackFaults},
+    measurement::Measurement,
+};
+
+#[cfg(not(feature = "defmt"))]
+use bitflags::bitflags;
+#[cfg(feature = "defmt")]
+use defmt::bitflags;
+
+/// SpecificationInfo reported to the host: SBS 1.1 with PEC support, no voltage or current scaling
+pub const SPECIFICATION_INFO: u16 = 0x0031;
+/// Maximum length of the data in a block response
+pub const MAX_BLOCK_LEN: usize = 32;
+
+/// SBS command codes supported by the [`SmartBattery`]
+#[repr(u8)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Command {
+    /// Chip ID of the L9961
+    ManufacturerAccess = 0x00,
+    /// Remaining capacity below which the REMAINING_CAPACITY_ALARM is raised
+    RemainingCapacityAlarm = 0x01,
+    /// Remaining run time in minutes below which the REMAINING_TIME_ALARM is raised
+    RemainingTimeAlarm = 0x02,
+    /// Battery operational modes
+    BatteryMode = 0x03,
+    /// Rate used by the AtRate calculations
+    AtRate = 0x04,
+    /// Minutes to full charge at the AtRate
+    AtRateTimeToFull = 0x05,
+    /// Minutes to empty at the AtRate
+    AtRateTimeToEmpty = 0x06,
+    /// Whether the battery can deliver the AtRate for 10 seconds
+    AtRateOk = 0x07,
+    /// Pack temperature in 0.1 K
+    Temperature = 0x08,
+    /// Pack voltage in mV
+    Voltage = 0x09,
+    /// Current in mA, positive while charging
+    Current = 0x0A,
+    /// Average current in mA, positive while charging
+    AverageCurrent = 0x0B,
+    /// Expected error of the state of charge calculation in percent
+    MaxError = 0x0C,
+    /// Remaining capacity in percent of the full charge capacity
+    RelativeStateOfCharge = 0x0D,
+    /// Remaining capacity in percent of the design capacity
+    AbsoluteStateOfCharge = 0x0E,
+    /// Remaining capacity
+    RemainingCapacity = 0x0F,
+    /// Capacity when fully charged
+    FullChargeCapacity = 0x10,
+    /// Minutes to empty at the present current
+    RunTimeToEmpty = 0x11,
+    /// Minutes to empty at the average current
+    AverageTimeToEmpty = 0x12,
+    /// Minutes to full charge at the average current
+    AverageTimeToFull = 0x13,
+    /// Desired charging current in mA
+    ChargingCurrent = 0x14,
+    /// Desired charging voltage in mV
+    ChargingVoltage = 0x15,
+    /// Alarm and status flags
+    BatteryStatus = 0x16,
+    /// Number of charge cycles
+    CycleCount = 0x17,
+    /// Theoretical capacity of a new pack
+    DesignCapacity = 0x18,
+    /// Theoretical voltage of a new pack in mV
+    DesignVoltage = 0x19,
+    /// Version of the specification implemented
+    SpecificationInfo = 0x1A,
+    /// Date of manufacture, packed as `day + 32 * month + 512 * (year - 1980)`
+    ManufactureDate = 0x1B,
+    /// Serial number
+    SerialNumber = 0x1C,
+    /// Manufacturer name string
+    ManufacturerName = 0x20,
+    /// Device name string
+    DeviceName = 0x21,
+    /// Chemistry string
+    DeviceChemistry = 0x22,
+    /// Date of first usage, in the same format as the date of manufacture
+    ManufacturerData = 0x23,
+    /// Cell 5 voltage in mV
+    CellVoltage5 = 0x3B,
+    /// Cell 4 voltage in mV
+    CellVoltage4 = 0x3C,
+    /// Cell 3 voltage in mV
+    CellVoltage3 = 0x3D,
+    /// Cell 2 voltage in mV
+    CellVoltage2 = 0x3E,
+    /// Cell 1 voltage in mV
+    CellVoltage1 = 0x3F,
+}
+
+impl TryFrom<u8> for Command {
+    type Error = ErrorCode;
+
+    fn try_from(code: u8) -> Result<Self, ErrorCode> {
+        Ok(match code {
+            0x00 => Command::ManufacturerAccess,
+            0x01 => Command::RemainingCapacityAlarm,
+            0x02 => Command::RemainingTimeAlarm,
+            0x03 => Command::BatteryMode,
+            0x04 => Command::AtRate,
+            0x05 => Command::AtRateTimeToFull,
+            0x06 => Command::AtRateTimeToEmpty,
+            0x07 => Command::AtRateOk,
+            0x08 => Command::Temperature,
+            0x09 => Command::Voltage,
+            0x0A => Command::Current,
+            0x0B => Command::AverageCurrent,
+            0x0C => Command::MaxError,
+            0x0D => Command::RelativeStateOfCharge,
+            0x0E => Command::AbsoluteStateOfCharge,
+            0x0F => Command::RemainingCapacity,
+            0x10 => Command::FullChargeCapacity,
+            0x11 => Command::RunTimeToEmpty,
+            0x12 => Command::AverageTimeToEmpty,
+            0x13 => Command::AverageTimeToFull,
+            0x14 => Command::ChargingCurrent,
+            0x15 => Command::ChargingVoltage,
+            0x16 => Command::BatteryStatus,
+            0x17 => Command::CycleCount,
+            0x18 => Command::DesignCapacity,
+            0x19 => Command::DesignVoltage,
+            0x1A => Command::SpecificationInfo,
+            0x1B => Command::ManufactureDate,
+            0x1C => Command::SerialNumber,
+            0x20 => Command::ManufacturerName,
+            0x21 => Command::DeviceName,
+            0x22 => Command::DeviceChemistry,
+            0x23 => Command::ManufacturerData,
+            0x3B => Command::CellVoltage5,
+            0x3C => Command::CellVoltage4,
+            0x3D => Command::CellVoltage3,
+            0x3E => Command::CellVoltage2,
+            0x3F => Command::CellVoltage1,
+            _ => return Err(ErrorCode::UnsupportedCommand),
+        })
+    }
+}
+
+/// Error codes reported in the low nibble of BatteryStatus
+#[repr(u16)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ErrorCode {
+    /// The last command completed without error
+    Ok = 0,
+    /// The battery is unable to process the command
+    Busy = 1,
+    /// The command is reserved
+    ReservedCommand = 2,
+    /// The command is not supported
+    UnsupportedCommand = 3,
+    /// The command cannot be written
+    AccessDenied = 4,
+    /// The written value is out of range
+    OverflowUnderflow = 5,
+    /// The command was written with the wrong amount of data
+    BadSize = 6,
+    /// Any other error
+    UnknownError = 7,
+}
+
+bitflags! {
+    /// Alarm and status flags of the BatteryStatus command
+    pub struct BatteryStatus:u16 {
+        /// A cell or the pack is over voltage
+        const OVER_CHARGED_ALARM = 0x8000;
+        /// Charging should stop
+        const TERMINATE_CHARGE_ALARM = 0x4000;
+        /// The pack or the L9961 is over temperature
+        const OVER_TEMP_ALARM = 0x1000;
+        /// Discharging should stop
+        const TERMINATE_DISCHARGE_ALARM = 0x0800;
+        /// The remaining capacity is below the RemainingCapacityAlarm
+        const REMAINING_CAPACITY_ALARM = 0x0200;
+        /// The time to empty is below the RemainingTimeAlarm
+        const REMAINING_TIME_ALARM = 0x0100;
+        /// A measurement has been received
+        const INITIALIZED = 0x0080;
+        /// The pack is not charging
+        const DISCHARGING = 0x0040;
+        /// The pack is fully charged
+        const FULLY_CHARGED = 0x0020;
+        /// The pack is fully discharged
+        const FULLY_DISCHARGED = 0x0010;
+    }
+}
+
+impl BatteryStatus {
+    /// Flags which are alarms, broadcast to the host unless alarms are disabled in the BatteryMode
+    pub const ALARMS: BatteryStatus = BatteryStatus::from_bits_truncate(0xFF00);
+}
+
+bitflags! {
+    /// Operational mode flags of the BatteryMode command
+    pub struct BatteryMode:u16 {
+        /// The battery contains an internal charge controller
+        const INTERNAL_CHARGE_CONTROLLER = 0x0001;
+        /// The battery can act as a primary or secondary battery
+        const PRIMARY_BATTERY_SUPPORT = 0x0002;
+        /// A conditioning cycle is requested
+        const CONDITION_FLAG = 0x0080;
+        /// The internal charge controller is enabled
+        const CHARGE_CONTROLLER_ENABLED = 0x0100;
+        /// The battery is operating as the primary battery
+        const PRIMARY_BATTERY = 0x0200;
+        /// Alarm warnings are not broadcast to the host
+        const ALARM_MODE = 0x2000;
+        /// ChargingCurrent and ChargingVoltage are not broadcast to the charger
+        const CHARGER_MODE = 0x4000;
+        /// Capacities are reported in 10 mWh rather than mAh
+        const CAPACITY_MODE = 0x8000;
+    }
+}
+
+impl BatteryMode {
+    /// Flags which the host may write
+    pub const WRITABLE: BatteryMode = BatteryMode::from_bits_truncate(0xE300);
+}
+
+/// Identity of the pack, as stored in the L9961's user registers
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Identity {
+    /// CHIP_ID register
+    pub chip_id: u16,
+    /// MANUFACTURER_NAME_MSB and MANUFACTURER_NAME_LSB, reported as up to 4 ASCII characters
+    pub manufacturer_name: u32,
+    /// MANUFACTURING_DATE register, expected to be packed as by [`sbs_date`]
+    pub manufacturing_date: u16,
+    /// FIRST_USAGE_DATE register, expected to be packed as by [`sbs_date`]
+    pub first_usage_date: u16,
+    /// SERIAL_NUMBER_MSB and SERIAL_NUMBER_LSB, of which the host only sees the LSB
+    pub serial_number: u32,
+    /// DEVICE_NAME_MSB and DEVICE_NAME_LSB, reported as up to 4 ASCII characters
+    pub device_name: u32,
+}
+
+/// Pack a date in the format of the ManufactureDate command,
+/// or `None` if the year is outside the 1980 to 2107 range the format can hold
+pub const fn sbs_date(year: u16, month: u8, day: u8) -> Option<u16> {
+    if year < 1980 || year > 2107 {
+        return None;
+    }
+    Some(day as u16 + 32 * month as u16 + 512 * (year - 1980))
+}
+
+/// Static properties of the pack
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PackInfo {
+    /// Capacity of a new pack in mAh
+    pub design_capacity_mah: u16,
+    /// Nominal voltage of a new pack in mV
+    pub design_voltage_mv: u16,
+    /// Current requested from the charger in mA
+    pub charging_current_ma: u16,
+    /// Voltage requested from the charger in mV
+    pub charging_voltage_mv: u16,
+    /// Chemistry reported to the host, e.g. "LION"
+    pub chemistry: &'static str,
+}
+
+/// State of charge and capacity, estimated by the application
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GaugeState {
+    /// Remaining capacity in mAh
+    pub remaining_capacity_mah: u16,
+    /// Capacity when fully charged in mAh
+    pub full_charge_capacity_mah: u16,
+    /// Average current in mA, positive while charging
+    pub average_current_ma: i16,
+    /// Expected error of the estimate in percent
+    pub max_error_percent: u8,
+    /// Number of charge cycles
+    pub cycle_count: u16,
+    /// Pack temperature in 0.1 K from the NTC, if characterized.
+    /// The die temperature is reported otherwise.
+    pub temperature_dk: Option<u16>,
+}
+
+/// The bytes to transmit in response to a read command
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Response {
+    bytes: [u8; MAX_BLOCK_LEN + 1],
+    len: usize,
+}
+
+impl Response {
+    /// A read word response, transmitted least significant byte first
+    pub fn word(value: u16) -> Self {
+        let mut bytes = [0; MAX_BLOCK_LEN + 1];
+        bytes[..2].copy_from_slice(&value.to_le_bytes());
+        Self { bytes, len: 2 }
+    }
+
+    /// A block read response, prefixed by its length. Data beyond [`MAX_BLOCK_LEN`] is truncated.
+    pub fn block(data: &[u8]) -> Self {
+        let len = data.len().min(MAX_BLOCK_LEN);
+        let mut bytes = [0; MAX_BLOCK_LEN + 1];
+        bytes[0] = len as u8;
+        bytes[1..=len].copy_from_slice(&data[..len]);
+        Self {
+            bytes,
+            len: len + 1,
+        }
+    }
+
+    /// The bytes to transmit
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes[..self.len]
+    }
+
+    /// The PEC of the complete read transaction to the 7-bit `address`
+    pub fn pec(&self, address: u8, command: u8) -> u8 {
+        let header = pec_update(0, &[address << 1, command, address << 1 | 1]);
+        pec_update(header, self.as_bytes())
+    }
+}
+
+/// SMBus packet error code: CRC-8 with polynomial 0x07 over every byte of a transaction, including addresses
+pub const fn pec(data: &[u8]) -> u8 {
+    pec_update(0, data)
+}
+
+const fn pec_update(mut crc: u8, data: &[u8]) -> u8 {
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= data[i];
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x80 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x07,
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+/// Answers SBS commands from the latest measurement, identity and gauge state
+pub struct SmartBattery {
+    pack: PackInfo,
+    identity: Identity,
+    gauge: GaugeState,
+    measurement: Option<Measurement>,
+    battery_mode: BatteryMode,
+    remaining_capacity_alarm: u16,
+    remaining_time_alarm: u16,
+    at_rate: i16,
+    last_error: ErrorCode,
+}
+
+impl SmartBattery {
+    /// Create a new smart battery for the given pack, with the alarms at their SBS defaults
+    pub fn new(pack: PackInfo) -> Self {
+        Self {
+            pack,
+            identity: Identity::default(),
+            gauge: GaugeState {
+                full_charge_capacity_mah: pack.design_capacity_mah,
+                ..GaugeState::default()
+            },
+            measurement: None,
+            battery_mode: BatteryMode::empty(),
+            remaining_capacity_alarm: pack.design_capacity_mah / 10,
+            remaining_time_alarm: 10,
+            at_rate: 0,
+            last_error: ErrorCode::Ok,
+        }
+    }
+
+    /// Update the measurement the voltages, current, temperature and alarms are reported from
+    pub fn update_measurement(&mut self, measurement: &Measurement) {
+        self.measurement = Some(*measurement);
+    }
+
+    /// Update the identity reported to the host, e.g. from [`L9961::read_identity`]
+    pub fn set_identity(&mut self, identity: Identity) {
+        self.identity = identity;
+    }
+
+    /// Update the state of charge and capacity reported to the host
+    pub fn update_gauge(&mut self, gauge: GaugeState) {
+        self.gauge = gauge;
+    }
+
+    /// The current battery mode, as written by the host
+    pub fn battery_mode(&self) -> BatteryMode {
+        self.battery_mode
+    }
+
+    /// The alarms to broadcast to the host with an AlarmWarning, if any are set and alarms are enabled
+    pub fn alarm_warning(&self) -> Option<u16> {
+        let alarms = self.battery_status() & BatteryStatus::ALARMS;
+        match alarms.is_empty() || self.battery_mode.contains(BatteryMode::ALARM_MODE) {
+            true => None,
+            false => Some(alarms.bits() | self.last_error as u16),
+        }
+    }
+
+    /// Status flags derived from the latest measurement and gauge state
+    pub fn battery_status(&self) -> BatteryStatus {
+        let mut status = BatteryStatus::empty();
+        let Some(measurement) = &self.measurement else {
+            return status;
+        };
+        status |= BatteryStatus::INITIALIZED;
+
+        let pack_faults = measurement.pack_faults;
+        let cell_faults = (1..=5)
+            .filter_map(|cell| measurement.cell(cell))
+            .fold(CellFaults::empty(), |faults, cell| faults | cell.faults);
+
+        let over_charged = pack_faults.contains(PackFaults::OVER_VOLTAGE)
+            || cell_faults.intersects(CellFaults::OVER_VOLTAGE | CellFaults::EXTREME_OVER_VOLTAGE);
+        let over_temp = pack_faults.intersects(
+            PackFaults::NTC_OVER_TEMP
+                | PackFaults::NTC_SEVERE_OVER_TEMP
+                | PackFaults::DIE_OVER_TEMP,
+        );
+        let terminate_charge = over_charged
+            || over_temp
+            || pack_faults.intersects(
+                PackFaults::NTC_UNDER_TEMP
+                    | PackFaults::OVER_CURRENT_CHARGE
+                    | PackFaults::PERSISTENT_OVER_CURRENT_IN_CHARGE,
+            );
+        let under_voltage = pack_faults.contains(PackFaults::UNDER_VOLTAGE)
+            || cell_faults
+                .intersects(CellFaults::UNDER_VOLTAGE | CellFaults::EXTREME_UNDER_VOLTAGE);
+        let terminate_discharge = under_voltage
+            || pack_faults.intersects(
+                PackFaults::NTC_SEVERE_OVER_TEMP
+                    | PackFaults::DIE_OVER_TEMP
+                    | PackFaults::OVER_CURRENT_DISCHARGE
+                    | PackFaults::PERSISTENT_OVER_CURRENT_IN_DISCHARGE
+                    | PackFaults::SHORT_CIRCUIT_DISCHARGE
+                    | PackFaults::PERSISTENT_SHORT_CIRCUIT_DISCHARGE,
+            );
+
+        status.set(BatteryStatus::OVER_CHARGED_ALARM, over_charged);
+        status.set(BatteryStatus::TERMINATE_CHARGE_ALARM, terminate_charge);
+        status.set(BatteryStatus::OVER_TEMP_ALARM, over_temp);
+        status.set(
+            BatteryStatus::TERMINATE_DISCHARGE_ALARM,
+            terminate_discharge,
+        );
+        status.set(
+            BatteryStatus::REMAINING_CAPACITY_ALARM,
+            self.remaining_capacity_alarm > 0
+                && self.capacity(self.gauge.remaining_capacity_mah) < self.remaining_capacity_alarm,
+        );
+        status.set(
+            BatteryStatus::REMAINING_TIME_ALARM,
+            self.remaining_time_alarm > 0
+                && self.average_time_to_empty() < self.remaining_time_alarm,
+        );
+        status.set(BatteryStatus::DISCHARGING, self.current_ma() <= 0);
+        status.set(
+            BatteryStatus::FULLY_CHARGED,
+            self.relative_state_of_charge() >= 100,
+        );
+        status.set(
+            BatteryStatus::FULLY_DISCHARGED,
+            under_voltage
+                || self.gauge.full_charge_capacity_mah > 0 && self.relative_state_of_charge() == 0,
+        );
+        status
+    }
+
+    /// Answer a read word or block read command
+    pub fn read(&mut self, command: u8) -> Result<Response, ErrorCode> {
+        let response = Command::try_from(command).and_then(|command| self.respond(command));
+        self.last_error = match response {
+            Ok(_) => ErrorCode::Ok,
+            Err(error) => error,
+        };
+        response
+    }
+
+    /// Handle a write word command
+    pub fn write_word(&mut self, command: u8, value: u16) -> Result<(), ErrorCode> {
+        let result = Command::try_from(command).and_then(|command| match command {
+            Command::RemainingCapacityAlarm => {
+                self.remaining_capacity_alarm = value;
+                Ok(())
+            }
+            Command::RemainingTimeAlarm => {
+                self.remaining_time_alarm = value;
+                Ok(())
+            }
+            Command::BatteryMode => match BatteryMode::from_bits(value) {
+                Some(mode) if BatteryMode::WRITABLE.contains(mode) => {
+                    self.battery_mode = mode;
+                    Ok(())
+                }
+                _ => Err(ErrorCode::OverflowUnderflow),
+            },
+            Command::AtRate => {
+                self.at_rate = value as i16;
+                Ok(())
+            }
+            _ => Err(ErrorCode::AccessDenied),
+        });
+        self.last_error = match result {
+            Ok(()) => ErrorCode::Ok,
+            Err(error) => error,
+        };
+        result
+    }
+
+    fn respond(&self, command: Command) -> Result<Response, ErrorCode> {
+        let measurement = self.measurement.as_ref().ok_or(ErrorCode::Busy);
+        Ok(Response::word(match command {
+            Command::ManufacturerAccess => self.identity.chip_id,
+            Command::RemainingCapacityAlarm => self.remaining_capacity_alarm,
+            Command::RemainingTimeAlarm => self.remaining_time_alarm,
+            Command::BatteryMode => self.battery_mode.bits(),
+            Command::AtRate => self.at_rate as u16,
+            Command::AtRateTimeToFull => match self.at_rate_ma() {
+                rate @ 1.. => self.minutes_to_full(rate),
+                _ => u16::MAX,
+            },
+            Command::AtRateTimeToEmpty => match self.at_rate_ma() {
+                rate @ ..0 => self.minutes_to_empty(rate),
+                _ => u16::MAX,
+            },
+            Command::AtRateOk => {
+                (self.at_rate_ma() >= 0 || self.gauge.remaining_capacity_mah > 0) as u16
+            }
+            Command::Temperature => match self.gauge.temperature_dk {
+                Some(temperature) => temperature,
+                None => measurement?.die_temp * 10 + 2732,
+            },
+            Command::Voltage => measurement?.vbat_mv,
+            Command::Current => measurement.map(|_| self.current_ma() as u16)?,
+            Command::AverageCurrent => self.gauge.average_current_ma as u16,
+            Command::MaxError => self.gauge.max_error_percent as u16,
+            Command::RelativeStateOfCharge => self.relative_state_of_charge(),
+            Command::AbsoluteStateOfCharge => percent(
+                self.gauge.remaining_capacity_mah,
+                self.pack.design_capacity_mah,
+            ),
+            Command::RemainingCapacity => self.capacity(self.gauge.remaining_capacity_mah),
+            Command::FullChargeCapacity => self.capacity(self.gauge.full_charge_capacity_mah),
+            Command::RunTimeToEmpty => match self.current_ma() {
+                current @ ..0 => self.minutes_to_empty(current),
+                _ => u16::MAX,
+            },
+            Command::AverageTimeToEmpty => self.average_time_to_empty(),
+            Command::AverageTimeToFull => match self.gauge.average_current_ma as i32 {
+                current @ 1.. => self.minutes_to_full(current),
+                _ => u16::MAX,
+            },
+            Command::ChargingCurrent => self.pack.charging_current_ma,
+            Command::ChargingVoltage => self.pack.charging_voltage_mv,
+            Command::BatteryStatus => self.battery_status().bits() | self.last_error as u16,
+            Command::CycleCount => self.gauge.cycle_count,
+            Command::DesignCapacity => self.capacity(self.pack.design_capacity_mah),
+            Command::DesignVoltage => self.pack.design_voltage_mv,
+            Command::SpecificationInfo => SPECIFICATION_INFO,
+            Command::ManufactureDate => self.identity.manufacturing_date,
+            Command::SerialNumber => self.identity.serial_number as u16,
+            Command::ManufacturerName => {
+                return Ok(ascii_block(self.identity.manufacturer_name));
+            }
+            Command::DeviceName => return Ok(ascii_block(self.identity.device_name)),
+            Command::DeviceChemistry => {
+                return Ok(Response::block(self.pack.chemistry.as_bytes()));
+            }
+            Command::ManufacturerData => {
+                return Ok(Response::block(
+                    &self.identity.first_usage_date.to_le_bytes(),
+                ));
+            }
+            Command::CellVoltage5 => cell_voltage(measurement?, 5),
+            Command::CellVoltage4 => cell_voltage(measurement?, 4),
+            Command::CellVoltage3 => cell_voltage(measurement?, 3),
+            Command::CellVoltage2 => cell_voltage(measurement?, 2),
+            Command::CellVoltage1 => cell_voltage(measurement?, 1),
+        }))
+    }
+
+    /// Instantaneous current in mA from the coulomb counter, positive while charging
+    fn current_ma(&self) -> i32 {
+        #[cfg(feature = "coulomb_counting")]
+        if let Some(measurement) = &self.measurement {
+            return crate::conversions::current_ma_from_signed_code(measurement.cc_inst_meas)
+                .clamp(i16::MIN as i32, i16::MAX as i32);
+        }
+        0
+    }
+
+    /// The AtRate in mA, converted from 10 mW in capacity mode
+    fn at_rate_ma(&self) -> i32 {
+        match self.battery_mode.contains(BatteryMode::CAPACITY_MODE) {
+            true => self.at_rate as i32 * 10_000 / self.pack.design_voltage_mv.max(1) as i32,
+            false => self.at_rate as i32,
+        }
+    }
+
+    /// A capacity in mAh, converted to 10 mWh in capacity mode
+    fn capacity(&self, capacity_mah: u16) -> u16 {
+        match self.battery_mode.contains(BatteryMode::CAPACITY_MODE) {
+            true => (capacity_mah as u32 * self.pack.design_voltage_mv as u32 / 10_000).min(0xFFFF)
+                as u16,
+            false => capacity_mah,
+        }
+    }
+
+    fn relative_state_of_charge(&self) -> u16 {
+        percent(
+            self.gauge.remaining_capacity_mah,
+            self.gauge.full_charge_capacity_mah,
+        )
+    }
+
+    fn average_time_to_empty(&self) -> u16 {
+        match self.gauge.average_current_ma as i32 {
+            current @ ..0 => self.minutes_to_empty(current),
+            _ => u16::MAX,
+        }
+    }
+
+    fn minutes_to_empty(&self, current_ma: i32) -> u16 {
+        minutes(self.gauge.remaining_capacity_mah as u32, current_ma)
+    }
+
+    fn minutes_to_full(&self, current_ma: i32) -> u16 {
+        minutes(
+            self.gauge
+                .full_charge_capacity_mah
+                .saturating_sub(self.gauge.remaining_capacity_mah) as u32,
+            current_ma,
+        )
+    }
+}
+
+/// Minutes to move `capacity_mah` at `current_ma`, saturating below the 65535 "not applicable" value
+fn minutes(capacity_mah: u32, current_ma: i32) -> u16 {
+    (capacity_mah * 60 / current_ma.unsigned_abs()).min(u16::MAX as u32 - 1) as u16
+}
+
+fn percent(value: u16, full: u16) -> u16 {
+    match full {
+        0 => 0,
+        full => (value as u32 * 100 / full as u32).min(100) as u16,
+    }
+}
+
+fn cell_voltage(measurement: &Measurement, cell: u8) -> u16 {
+    measurement
+        .cell(cell)
+        .map_or(0, |measurement| measurement.voltage_mv)
+}
+
+/// Report a 32 bit name register pair as ASCII, most significant byte first, dropping NUL bytes
+fn ascii_block(name: u32) -> Response {
+    let mut bytes = [0; 4];
+    let mut len = 0;
+    for byte in name.to_be_bytes() {
+        if byte != 0 {
+            bytes[len] = byte;
+            len += 1;
+        }
+    }
+    Response::block(&bytes[..len])
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Read the chip ID and the manufacturer, date, serial number and device name registers
+    pub async fn read_identity(&mut self) -> Result<Identity, I2C::Error> {
+        let chip_id = self.read_register(Registers::ChipID).await?;
+        let registers = self
+            .read_registers(Registers::ManufacturerNameMsb, 8)
+            .await?;
+        Ok(Identity {
+            chip_id,
+            manufacturer_name: (registers[0] as u32) << 16 | registers[1] as u32,
+            manufacturing_date: registers[2],
+            first_usage_date: registers[3],
+            serial_number: (registers[4] as u32) << 16 | registers[5] as u32,
+            device_name: (registers[6] as u32) << 16 | registers[7] as u32,
+        })
+    }
+}
@@ -0,0 +1,878 @@
+//! # Secondary protection
+//! A software protection layer, independent of the thresholds programmed into the L9961,
+//! for systems which require a second mechanism able to disconnect the pack.
+//!
+//! [`SecondaryProtection`] evaluates every [`Measurement`] against its own [`SecondaryLimits`]:
+//! cell and pack over- and under-voltage, NTC and die over-temperature, charge and discharge over-current,
+//! and the plausibility of the cell voltage sum against the pack voltage.
+//! A limit trips once it has been exceeded for [`SecondaryLimits::debounce`] consecutive measurements,
+//! and stays tripped until [`SecondaryProtection::reset`] is called.
+//!
+//! [`L9961::apply_secondary_protection`] commands the FETs open through CFG3_ACT when a limit trips,
+//! verifies the result by reading CFG3_ACT back, and keeps them open while any limit is tripped.
+//! The [`SecondaryTrip`] it returns reports whether the L9961 had flagged the same faults,
+//! i.e. whether its own protection was acting as well, or whether the software layer caught a fault the device did not.
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+//! # use embedded_hal::digital::OutputPin;
+//! use l9961::{
+//!     L9961,
+//!     config::{Chemistry, NtcSensor},
+//!     secondary_protection::{ProtectionMechanism, SecondaryLimits, SecondaryProtection},
+//! };
+//!
+//! # async fn run<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//! #     mut l9961: L9961<I2C, READY, FAULTN, WAKEUP>,
+//! #     mut delay: impl DelayNs,
+//! #     sensor: NtcSensor,
+//! # ) -> Result<(), I2C::Error> {
+//! let limits = SecondaryLimits {
+//!     ntc_over_temperature_mv: Some(sensor.voltage_mv(70)),
+//!     discharge_over_current_ma: Some(30_000),
+//!     ..SecondaryLimits::for_chemistry(Chemistry::Nmc, 5)
+//! };
+//! let mut protection = SecondaryProtection::new(limits);
+//! loop {
+//!     let Some(measurement) = l9961.make_measurement(&mut delay).await? else {
+//!         continue;
+//!     };
+//!     if let Some(trip) = l9961
+//!         .apply_secondary_protection(&mut protection, &measurement)
+//!         .await?
+//!     {
+//!         if trip.mechanism == ProtectionMechanism::Software {
+//!             // The device did not flag the fault, report a primary protection failure
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, i2c::I2c};
+
+#[cfg(feature = "coulomb_counting")]
+use crate::conversions::current_ma_from_signed_code;
+use crate::{
+    L9961,
+    config::Chemistry,
+    faults::DiagnosticFlags,
+    measurement::Measurement,
+    registers::{Cfg3Act, DiagCurr, DiagOvOtUt, DiagUv},
+};
+
+#[cfg(not(feature = "defmt"))]
+use bitflags::bitflags;
+#[cfg(feature = "defmt")]
+use defmt::bitflags;
+
+bitflags! {
+    /// Limits of the secondary protection
+    pub struct SecondaryFaults:u16 {
+        /// A cell is over the cell over-voltage limit
+        const CELL_OVER_VOLTAGE = 0x0001;
+        /// A cell is under the cell under-voltage limit
+        const CELL_UNDER_VOLTAGE = 0x0002;
+        /// The pack is over the pack over-voltage limit
+        const PACK_OVER_VOLTAGE = 0x0004;
+        /// The pack is under the pack under-voltage limit
+        const PACK_UNDER_VOLTAGE = 0x0008;
+        /// The NTC is over the over-temperature limit
+        const NTC_OVER_TEMPERATURE = 0x0010;
+        /// The die is over the over-temperature limit
+        const DIE_OVER_TEMPERATURE = 0x0020;
+        /// The charge current is over the charge over-current limit
+        const CHARGE_OVER_CURRENT = 0x0040;
+        /// The discharge current is over the discharge over-current limit
+        const DISCHARGE_OVER_CURRENT = 0x0080;
+        /// The sum of the cell voltages differs from the pack voltage by more than the mismatch limit
+        const CELL_SUM_MISMATCH = 0x0100;
+    }
+}
+
+impl_display_flags!(SecondaryFaults);
+
+/// Every limit, in the order of the debounce counters
+const LIMITS: [SecondaryFaults; 9] = [
+    SecondaryFaults::CELL_OVER_VOLTAGE,
+    SecondaryFaults::CELL_UNDER_VOLTAGE,
+    SecondaryFaults::PACK_OVER_VOLTAGE,
+    SecondaryFaults::PACK_UNDER_VOLTAGE,
+    SecondaryFaults::NTC_OVER_TEMPERATURE,
+    SecondaryFaults::DIE_OVER_TEMPERATURE,
+    SecondaryFaults::CHARGE_OVER_CURRENT,
+    SecondaryFaults::DISCHARGE_OVER_CURRENT,
+    SecondaryFaults::CELL_SUM_MISMATCH,
+];
+
+impl SecondaryFaults {
+    /// Faults which require the charge FET to be open
+    const OPEN_CHARGE: Self = Self::from_bits_truncate(
+        Self::CELL_OVER_VOLTAGE.bits()
+            | Self::PACK_OVER_VOLTAGE.bits()
+            | Self::CHARGE_OVER_CURRENT.bits()
+            | Self::NTC_OVER_TEMPERATURE.bits()
+            | Self::DIE_OVER_TEMPERATURE.bits()
+            | Self::CELL_SUM_MISMATCH.bits(),
+    );
+
+    /// Faults which require the discharge FET to be open
+    const OPEN_DISCHARGE: Self = Self::from_bits_truncate(
+        Self::CELL_UNDER_VOLTAGE.bits()
+            | Self::PACK_UNDER_VOLTAGE.bits()
+            | Self::DISCHARGE_OVER_CURRENT.bits()
+            | Self::NTC_OVER_TEMPERATURE.bits()
+            | Self::DIE_OVER_TEMPERATURE.bits()
+            | Self::CELL_SUM_MISMATCH.bits(),
+    );
+
+    /// Faults which require balancing to stop, as it would discharge or heat the cells further
+    const STOP_BALANCING: Self = Self::from_bits_truncate(
+        Self::CELL_UNDER_VOLTAGE.bits()
+            | Self::PACK_UNDER_VOLTAGE.bits()
+            | Self::NTC_OVER_TEMPERATURE.bits()
+            | Self::DIE_OVER_TEMPERATURE.bits()
+            | Self::CELL_SUM_MISMATCH.bits(),
+    );
+
+    /// Whether any of the faults requires the charge FET to be open:
+    /// over-voltage, charge over-current, over-temperature or a cell sum mismatch
+    pub fn opens_charge(&self) -> bool {
+        self.intersects(Self::OPEN_CHARGE)
+    }
+
+    /// Whether any of the faults requires the discharge FET to be open:
+    /// under-voltage, discharge over-current, over-temperature or a cell sum mismatch
+    pub fn opens_discharge(&self) -> bool {
+        self.intersects(Self::OPEN_DISCHARGE)
+    }
+
+    /// Whether any of the faults requires balancing to stop:
+    /// under-voltage, over-temperature or a cell sum mismatch
+    pub fn stops_balancing(&self) -> bool {
+        self.intersects(Self::STOP_BALANCING)
+    }
+
+    /// Whether CFG3_ACT holds the FETs open and balancing stopped as the faults require
+    pub fn is_held_by(&self, cfg3_act: Cfg3Act) -> bool {
+        let mut held = cfg3_act;
+        self.hold(&mut held);
+        held == cfg3_act
+    }
+
+    /// Clear the CFG3_ACT bits the faults require to be off
+    fn hold(&self, cfg3_act: &mut Cfg3Act) {
+        if self.opens_charge() {
+            cfg3_act.set_charge_enabled(false);
+        }
+        if self.opens_discharge() {
+            cfg3_act.set_discharge_enabled(false);
+        }
+        if self.stops_balancing() {
+            cfg3_act.set_cell_1_balance_enabled(false);
+            cfg3_act.set_cell_2_balance_enabled(false);
+            cfg3_act.set_cell_3_balance_enabled(false);
+            cfg3_act.set_cell_4_balance_enabled(false);
+            cfg3_act.set_cell_5_balance_enabled(false);
+        }
+    }
+
+    /// The faults which the L9961 flagged in its diagnostic registers as well
+    fn flagged_in(&self, flags: &DiagnosticFlags) -> Self {
+        let ov_ot_ut = flags.ov_ot_ut;
+        let uv = flags.uv;
+        let curr = flags.curr;
+
+        let mut flagged = Self::empty();
+        flagged.set(
+            Self::CELL_OVER_VOLTAGE,
+            ov_ot_ut.intersects(
+                DiagOvOtUt::CELL1_OV
+                    | DiagOvOtUt::CELL2_OV
+                    | DiagOvOtUt::CELL3_OV
+                    | DiagOvOtUt::CELL4_OV
+                    | DiagOvOtUt::CELL5_OV
+                    | DiagOvOtUt::CELL1_SEVERE_OV
+                    | DiagOvOtUt::CELL2_SEVERE_OV
+                    | DiagOvOtUt::CELL3_SEVERE_OV
+                    | DiagOvOtUt::CELL4_SEVERE_OV
+                    | DiagOvOtUt::CELL5_SEVERE_OV,
+            ),
+        );
+        flagged.set(
+            Self::CELL_UNDER_VOLTAGE,
+            uv.intersects(
+                DiagUv::CELL1_UV
+                    | DiagUv::CELL2_UV
+                    | DiagUv::CELL3_UV
+                    | DiagUv::CELL4_UV
+                    | DiagUv::CELL5_UV
+                    | DiagUv::V_SEVERE_CELL1_UV
+                    | DiagUv::V_SEVERE_CELL2_UV
+                    | DiagUv::V_SEVERE_CELL3_UV
+                    | DiagUv::V_SEVERE_CELL4_UV
+                    | DiagUv::V_SEVERE_CELL5_UV,
+            ),
+        );
+        flagged.set(
+            Self::PACK_OVER_VOLTAGE,
+            ov_ot_ut.contains(DiagOvOtUt::PACK_OV),
+        );
+        flagged.set(Self::PACK_UNDER_VOLTAGE, uv.contains(DiagUv::VB_UV));
+        flagged.set(
+            Self::NTC_OVER_TEMPERATURE,
+            ov_ot_ut.intersects(DiagOvOtUt::NTC_OT | DiagOvOtUt::NTC_SEVERE_OT),
+        );
+        flagged.set(
+            Self::DIE_OVER_TEMPERATURE,
+            ov_ot_ut.contains(DiagOvOtUt::DIE_OT),
+        );
+        flagged.set(
+            Self::CHARGE_OVER_CURRENT,
+            curr.intersects(DiagCurr::OVC_CHG | DiagCurr::PERSIST_OVC_CHG),
+        );
+        flagged.set(
+            Self::DISCHARGE_OVER_CURRENT,
+            curr.intersects(
+                DiagCurr::OVC_DCHG
+                    | DiagCurr::PERSIST_OVC_DCHG
+                    | DiagCurr::SC_DCHG
+                    | DiagCurr::PERSIST_SC_DCHG,
+            ),
+        );
+        flagged.set(
+            Self::CELL_SUM_MISMATCH,
+            ov_ot_ut.contains(DiagOvOtUt::VB_SUM_CHECK_FAIL),
+        );
+        *self & flagged
+    }
+}
+
+/// Limits of the secondary protection. Each limit is optional, and `None` disables its check.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SecondaryLimits {
+    /// Number of cells in series, whose voltages are checked
+    pub cells: u8,
+    /// Cell voltage in mV above which the charge FET is opened
+    pub cell_over_voltage_mv: Option<u16>,
+    /// Cell voltage in mV below which the discharge FET is opened
+    pub cell_under_voltage_mv: Option<u16>,
+    /// Pack voltage in mV above which the charge FET is opened
+    pub pack_over_voltage_mv: Option<u16>,
+    /// Pack voltage in mV below which the discharge FET is opened
+    pub pack_under_voltage_mv: Option<u16>,
+    /// NTC voltage in mV at or below which both FETs are opened.
+    /// The NTC voltage falls as the temperature rises.
+    #[cfg(feature = "ntc")]
+    pub ntc_over_temperature_mv: Option<u16>,
+    /// Die temperature in degrees Celsius above which both FETs are opened
+    pub die_over_temperature_c: Option<u16>,
+    /// Charge current in mA above which the charge FET is opened
+    #[cfg(feature = "coulomb_counting")]
+    pub charge_over_current_ma: Option<u32>,
+    /// Discharge current in mA above which the discharge FET is opened
+    #[cfg(feature = "coulomb_counting")]
+    pub discharge_over_current_ma: Option<u32>,
+    /// Difference in mV between the sum of the cell voltages and the pack voltage above which both FETs are opened
+    pub cell_sum_mismatch_mv: Option<u16>,
+    /// Number of consecutive measurements a limit must be exceeded for before it trips.
+    /// Zero is treated as one.
+    pub debounce: u8,
+}
+
+impl SecondaryLimits {
+    /// Limits with every check disabled, tripping after 3 consecutive measurements
+    pub const fn new(cells: u8) -> Self {
+        Self {
+            cells,
+            cell_over_voltage_mv: None,
+            cell_under_voltage_mv: None,
+            pack_over_voltage_mv: None,
+            pack_under_voltage_mv: None,
+            #[cfg(feature = "ntc")]
+            ntc_over_temperature_mv: None,
+            die_over_temperature_c: None,
+            #[cfg(feature = "coulomb_counting")]
+            charge_over_current_ma: None,
+            #[cfg(feature = "coulomb_counting")]
+            discharge_over_current_ma: None,
+            cell_sum_mismatch_mv: None,
+            debounce: 3,
+        }
+    }
+
+    /// Limits for a pack of `cells` cells of the given chemistry.
+    /// The cell and pack voltage limits are placed at the severe limits of the chemistry,
+    /// beyond the thresholds of [`crate::config::VoltageThresholds::for_chemistry`],
+    /// so the device protects the pack first and the software layer acts if it does not.
+    /// The die is limited to 120 °C and the cell sum mismatch to 1 V, while the temperature of the NTC
+    /// and the current depend on the pack and are left disabled.
+    pub const fn for_chemistry(chemistry: Chemistry, cells: u8) -> Self {
+        let limits = chemistry.cell_limits();
+        Self {
+            cell_over_voltage_mv: Some(limits.severe_over_voltage_mv),
+            cell_under_voltage_mv: Some(limits.severe_under_voltage_mv),
+            pack_over_voltage_mv: Some(limits.severe_over_voltage_mv.saturating_mul(cells as u16)),
+            pack_under_voltage_mv: Some(
+                limits.severe_under_voltage_mv.saturating_mul(cells as u16),
+            ),
+            die_over_temperature_c: Some(120),
+            cell_sum_mismatch_mv: Some(1000),
+            ..Self::new(cells)
+        }
+    }
+
+    /// The limits the measurement exceeds, without debouncing
+    pub fn exceeded(&self, measurement: &Measurement) -> SecondaryFaults {
+        let mut exceeded = SecondaryFaults::empty();
+        for cell in 1..=self.cells {
+            let Some(cell) = measurement.cell(cell) else {
+                break;
+            };
+            if let Some(limit) = self.cell_over_voltage_mv
+                && cell.voltage_mv > limit
+            {
+                exceeded |= SecondaryFaults::CELL_OVER_VOLTAGE;
+            }
+            if let Some(limit) = self.cell_under_voltage_mv
+                && cell.voltage_mv < limit
+            {
+                exceeded |= SecondaryFaults::CELL_UNDER_VOLTAGE;
+            }
+        }
+        if let Some(limit) = self.pack_over_voltage_mv
+            && measurement.vbat_mv > limit
+        {
+            exceeded |= SecondaryFaults::PACK_OVER_VOLTAGE;
+        }
+        if let Some(limit) = self.pack_under_voltage_mv
+            && measurement.vbat_mv < limit
+        {
+            exceeded |= SecondaryFaults::PACK_UNDER_VOLTAGE;
+        }
+        #[cfg(feature = "ntc")]
+        if let Some(limit) = self.ntc_over_temperature_mv
+            && measurement.ntc_mv <= limit
+        {
+            exceeded |= SecondaryFaults::NTC_OVER_TEMPERATURE;
+        }
+        if let Some(limit) = self.die_over_temperature_c
+            && measurement.die_temp > limit
+        {
+            exceeded |= SecondaryFaults::DIE_OVER_TEMPERATURE;
+        }
+        #[cfg(feature = "coulomb_counting")]
+        {
+            // Positive while charging
+            let current_ma = current_ma_from_signed_code(measurement.cc_inst_meas);
+            if let Some(limit) = self.charge_over_current_ma
+                && current_ma > 0
+                && current_ma.unsigned_abs() > limit
+            {
+                exceeded |= SecondaryFaults::CHARGE_OVER_CURRENT;
+            }
+            if let Some(limit) = self.discharge_over_current_ma
+                && current_ma < 0
+                && current_ma.unsigned_abs() > limit
+            {
+                exceeded |= SecondaryFaults::DISCHARGE_OVER_CURRENT;
+            }
+        }
+        if let Some(limit) = self.cell_sum_mismatch_mv
+            && measurement.cell_sum_mv.abs_diff(measurement.vbat_mv) > limit
+        {
+            exceeded |= SecondaryFaults::CELL_SUM_MISMATCH;
+        }
+        exceeded
+    }
+}
+
+/// Which protection acted on the faults of a [`SecondaryTrip`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ProtectionMechanism {
+    /// The L9961 flagged every tripped fault in its diagnostic registers as well,
+    /// so its own protection was acting and the software layer confirmed it
+    Hardware,
+    /// The L9961 did not flag some of the tripped faults, so the software layer alone acted on them
+    Software,
+}
+
+impl core::fmt::Display for ProtectionMechanism {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::Hardware => "Hardware",
+            Self::Software => "Software",
+        })
+    }
+}
+
+/// Report of the limits which tripped at a measurement, returned by [`L9961::apply_secondary_protection`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SecondaryTrip {
+    /// The limits which tripped at this measurement
+    pub faults: SecondaryFaults,
+    /// The tripped limits which the L9961 flagged in its diagnostic registers, or its fault log held as active
+    pub flagged_by_device: SecondaryFaults,
+    /// Which protection acted
+    pub mechanism: ProtectionMechanism,
+    /// The raw CFG3_ACT value read back after commanding the FETs open
+    pub cfg3_act: u16,
+    /// Whether CFG3_ACT read back with the FETs open and balancing stopped as every tripped limit requires
+    pub verified: bool,
+}
+
+/// State of the secondary protection: its limits, the debounce counters and the tripped limits
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct SecondaryProtection {
+    limits: SecondaryLimits,
+    counters: [u8; LIMITS.len()],
+    tripped: SecondaryFaults,
+}
+
+impl SecondaryProtection {
+    /// Create the protection, with no limit tripped
+    pub const fn new(limits: SecondaryLimits) -> Self {
+        Self {
+            limits,
+            counters: [0; LIMITS.len()],
+            tripped: SecondaryFaults::empty(),
+        }
+    }
+
+    /// The limits of the protection
+    pub const fn limits(&self) -> &SecondaryLimits {
+        &self.limits
+    }
+
+    /// The limits which have tripped since the protection was created or last reset
+    pub const fn tripped(&self) -> SecondaryFaults {
+        self.tripped
+    }
+
+    /// Update the debounce counters with a measurement, returning the limits which newly tripped.
+    /// Tripped limits stay tripped until [`SecondaryProtection::reset`], even once the measurements recover.
+    pub fn evaluate(&mut self, measurement: &Measurement) -> SecondaryFaults {
+        let exceeded = self.limits.exceeded(measurement);
+        let debounce = self.limits.debounce.max(1);
+        let mut tripped = SecondaryFaults::empty();
+        for (counter, limit) in self.counters.iter_mut().zip(LIMITS) {
+            if !exceeded.contains(limit) {
+                *counter = 0;
+                continue;
+            }
+            *counter = counter.saturating_add(1);
+            if *counter >= debounce && !self.tripped.contains(limit) {
+                tripped |= limit;
+            }
+        }
+        self.tripped |= tripped;
+        tripped
+    }
+
+    /// Clear the tripped limits and the debounce counters, once the cause of the trip has been dealt with.
+    /// The FETs are not closed again, which is left to the application.
+    pub fn reset(&mut self) {
+        self.counters = [0; LIMITS.len()];
+        self.tripped = SecondaryFaults::empty();
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Evaluate a measurement with the secondary protection, and command the FETs open through CFG3_ACT
+    /// as the tripped limits require, verifying the result by reading CFG3_ACT back.
+    /// Returns a report if a limit newly tripped at this measurement.
+    /// The diagnostic registers are read when a limit trips to tell which protection acted,
+    /// as the flags read with the measurement may have been cleared several measurements earlier.
+    ///
+    /// While any limit is tripped CFG3_ACT is checked after every measurement,
+    /// and FETs or balancing which were enabled again are switched back off.
+    pub async fn apply_secondary_protection(
+        &mut self,
+        protection: &mut SecondaryProtection,
+        measurement: &Measurement,
+    ) -> Result<Option<SecondaryTrip>, I2C::Error> {
+        let faults = protection.evaluate(measurement);
+        let tripped = protection.tripped();
+        if tripped.is_empty() {
+            return Ok(None);
+        }
+
+        let cfg3_act = self.read_cfg3_act().await?;
+        if faults.is_empty() && tripped.is_held_by(cfg3_act) {
+            return Ok(None);
+        }
+        let mut held = cfg3_act;
+        tripped.hold(&mut held);
+        self.write_cfg3_act(held).await?;
+        let cfg3_act = self.read_cfg3_act().await?;
+        let verified = tripped.is_held_by(cfg3_act);
+
+        if faults.is_empty() {
+            warn!(
+                "Secondary protection reopened the FETs for {}, CFG3_ACT reads {}",
+                tripped, *cfg3_act
+            );
+            return Ok(None);
+        }
+        // The flags of the measurement are cleared once read, so by the time a limit trips after debouncing
+        // they are gone. Faults which persist latch again, and the fault log holds those it has recorded.
+        let flags = self
+            .read_diagnostic_flags()
+            .await?
+            .union(&self.fault_log.active());
+        let flagged_by_device = faults.flagged_in(&flags);
+        let mechanism = if flagged_by_device == faults {
+            ProtectionMechanism::Hardware
+        } else {
+            ProtectionMechanism::Software
+        };
+        warn!(
+            "Secondary protection tripped on {} ({}), CFG3_ACT reads {}",
+            faults, mechanism, *cfg3_act
+        );
+        Ok(Some(SecondaryTrip {
+            faults,
+            flagged_by_device,
+            mechanism,
+            cfg3_act: *cfg3_act,
+            verified,
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELLS: u8 = 3;
+
+    /// Every cell at 3.7 V, with a matching pack voltage, a cool die and no current
+    fn nominal() -> Measurement {
+        let mut measurement = Measurement::default();
+        measurement.cell_1.voltage_mv = 3700;
+        measurement.cell_2.voltage_mv = 3700;
+        measurement.cell_3.voltage_mv = 3700;
+        measurement.cell_sum_mv = 11100;
+        measurement.vbat_mv = 11100;
+        measurement.die_temp = 25;
+        #[cfg(feature = "ntc")]
+        {
+            measurement.ntc_mv = 900;
+        }
+        measurement
+    }
+
+    fn over_voltage() -> Measurement {
+        let mut measurement = nominal();
+        measurement.cell_1.voltage_mv = 4300;
+        measurement
+    }
+
+    fn limits() -> SecondaryLimits {
+        SecondaryLimits {
+            cell_over_voltage_mv: Some(4200),
+            cell_under_voltage_mv: Some(3000),
+            pack_over_voltage_mv: Some(12600),
+            pack_under_voltage_mv: Some(9000),
+            #[cfg(feature = "ntc")]
+            ntc_over_temperature_mv: Some(300),
+            die_over_temperature_c: Some(100),
+            cell_sum_mismatch_mv: Some(500),
+            ..SecondaryLimits::new(CELLS)
+        }
+    }
+
+    fn exceeded(
+        limits: &SecondaryLimits,
+        update: impl FnOnce(&mut Measurement),
+    ) -> SecondaryFaults {
+        let mut measurement = nominal();
+        update(&mut measurement);
+        limits.exceeded(&measurement)
+    }
+
+    #[test]
+    fn limits_are_exceeded_beyond_their_boundary() {
+        let limits = limits();
+        let none = SecondaryFaults::empty();
+        assert_eq!(limits.exceeded(&nominal()), none);
+
+        assert_eq!(exceeded(&limits, |m| m.cell_3.voltage_mv = 4200), none);
+        assert_eq!(
+            exceeded(&limits, |m| m.cell_3.voltage_mv = 4201),
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+        assert_eq!(exceeded(&limits, |m| m.cell_2.voltage_mv = 3000), none);
+        assert_eq!(
+            exceeded(&limits, |m| m.cell_2.voltage_mv = 2999),
+            SecondaryFaults::CELL_UNDER_VOLTAGE
+        );
+
+        let pack_mv = |mv: u16| {
+            move |m: &mut Measurement| {
+                m.vbat_mv = mv;
+                m.cell_sum_mv = mv;
+            }
+        };
+        assert_eq!(exceeded(&limits, pack_mv(12600)), none);
+        assert_eq!(
+            exceeded(&limits, pack_mv(12601)),
+            SecondaryFaults::PACK_OVER_VOLTAGE
+        );
+        assert_eq!(exceeded(&limits, pack_mv(9000)), none);
+        assert_eq!(
+            exceeded(&limits, pack_mv(8999)),
+            SecondaryFaults::PACK_UNDER_VOLTAGE
+        );
+
+        #[cfg(feature = "ntc")]
+        {
+            assert_eq!(exceeded(&limits, |m| m.ntc_mv = 301), none);
+            assert_eq!(
+                exceeded(&limits, |m| m.ntc_mv = 300),
+                SecondaryFaults::NTC_OVER_TEMPERATURE
+            );
+        }
+
+        assert_eq!(exceeded(&limits, |m| m.die_temp = 100), none);
+        assert_eq!(
+            exceeded(&limits, |m| m.die_temp = 101),
+            SecondaryFaults::DIE_OVER_TEMPERATURE
+        );
+
+        assert_eq!(exceeded(&limits, |m| m.cell_sum_mv = 11600), none);
+        assert_eq!(
+            exceeded(&limits, |m| m.cell_sum_mv = 11601),
+            SecondaryFaults::CELL_SUM_MISMATCH
+        );
+        assert_eq!(exceeded(&limits, |m| m.vbat_mv = 11600), none);
+        assert_eq!(
+            exceeded(&limits, |m| m.vbat_mv = 11601),
+            SecondaryFaults::CELL_SUM_MISMATCH
+        );
+    }
+
+    #[cfg(feature = "coulomb_counting")]
+    #[test]
+    fn current_limits_are_exceeded_beyond_their_boundary() {
+        let code = 1000;
+        let limit_ma = current_ma_from_signed_code(code).unsigned_abs();
+        let limits = SecondaryLimits {
+            charge_over_current_ma: Some(limit_ma),
+            discharge_over_current_ma: Some(limit_ma),
+            ..limits()
+        };
+        let none = SecondaryFaults::empty();
+        assert_eq!(exceeded(&limits, |m| m.cc_inst_meas = code), none);
+        assert_eq!(exceeded(&limits, |m| m.cc_inst_meas = -code), none);
+
+        let limits = SecondaryLimits {
+            charge_over_current_ma: Some(limit_ma - 1),
+            discharge_over_current_ma: Some(limit_ma - 1),
+            ..limits
+        };
+        assert_eq!(
+            exceeded(&limits, |m| m.cc_inst_meas = code),
+            SecondaryFaults::CHARGE_OVER_CURRENT
+        );
+        assert_eq!(
+            exceeded(&limits, |m| m.cc_inst_meas = -code),
+            SecondaryFaults::DISCHARGE_OVER_CURRENT
+        );
+    }
+
+    #[test]
+    fn limits_trip_once_debounced() {
+        let mut protection = SecondaryProtection::new(limits());
+        assert!(protection.evaluate(&over_voltage()).is_empty());
+        assert!(protection.evaluate(&over_voltage()).is_empty());
+        // A measurement within the limits restarts the count
+        assert!(protection.evaluate(&nominal()).is_empty());
+        assert!(protection.evaluate(&over_voltage()).is_empty());
+        assert!(protection.evaluate(&over_voltage()).is_empty());
+        assert_eq!(
+            protection.evaluate(&over_voltage()),
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+        assert_eq!(protection.tripped(), SecondaryFaults::CELL_OVER_VOLTAGE);
+
+        let mut protection = SecondaryProtection::new(SecondaryLimits {
+            debounce: 0,
+            ..limits()
+        });
+        assert_eq!(
+            protection.evaluate(&over_voltage()),
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+    }
+
+    #[test]
+    fn tripped_limits_latch_until_reset() {
+        let mut protection = SecondaryProtection::new(SecondaryLimits {
+            debounce: 1,
+            ..limits()
+        });
+        assert_eq!(
+            protection.evaluate(&over_voltage()),
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+        // Only newly tripped limits are returned
+        assert!(protection.evaluate(&over_voltage()).is_empty());
+        assert!(protection.evaluate(&nominal()).is_empty());
+        assert_eq!(protection.tripped(), SecondaryFaults::CELL_OVER_VOLTAGE);
+
+        protection.reset();
+        assert!(protection.tripped().is_empty());
+        assert!(protection.evaluate(&nominal()).is_empty());
+        assert_eq!(
+            protection.evaluate(&over_voltage()),
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+    }
+
+    #[test]
+    fn faults_hold_their_fets_and_balancing_off() {
+        let all_on = Cfg3Act::new(true, true, true, true, true, true, true);
+        let all_off = Cfg3Act::new(false, false, false, false, false, false, false);
+        // Fault, then whether charging, discharging and balancing stay enabled
+        let cases = [
+            (SecondaryFaults::CELL_OVER_VOLTAGE, false, true, true),
+            (SecondaryFaults::CELL_UNDER_VOLTAGE, true, false, false),
+            (SecondaryFaults::PACK_OVER_VOLTAGE, false, true, true),
+            (SecondaryFaults::PACK_UNDER_VOLTAGE, true, false, false),
+            (SecondaryFaults::NTC_OVER_TEMPERATURE, false, false, false),
+            (SecondaryFaults::DIE_OVER_TEMPERATURE, false, false, false),
+            (SecondaryFaults::CHARGE_OVER_CURRENT, false, true, true),
+            (SecondaryFaults::DISCHARGE_OVER_CURRENT, true, false, true),
+            (SecondaryFaults::CELL_SUM_MISMATCH, false, false, false),
+        ];
+        for (fault, charge, discharge, balancing) in cases {
+            let mut held = all_on;
+            fault.hold(&mut held);
+            assert_eq!(held.get_charge_enabled(), charge, "{}", fault);
+            assert_eq!(held.get_discharge_enabled(), discharge, "{}", fault);
+            for enabled in [
+                held.get_cell_1_balance_enabled(),
+                held.get_cell_2_balance_enabled(),
+                held.get_cell_3_balance_enabled(),
+                held.get_cell_4_balance_enabled(),
+                held.get_cell_5_balance_enabled(),
+            ] {
+                assert_eq!(enabled, balancing, "{}", fault);
+            }
+            assert!(fault.is_held_by(held), "{}", fault);
+            assert!(fault.is_held_by(all_off), "{}", fault);
+            assert!(!fault.is_held_by(all_on), "{}", fault);
+        }
+    }
+
+    #[test]
+    fn faults_are_matched_to_the_device_flags() {
+        let flags = |ov_ot_ut: DiagOvOtUt, uv: DiagUv, curr: DiagCurr| DiagnosticFlags {
+            ov_ot_ut,
+            uv,
+            curr,
+        };
+        let none = (DiagOvOtUt::empty(), DiagUv::empty(), DiagCurr::empty());
+        let cases = [
+            (
+                SecondaryFaults::CELL_OVER_VOLTAGE,
+                flags(DiagOvOtUt::CELL2_SEVERE_OV, none.1, none.2),
+            ),
+            (
+                SecondaryFaults::CELL_UNDER_VOLTAGE,
+                flags(none.0, DiagUv::CELL3_UV, none.2),
+            ),
+            (
+                SecondaryFaults::PACK_OVER_VOLTAGE,
+                flags(DiagOvOtUt::PACK_OV, none.1, none.2),
+            ),
+            (
+                SecondaryFaults::PACK_UNDER_VOLTAGE,
+                flags(none.0, DiagUv::VB_UV, none.2),
+            ),
+            (
+                SecondaryFaults::NTC_OVER_TEMPERATURE,
+                flags(DiagOvOtUt::NTC_SEVERE_OT, none.1, none.2),
+            ),
+            (
+                SecondaryFaults::DIE_OVER_TEMPERATURE,
+                flags(DiagOvOtUt::DIE_OT, none.1, none.2),
+            ),
+            (
+                SecondaryFaults::CHARGE_OVER_CURRENT,
+                flags(none.0, none.1, DiagCurr::PERSIST_OVC_CHG),
+            ),
+            (
+                SecondaryFaults::DISCHARGE_OVER_CURRENT,
+                flags(none.0, none.1, DiagCurr::SC_DCHG),
+            ),
+            (
+                SecondaryFaults::CELL_SUM_MISMATCH,
+                flags(DiagOvOtUt::VB_SUM_CHECK_FAIL, none.1, none.2),
+            ),
+        ];
+        for (fault, flags) in cases {
+            assert_eq!(fault.flagged_in(&flags), fault, "{}", fault);
+            assert!(fault.flagged_in(&DiagnosticFlags::empty()).is_empty());
+            // Only the faults which were tripped are reported as flagged
+            assert_eq!(
+                SecondaryFaults::empty().flagged_in(&flags),
+                SecondaryFaults::empty()
+            );
+        }
+    }
+
+    // defmt has no global logger in tests, so the driver is only exercised without it
+    #[cfg(all(feature = "std", not(feature = "defmt")))]
+    #[test]
+    fn trip_reports_which_protection_acted() {
+        use crate::{Config, NoPin, Registers, simulator::Simulator};
+
+        let trip = |diag_ov_ot_ut: DiagOvOtUt| {
+            let mut simulator = Simulator::with_nominal_measurements();
+            simulator.set_register(Registers::DiagOvOtUt, diag_ov_ot_ut.bits());
+            let mut l9961 = L9961::new_with_optional_pins(
+                simulator,
+                None::<NoPin>,
+                None::<NoPin>,
+                None::<NoPin>,
+                Config::default(),
+            );
+            let mut protection = SecondaryProtection::new(SecondaryLimits {
+                debounce: 1,
+                ..limits()
+            });
+            embassy_futures::block_on(
+                l9961.apply_secondary_protection(&mut protection, &over_voltage()),
+            )
+            .unwrap()
+            .unwrap()
+        };
+
+        let trip_by_device = trip(DiagOvOtUt::CELL1_OV);
+        assert_eq!(trip_by_device.mechanism, ProtectionMechanism::Hardware);
+        assert_eq!(
+            trip_by_device.flagged_by_device,
+            SecondaryFaults::CELL_OVER_VOLTAGE
+        );
+        assert!(trip_by_device.verified);
+
+        let trip_by_software = trip(DiagOvOtUt::empty());
+        assert_eq!(trip_by_software.mechanism, ProtectionMechanism::Software);
+        assert!(trip_by_software.flagged_by_device.is_empty());
+        assert!(trip_by_software.verified);
+    }
+}
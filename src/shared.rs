@@ -0,0 +1,223 @@
+//! # Shared driver handle
+//! Access to a single L9961 from several tasks, e.g. measurement, balancing, FET control and telemetry.
+//!
+//! [`SharedL9961`] holds the driver behind an async mutex, so every I2C transaction is serialized.
+//! Commands are issued by locking the driver with [`SharedL9961::lock`], which gives access to every method of [`L9961`].
+//!
+//! The READY and FAULTN pins are moved out of the driver into the handle,
+//! so a single task at a time waits on them through [`SharedL9961::make_measurement`]
+//! or [`SharedL9961::make_logged_measurement`], and the driver is only locked once a measurement has completed.
+//! Other tasks are therefore not held up for a whole measurement cycle while the device is measuring.
+//! Every measurement is published to the receivers of [`SharedL9961::measurement_receiver`],
+//! and every read of the diagnostic flags to the receivers of [`SharedL9961::fault_receiver`].
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+//! # use embedded_hal::digital::OutputPin;
+//! use embassy_sync::blocking_mutex::raw::CriticalSectionRawMutex;
+//! use l9961::{registers::Cfg3Act, shared::SharedL9961};
+//!
+//! type Shared<I2C, READY, FAULTN, WAKEUP> =
+//!     SharedL9961<CriticalSectionRawMutex, I2C, READY, FAULTN, WAKEUP>;
+//!
+//! // The only task waiting on READY and FAULTN
+//! async fn measurement_task<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//!     shared: &Shared<I2C, READY, FAULTN, WAKEUP>,
+//!     delay: &mut impl DelayNs,
+//! ) -> Result<(), I2C::Error> {
+//!     loop {
+//!         shared.make_measurement(delay).await?;
+//!     }
+//! }
+//!
+//! // Open the charge FET while the pack is full
+//! async fn charge_task<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//!     shared: &Shared<I2C, READY, FAULTN, WAKEUP>,
+//! ) -> Result<(), I2C::Error> {
+//!     let mut measurements = shared.measurement_receiver().unwrap();
+//!     loop {
+//!         let full = measurements.changed().await.cell_sum_mv >= 21_000;
+//!         let cfg3_act = Cfg3Act::new(false, false, false, false, false, !full, true);
+//!         shared.lock().await.write_cfg3_act(cfg3_act).await?;
+//!     }
+//! }
+//! ```
+//!
+//! Methods of the locked driver which wait on READY or FAULTN do not see them while they are held by the handle,
+//! and fall back as if they were not connected, see the [`crate::pins`] module:
+//! measurements through the locked driver wait for the measurement period,
+//! and waking the device through it applies the full 100 ms pulse.
+//! Use the methods of the handle instead, or [`SharedL9961::into_inner`] to return the pins to the driver.
+
+use embassy_sync::{
+    blocking_mutex::raw::RawMutex,
+    mutex::{Mutex, MutexGuard},
+    watch::{Receiver, Watch},
+};
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961,
+    faults::DiagnosticFlags,
+    measurement::{Measurement, MeasurementSignal, wait_for_cycle_end, wait_for_signal},
+    pulse_wakeup,
+};
+
+/// Default number of receivers of each kind of update
+pub const DEFAULT_RECEIVERS: usize = 4;
+
+/// The pins which signal measurements, held by the handle rather than the driver
+struct SignalPins<READY, FAULTN> {
+    ready: Option<READY>,
+    fault: Option<FAULTN>,
+}
+
+/// Handle sharing an [`L9961`] between tasks, see the [module documentation](self).
+///
+/// `M` selects the raw mutex used for synchronization, e.g. `CriticalSectionRawMutex` to share the handle
+/// between executors or interrupt priorities, or `NoopRawMutex` within a single executor.
+/// `N` is the maximum number of receivers of measurement updates, and separately of fault updates.
+pub struct SharedL9961<M, I2C, READY, FAULTN, WAKEUP, const N: usize = DEFAULT_RECEIVERS>
+where
+    M: RawMutex,
+{
+    driver: Mutex<M, L9961<I2C, READY, FAULTN, WAKEUP>>,
+    pins: Mutex<M, SignalPins<READY, FAULTN>>,
+    measurements: Watch<M, Measurement, N>,
+    faults: Watch<M, DiagnosticFlags, N>,
+}
+
+impl<M, I2C, READY, FAULTN, WAKEUP, const N: usize> SharedL9961<M, I2C, READY, FAULTN, WAKEUP, N>
+where
+    M: RawMutex,
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Share a driver, moving its READY and FAULTN pins into the handle.
+    /// FAULTN is not monitored if the [`crate::FaultMonitor`] has already been taken.
+    pub fn new(mut driver: L9961<I2C, READY, FAULTN, WAKEUP>) -> Self {
+        let pins = SignalPins {
+            ready: driver.ready.take(),
+            fault: driver.fault.take(),
+        };
+        Self {
+            driver: Mutex::new(driver),
+            pins: Mutex::new(pins),
+            measurements: Watch::new(),
+            faults: Watch::new(),
+        }
+    }
+
+    /// Lock the driver to issue commands.
+    /// Other tasks wait for the guard to be dropped before accessing the device,
+    /// so it should be held for as short a time as possible.
+    pub async fn lock(&self) -> MutexGuard<'_, M, L9961<I2C, READY, FAULTN, WAKEUP>> {
+        self.driver.lock().await
+    }
+
+    /// Wait for the device to complete a measurement, as [`L9961::make_measurement`] does,
+    /// and publish the measurement and any diagnostic flags which were read.
+    ///
+    /// Only one task at a time waits on READY and FAULTN, so concurrent calls are served one after the other,
+    /// each receiving the next measurement. Usually a single task makes measurements and the others receive them.
+    pub async fn make_measurement(
+        &self,
+        delay: &mut impl DelayNs,
+    ) -> Result<Option<Measurement>, I2C::Error> {
+        self.measure(delay, None::<fn() -> u64>).await
+    }
+
+    /// Wait for the device to complete a measurement and record any fault onsets or clears in the fault log,
+    /// as [`L9961::make_logged_measurement`] does, and publish the measurement and any diagnostic flags which were read.
+    pub async fn make_logged_measurement(
+        &self,
+        delay: &mut impl DelayNs,
+        now: impl FnOnce() -> u64,
+    ) -> Result<Option<Measurement>, I2C::Error> {
+        self.measure(delay, Some(now)).await
+    }
+
+    /// Wake up the device if it is asleep, as [`L9961::wake_if_asleep`] does.
+    /// This waits for any measurement in progress to complete.
+    pub async fn wake_if_asleep(&self, delay: &mut impl DelayNs) {
+        let mut pins = self.pins.lock().await;
+        let mut driver = self.driver.lock().await;
+        pulse_wakeup(driver.wake.as_mut(), pins.ready.as_mut(), delay).await;
+    }
+
+    /// A receiver of every measurement made through the handle,
+    /// or `None` if all `N` receivers have been handed out
+    pub fn measurement_receiver(&self) -> Option<Receiver<'_, M, Measurement, N>> {
+        self.measurements.receiver()
+    }
+
+    /// A receiver of the diagnostic flags, published every time they are read while measuring.
+    /// Flags with no faults set are published when a logged measurement finds that every fault has cleared.
+    /// Returns `None` if all `N` receivers have been handed out.
+    pub fn fault_receiver(&self) -> Option<Receiver<'_, M, DiagnosticFlags, N>> {
+        self.faults.receiver()
+    }
+
+    /// The most recent measurement, if any has been made
+    pub fn latest_measurement(&self) -> Option<Measurement> {
+        self.measurements.try_get()
+    }
+
+    /// Return the driver, with READY and FAULTN restored to it
+    pub fn into_inner(self) -> L9961<I2C, READY, FAULTN, WAKEUP> {
+        let pins = self.pins.into_inner();
+        let mut driver = self.driver.into_inner();
+        driver.ready = pins.ready;
+        driver.fault = pins.fault;
+        driver
+    }
+
+    /// Wait on the pins without holding the driver, then lock it to read the measurement.
+    /// Faults are logged if `now` is provided.
+    async fn measure(
+        &self,
+        delay: &mut impl DelayNs,
+        now: Option<impl FnOnce() -> u64>,
+    ) -> Result<Option<Measurement>, I2C::Error> {
+        let mut pins = self.pins.lock().await;
+        let (cycle_time, read_diagnostics) = {
+            let driver = self.driver.lock().await;
+            let read_diagnostics = now.is_some() && !driver.fault_log.active().is_empty();
+            (driver.measurement_cycle_ms(), read_diagnostics)
+        };
+
+        let pins = &mut *pins;
+        let signal =
+            wait_for_signal(pins.ready.as_mut(), pins.fault.as_mut(), delay, cycle_time).await;
+        let result = {
+            let mut driver = self.driver.lock().await;
+            let result = driver
+                .read_signalled_measurement(signal, read_diagnostics)
+                .await?;
+            if let (Some((measurement, Some(faults))), Some(now)) = (&result, now) {
+                driver.fault_log.record_cleared(
+                    now(),
+                    faults.latched,
+                    faults.still_active,
+                    Some(measurement),
+                );
+            }
+            result
+        };
+        if signal == MeasurementSignal::Fault {
+            wait_for_cycle_end(pins.ready.as_mut(), delay, cycle_time).await;
+        }
+
+        let Some((measurement, faults)) = result else {
+            return Ok(None);
+        };
+        if let Some(faults) = faults {
+            self.faults.sender().send(faults.latched);
+        }
+        self.measurements.sender().send(measurement);
+        Ok(Some(measurement))
+    }
+}
@@ -0,0 +1,242 @@
+//! # Ship mode
+//! Workflow for storing a pack in ship mode, the lowest power state of the L9961, and for waking it again.
+//!
+//! [`L9961::enter_ship_mode`] prepares the device before sending GO2SHIP:
+//! the FETs are opened and balancing stopped, which is verified by reading CFG3_ACT back,
+//! and the configuration is optionally committed to NVM, which is verified by polling the NVM upload counter
+//! while the NVM is programmed.
+//! If any step fails the device is left awake and the reason is returned.
+//! Otherwise the configuration registers are captured,
+//! so [`L9961::wake_with_report`] can tell whether the device kept them while asleep.
+//!
+//! Waking from ship mode reloads the configuration from NVM,
+//! so a configuration which was not committed is lost and must be applied again.
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+//! # use embedded_hal::digital::OutputPin;
+//! use l9961::{
+//!     L9961,
+//!     ship_mode::{ConfigurationState, ShipEntry, ShipOptions},
+//! };
+//!
+//! # async fn run<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//! #     mut l9961: L9961<I2C, READY, FAULTN, WAKEUP>,
+//! #     mut delay: impl DelayNs,
+//! # ) -> Result<(), I2C::Error> {
+//! let options = ShipOptions { commit_to_nvm: true };
+//! if let ShipEntry::Entered { configuration } = l9961.enter_ship_mode(&mut delay, options).await? {
+//!     // ... until the pack is taken out of storage
+//!     let report = l9961.wake_with_report(&mut delay, &configuration).await?;
+//!     if report.configuration != ConfigurationState::Retained {
+//!         l9961.apply_config().await?;
+//!     }
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use core::ops::Range;
+
+use embedded_hal::{
+    digital::OutputPin,
+    i2c::{Error, ErrorKind},
+};
+use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+
+use crate::{
+    L9961, Registers,
+    registers::{Cfg3Act, RegisterSnapshot, VCell1Faults, regmap::nvm_1::NVM_UPLOADS_COUNT},
+};
+
+/// Registers holding the configuration of the device, which are committed to and reloaded from NVM
+const CONFIGURATION_REGISTERS: Range<usize> = Registers::Cfg3Act as usize..Registers::Nvm1 as usize;
+/// Interval between reads of the NVM upload counter while the NVM is programmed
+const NVM_POLL_INTERVAL_MS: u32 = 5;
+/// Time after which an NVM upload which has not been counted is considered failed
+const NVM_UPLOAD_TIMEOUT_MS: u32 = 100;
+
+/// Options for entering ship mode
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ShipOptions {
+    /// Upload the configuration registers to NVM before entering ship mode,
+    /// so the configuration is restored on waking
+    pub commit_to_nvm: bool,
+}
+
+/// Outcome of [`L9961::enter_ship_mode`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ShipEntry {
+    /// GO2SHIP was sent
+    Entered {
+        /// The configuration registers before entering ship mode, to compare against on waking
+        configuration: RegisterSnapshot,
+    },
+    /// CFG3_ACT still enabled a FET or balancing after being cleared, so ship mode was not entered
+    FetsNotOpen {
+        /// The raw CFG3_ACT value read back
+        cfg3_act: u16,
+    },
+    /// The NVM upload counter did not increase, so the upload failed and ship mode was not entered
+    NvmCommitFailed {
+        /// The number of NVM uploads reported by the device
+        uploads: u8,
+    },
+}
+
+/// How the configuration registers compare to the ones captured before the device went to sleep
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ConfigurationState {
+    /// Every configuration register holds the value it had before
+    Retained,
+    /// Some configuration registers differ, e.g. because they were reloaded from NVM
+    Changed {
+        /// The number of configuration registers which differ
+        registers: u8,
+    },
+    /// The device did not respond, so its configuration could not be read
+    Unknown,
+}
+
+/// What was observed while waking the device through the WAKEUP pin
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WakeReport {
+    /// Whether READY toggled within 100 ms of asserting WAKEUP.
+    /// READY does not toggle if the device was already awake with measurements disabled.
+    pub ready: bool,
+    /// Whether the device acknowledged its configured address.
+    /// A device which reloaded a different address from NVM does not respond.
+    pub responding: bool,
+    /// State of the configuration registers
+    pub configuration: ConfigurationState,
+    /// Whether the CRC of the configuration reloaded from NVM failed
+    pub crc_cfg_fail: bool,
+}
+
+impl WakeReport {
+    /// Whether the device is awake and holds the configuration it had before going to sleep
+    pub const fn is_ready_for_use(&self) -> bool {
+        self.responding
+            && !self.crc_cfg_fail
+            && matches!(self.configuration, ConfigurationState::Retained)
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Open the FETs, stop balancing and optionally commit the configuration to NVM, then send GO2SHIP.
+    /// Ship mode is only entered once every step has been verified.
+    /// The NVM upload counter is polled until it increases, for up to 100 ms while the NVM is programmed.
+    pub async fn enter_ship_mode(
+        &mut self,
+        delay: &mut impl DelayNs,
+        options: ShipOptions,
+    ) -> Result<ShipEntry, I2C::Error> {
+        self.write_cfg3_act(Cfg3Act::new(
+            false, false, false, false, false, false, false,
+        ))
+        .await?;
+        let cfg3_act = self.read_cfg3_act().await?;
+        if !cfg3_act.is_all_off() {
+            warn!("Ship mode not entered, CFG3_ACT reads {}", *cfg3_act);
+            return Ok(ShipEntry::FetsNotOpen {
+                cfg3_act: *cfg3_act,
+            });
+        }
+
+        if options.commit_to_nvm {
+            let before = self.read_nvm_uploads().await?;
+            self.upload_configuration_to_nvm().await?;
+            let uploads = self.wait_for_nvm_upload(delay, before).await?;
+            if uploads <= before {
+                warn!(
+                    "Ship mode not entered, NVM upload count stayed at {}",
+                    uploads
+                );
+                return Ok(ShipEntry::NvmCommitFailed { uploads });
+            }
+        }
+
+        let configuration = self.read_register_snapshot().await?;
+        info!("Entering ship mode");
+        self.go_2_ship().await?;
+        Ok(ShipEntry::Entered { configuration })
+    }
+
+    /// Wake the device from ship mode or standby through the WAKEUP pin,
+    /// and compare its configuration registers against those captured before it went to sleep
+    pub async fn wake_with_report(
+        &mut self,
+        delay: &mut impl DelayNs,
+        before: &RegisterSnapshot,
+    ) -> Result<WakeReport, I2C::Error> {
+        let ready = self.pulse_wakeup(delay).await;
+        let after = match self.read_register_snapshot().await {
+            Ok(snapshot) => snapshot,
+            Err(error) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => {
+                return Ok(WakeReport {
+                    ready,
+                    responding: false,
+                    configuration: ConfigurationState::Unknown,
+                    crc_cfg_fail: false,
+                });
+            }
+            Err(error) => return Err(error),
+        };
+
+        let changed = before.raw_values()[CONFIGURATION_REGISTERS]
+            .iter()
+            .zip(&after.raw_values()[CONFIGURATION_REGISTERS])
+            .filter(|(before, after)| before != after)
+            .count() as u8;
+        let configuration = match changed {
+            0 => ConfigurationState::Retained,
+            registers => ConfigurationState::Changed { registers },
+        };
+        let crc_cfg_fail = after.get::<VCell1Faults>().get_crc_cfg_fail();
+        Ok(WakeReport {
+            ready,
+            responding: true,
+            configuration,
+            crc_cfg_fail,
+        })
+    }
+
+    /// Read the number of uploads to NVM
+    async fn read_nvm_uploads(&mut self) -> Result<u8, I2C::Error> {
+        let nvm_1 = self.read_register(Registers::Nvm1).await?;
+        Ok(NVM_UPLOADS_COUNT.get(nvm_1) as u8)
+    }
+
+    /// Poll the number of uploads to NVM until it rises above `before` or the upload times out,
+    /// returning the last count read.
+    /// The device may not acknowledge while it programs the NVM, which is retried until the timeout.
+    async fn wait_for_nvm_upload(
+        &mut self,
+        delay: &mut impl DelayNs,
+        before: u8,
+    ) -> Result<u8, I2C::Error> {
+        let mut waited_ms = 0;
+        loop {
+            delay.delay_ms(NVM_POLL_INTERVAL_MS).await;
+            waited_ms += NVM_POLL_INTERVAL_MS;
+            let timed_out = waited_ms >= NVM_UPLOAD_TIMEOUT_MS;
+            match self.read_nvm_uploads().await {
+                Ok(uploads) if uploads > before || timed_out => return Ok(uploads),
+                Ok(_) => (),
+                Err(error) if timed_out => return Err(error),
+                Err(error) if matches!(error.kind(), ErrorKind::NoAcknowledge(_)) => (),
+                Err(error) => return Err(error),
+            }
+        }
+    }
+}
@@ -0,0 +1,298 @@
+//! # Simulator
+//! A simulated L9961 on an I2C bus, for developing and testing host tooling and application logic without hardware.
+//!
+//! The simulator implements the register map: writes are limited to the writable bits of each register,
+//! latched (RLW) bits are cleared by writing 1, NVM uploads and downloads copy the configuration registers,
+//! and the GO2SHIP, GO2STBY and fuse commands written to VCELL1 to VCELL4 are recorded rather than stored.
+//! Measurement registers hold values set through the simulator, and faults can be injected by setting
+//! the diagnostic registers directly.
+//! CRC is not simulated.
+
+use embedded_hal::i2c::{ErrorKind, ErrorType, NoAcknowledgeSource, Operation};
+
+use crate::{
+    Registers,
+    conversions::{
+        cell_voltage_measurement_code_from_mv, ntc_voltage_code_from_mv,
+        pack_voltage_measurement_code_from_mv,
+    },
+    registers::{
+        Access, Field, REGISTER_COUNT, REGISTERS, RegisterSnapshot,
+        regmap::{
+            cc_inst_meas, dev_addr, die_temp, ntc_gpio, nvm_1, vb, vcell1, vcell2, vcell3, vcell4,
+            vcell5, vcellsum,
+        },
+    },
+};
+
+/// NVM_2 command which uploads the configuration registers to NVM
+const NVM_UPLOAD: u16 = 0xAAAA;
+/// NVM_2 command which downloads the configuration registers from NVM
+const NVM_DOWNLOAD: u16 = 0x5555;
+/// Value of the 2 bit command fields in VCELL1 to VCELL4 which triggers the command
+const COMMAND: u16 = 0b10;
+/// Registers which are copied to and from NVM
+const NVM_REGISTERS: core::ops::Range<usize> =
+    Registers::Cfg3Act as usize..Registers::Nvm1 as usize;
+
+/// Power state of the simulated device
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum PowerMode {
+    /// Measuring and protecting
+    Active,
+    /// Entered through GO2STBY
+    Standby,
+    /// Entered through GO2SHIP
+    Ship,
+}
+
+/// A simulated L9961
+#[derive(Clone, Debug)]
+pub struct Simulator {
+    address: u8,
+    registers: [u16; REGISTER_COUNT],
+    nvm: [u16; REGISTER_COUNT],
+    pointer: usize,
+    power_mode: PowerMode,
+    fuse_armed: bool,
+    fuse_blown: bool,
+}
+
+impl Default for Simulator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Simulator {
+    /// Create a simulated device at the default address, with every register at its reset value
+    pub fn new() -> Self {
+        let registers = *RegisterSnapshot::reset_values().raw_values();
+        Self {
+            address: dev_addr::DEV_ADDR_ID.get(dev_addr::RESET_VALUE) as u8,
+            registers,
+            nvm: registers,
+            pointer: 0,
+            power_mode: PowerMode::Active,
+            fuse_armed: false,
+            fuse_blown: false,
+        }
+    }
+
+    /// Create a simulated device with 3.7V cells, at 25 degrees Celsius and without current flow
+    pub fn with_nominal_measurements() -> Self {
+        let mut simulator = Self::new();
+        simulator.set_cell_voltages_mv(&[3700; 5]);
+        simulator.set_ntc_mv(900);
+        simulator.set_die_temp_celsius(25);
+        simulator
+    }
+
+    /// The I2C address the device currently responds to
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// The raw value of a register
+    pub fn register(&self, register: Registers) -> u16 {
+        self.registers[register as usize]
+    }
+
+    /// Set the raw value of a register, bypassing its access rules, e.g. to inject faults
+    pub fn set_register(&mut self, register: Registers, value: u16) {
+        self.registers[register as usize] = value;
+    }
+
+    /// A snapshot of every register
+    pub fn snapshot(&self) -> RegisterSnapshot {
+        RegisterSnapshot::from_raw(self.registers)
+    }
+
+    /// The power state, as changed by the GO2SHIP and GO2STBY commands
+    pub fn power_mode(&self) -> PowerMode {
+        self.power_mode
+    }
+
+    /// Wake the device from standby or ship mode, as the WAKEUP pin would
+    pub fn wake(&mut self) {
+        self.power_mode = PowerMode::Active;
+    }
+
+    /// Whether the fuse has been armed and then fired
+    pub fn fuse_blown(&self) -> bool {
+        self.fuse_blown
+    }
+
+    /// Set the measured cell voltages, along with their sum and the pack voltage
+    pub fn set_cell_voltages_mv(&mut self, voltages_mv: &[u16]) {
+        for (cell, voltage_mv) in voltages_mv.iter().enumerate().take(5) {
+            self.set_cell_voltage_mv(cell as u8 + 1, *voltage_mv);
+        }
+        let sum_mv = voltages_mv.iter().take(5).map(|mv| *mv as u32).sum::<u32>() as u16;
+        self.set_measurement(
+            Registers::VCellSum,
+            vcellsum::VCELLSUM_MEAS,
+            cell_voltage_measurement_code_from_mv(sum_mv),
+        );
+        self.set_vbat_mv(sum_mv);
+    }
+
+    /// Set the measured voltage of a single cell (1 indexed)
+    pub fn set_cell_voltage_mv(&mut self, cell: u8, voltage_mv: u16) {
+        let (register, field) = match cell {
+            1 => (Registers::VCell1, vcell1::VCELL1_MEAS),
+            2 => (Registers::VCell2, vcell2::VCELL2_MEAS),
+            3 => (Registers::VCell3, vcell3::VCELL3_MEAS),
+            4 => (Registers::VCell4, vcell4::VCELL4_MEAS),
+            5 => (Registers::VCell5, vcell5::VCELL5_MEAS),
+            _ => panic!("Invalid cell number"),
+        };
+        self.set_measurement(
+            register,
+            field,
+            cell_voltage_measurement_code_from_mv(voltage_mv),
+        );
+    }
+
+    /// Set the measured pack voltage
+    pub fn set_vbat_mv(&mut self, voltage_mv: u16) {
+        self.set_measurement(
+            Registers::VB,
+            vb::VB_MEAS,
+            pack_voltage_measurement_code_from_mv(voltage_mv),
+        );
+    }
+
+    /// Set the measured NTC voltage
+    pub fn set_ntc_mv(&mut self, voltage_mv: u16) {
+        self.set_measurement(
+            Registers::NtcGpio,
+            ntc_gpio::NTC_MEAS,
+            ntc_voltage_code_from_mv(voltage_mv),
+        );
+    }
+
+    /// Set the measured die temperature
+    pub fn set_die_temp_celsius(&mut self, temperature: u16) {
+        let code = (343_165u32.saturating_sub(temperature as u32 * 1000)) / 196;
+        self.set_measurement(Registers::DieTemp, die_temp::DIE_TEMP_MEAS, code as u16);
+    }
+
+    /// Set the instantaneous current code of the coulomb counter
+    pub fn set_current_code(&mut self, code: i16) {
+        self.set_measurement(
+            Registers::CCInstMeas,
+            cc_inst_meas::CC_CUR_INST_MEAS,
+            code as u16,
+        );
+    }
+
+    fn set_measurement(&mut self, register: Registers, field: Field, code: u16) {
+        let raw = &mut self.registers[register as usize];
+        *raw = field.set(*raw, code);
+    }
+
+    fn read_word(&mut self) -> u16 {
+        let value = match REGISTERS[self.pointer].access {
+            Access::WriteOnly => 0,
+            _ => self.registers[self.pointer],
+        };
+        self.pointer = (self.pointer + 1) % REGISTER_COUNT;
+        value
+    }
+
+    fn write_word(&mut self, value: u16) {
+        let info = &REGISTERS[self.pointer];
+        let merged =
+            (self.registers[self.pointer] & !info.writable_mask) | (value & info.writable_mask);
+        match info.address {
+            Registers::Nvm2 => match value {
+                NVM_UPLOAD => {
+                    self.nvm[NVM_REGISTERS].copy_from_slice(&self.registers[NVM_REGISTERS]);
+                    let uploads = &mut self.registers[Registers::Nvm1 as usize];
+                    let count = nvm_1::NVM_UPLOADS_COUNT.get(*uploads) + 1;
+                    *uploads = nvm_1::NVM_UPLOADS_COUNT.set(*uploads, count);
+                }
+                NVM_DOWNLOAD => {
+                    self.registers[NVM_REGISTERS].copy_from_slice(&self.nvm[NVM_REGISTERS]);
+                }
+                _ => (),
+            },
+            Registers::VCell1 if vcell1::GO2SHIP.get(value) == COMMAND => {
+                self.power_mode = PowerMode::Ship;
+            }
+            Registers::VCell2 if vcell2::GO2STBY.get(value) == COMMAND => {
+                self.power_mode = PowerMode::Standby;
+            }
+            Registers::VCell3 if vcell3::FUSE_TRIG_ARM.get(value) == COMMAND => {
+                self.fuse_armed = true;
+            }
+            Registers::VCell4 if vcell4::FUSE_TRIG_FIRE.get(value) == COMMAND => {
+                self.fuse_blown |= self.fuse_armed;
+            }
+            Registers::VCell1 | Registers::VCell2 | Registers::VCell3 | Registers::VCell4 => (),
+            Registers::DevAddr => {
+                self.registers[self.pointer] = merged;
+                self.address = dev_addr::DEV_ADDR_ID.get(merged) as u8;
+            }
+            _ => match info.access {
+                Access::ReadOnly | Access::WriteOnly => (),
+                Access::ReadWrite => self.registers[self.pointer] = merged,
+                Access::ReadLatchedWrite => {
+                    self.registers[self.pointer] &= !(value & info.writable_mask)
+                }
+            },
+        }
+        self.pointer = (self.pointer + 1) % REGISTER_COUNT;
+    }
+}
+
+impl ErrorType for Simulator {
+    type Error = ErrorKind;
+}
+
+impl embedded_hal::i2c::I2c for Simulator {
+    fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        if address != self.address {
+            return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address));
+        }
+        for operation in operations {
+            match operation {
+                Operation::Write(bytes) => {
+                    let Some((register, data)) = bytes.split_first() else {
+                        continue;
+                    };
+                    if *register as usize >= REGISTER_COUNT {
+                        return Err(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Data));
+                    }
+                    self.pointer = *register as usize;
+                    for word in data.chunks_exact(2) {
+                        self.write_word(u16::from_be_bytes([word[0], word[1]]));
+                    }
+                }
+                Operation::Read(buffer) => {
+                    for word in buffer.chunks_mut(2) {
+                        let value = self.read_word().to_be_bytes();
+                        word.copy_from_slice(&value[..word.len()]);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl embedded_hal_async::i2c::I2c for Simulator {
+    async fn transaction(
+        &mut self,
+        address: u8,
+        operations: &mut [Operation<'_>],
+    ) -> Result<(), Self::Error> {
+        embedded_hal::i2c::I2c::transaction(self, address, operations)
+    }
+}
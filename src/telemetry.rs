@@ -0,0 +1,311 @@
+//! # Telemetry
+//!
+//! A compact, versioned binary frame for streaming [`Measurement`]s over bandwidth limited links.
+//! Frames are encoded without allocation on the device, and decoded by host tooling with the `std` feature.
+//!
+//! The frame layout is independent of the cell count, NTC and coulomb counting features,
+//! so a host built with any feature set can decode frames from any device.
+//! All multi-byte values are little endian.
+//!
+//! | Offset | Size | Content                                                               |
+//! |--------|------|-----------------------------------------------------------------------|
+//! | 0      | 2    | Sync bytes `0x99 0x61`                                                |
+//! | 2      | 1    | Format version ([`FRAME_VERSION`])                                    |
+//! | 3      | 1    | Payload length ([`PAYLOAD_LEN`])                                      |
+//! | 4      | 2    | Sequence number                                                       |
+//! | 6      | 1    | Content flags: number of cells (bits 0-2), NTC (bit 3), coulomb counter (bit 4) |
+//! | 7      | 10   | Cell 1-5 voltages in mV                                               |
+//! | 17     | 5    | Cell 1-5 [`CellFaults`]                                               |
+//! | 22     | 2    | Sum of cell voltages in mV                                            |
+//! | 24     | 2    | Pack voltage in mV                                                    |
+//! | 26     | 2    | NTC voltage in mV                                                     |
+//! | 28     | 2    | Die temperature in degrees Celsius                                    |
+//! | 30     | 2    | Instantaneous current code (signed)                                   |
+//! | 32     | 4    | Accumulated current code (signed)                                     |
+//! | 36     | 1    | Number of accumulated current samples                                 |
+//! | 37     | 2    | [`PackFaults`]                                                        |
+//! | 39     | 2    | CRC-16/CCITT-FALSE of bytes 2 to 38                                   |
+//!
+//! Values the device was not built to measure are encoded as 0, and flagged as absent in the content flags.
+
+#[cfg(feature = "std")]
+mod decoder;
+
+#[cfg(feature = "std")]
+pub use decoder::{DecodeError, FrameDecoder};
+
+use crate::{
+    faults::{CellFaults, PackFaults},
+    measurement::Measurement,
+};
+
+/// Sync bytes starting every frame
+pub const FRAME_SYNC: [u8; 2] = [0x99, 0x61];
+/// Version of the frame layout produced by this crate
+pub const FRAME_VERSION: u8 = 1;
+/// Length of the frame payload, between the header and the CRC
+pub const PAYLOAD_LEN: usize = 35;
+/// Length of an encoded frame
+pub const FRAME_LEN: usize = HEADER_LEN + PAYLOAD_LEN + 2;
+
+const HEADER_LEN: usize = 4;
+const NTC_PRESENT: u8 = 0x08;
+const CC_PRESENT: u8 = 0x10;
+const CELL_COUNT_MASK: u8 = 0x07;
+
+/// Coulomb counter values carried by a frame
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct CoulombCount {
+    /// Instantaneous current code
+    pub inst_meas: i16,
+    /// Accumulated current code
+    pub acc: i32,
+    /// Number of accumulated samples
+    pub samples: u8,
+}
+
+/// The contents of a telemetry frame
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct TelemetryFrame {
+    /// Sequence number, incremented by the sender for every frame to detect loss
+    pub sequence: u16,
+    /// Number of cells measured by the device
+    pub cell_count: u8,
+    /// Cell voltages in mV, only the first `cell_count` are meaningful
+    pub cell_voltages_mv: [u16; 5],
+    /// Cell faults, only the first `cell_count` are meaningful
+    pub cell_faults: [CellFaults; 5],
+    /// Sum of cell voltages in mV
+    pub cell_sum_mv: u16,
+    /// Pack voltage in mV
+    pub vbat_mv: u16,
+    /// NTC voltage in mV, if the device measures it
+    pub ntc_mv: Option<u16>,
+    /// Die temperature in degrees Celsius
+    pub die_temp_c: u16,
+    /// Coulomb counter values, if the device measures them
+    pub coulomb_count: Option<CoulombCount>,
+    /// Pack faults
+    pub pack_faults: PackFaults,
+}
+
+impl TelemetryFrame {
+    /// Create a frame from a measurement
+    pub fn from_measurement(measurement: &Measurement, sequence: u16) -> Self {
+        let mut cell_voltages_mv = [0; 5];
+        let mut cell_faults = [CellFaults::empty(); 5];
+        let mut cell_count = 0;
+        for cell in 1..=5 {
+            if let Some(cell_measurement) = measurement.cell(cell) {
+                cell_voltages_mv[cell_count] = cell_measurement.voltage_mv;
+                cell_faults[cell_count] = cell_measurement.faults;
+                cell_count += 1;
+            }
+        }
+        Self {
+            sequence,
+            cell_count: cell_count as u8,
+            cell_voltages_mv,
+            cell_faults,
+            cell_sum_mv: measurement.cell_sum_mv,
+            vbat_mv: measurement.vbat_mv,
+            #[cfg(feature = "ntc")]
+            ntc_mv: Some(measurement.ntc_mv),
+            #[cfg(not(feature = "ntc"))]
+            ntc_mv: None,
+            die_temp_c: measurement.die_temp,
+            #[cfg(feature = "coulomb_counting")]
+            coulomb_count: Some(CoulombCount {
+                inst_meas: measurement.cc_inst_meas,
+                acc: measurement.cc_acc,
+                samples: measurement.cc_samples,
+            }),
+            #[cfg(not(feature = "coulomb_counting"))]
+            coulomb_count: None,
+            pack_faults: measurement.pack_faults,
+        }
+    }
+
+    /// Encode the frame
+    pub fn encode(&self) -> [u8; FRAME_LEN] {
+        let mut frame = [0; FRAME_LEN];
+        frame[0..2].copy_from_slice(&FRAME_SYNC);
+        frame[2] = FRAME_VERSION;
+        frame[3] = PAYLOAD_LEN as u8;
+
+        let mut flags = self.cell_count & CELL_COUNT_MASK;
+        if self.ntc_mv.is_some() {
+            flags |= NTC_PRESENT;
+        }
+        if self.coulomb_count.is_some() {
+            flags |= CC_PRESENT;
+        }
+        let coulomb_count = self.coulomb_count.unwrap_or_default();
+
+        let mut writer = Writer {
+            buffer: &mut frame,
+            position: HEADER_LEN,
+        };
+        writer.put(&self.sequence.to_le_bytes());
+        writer.put(&[flags]);
+        for voltage in self.cell_voltages_mv {
+            writer.put(&voltage.to_le_bytes());
+        }
+        for faults in self.cell_faults {
+            writer.put(&[faults.bits()]);
+        }
+        writer.put(&self.cell_sum_mv.to_le_bytes());
+        writer.put(&self.vbat_mv.to_le_bytes());
+        writer.put(&self.ntc_mv.unwrap_or(0).to_le_bytes());
+        writer.put(&self.die_temp_c.to_le_bytes());
+        writer.put(&coulomb_count.inst_meas.to_le_bytes());
+        writer.put(&coulomb_count.acc.to_le_bytes());
+        writer.put(&[coulomb_count.samples]);
+        writer.put(&self.pack_faults.bits().to_le_bytes());
+
+        let crc = crc16(&frame[2..FRAME_LEN - 2]);
+        frame[FRAME_LEN - 2..].copy_from_slice(&crc.to_le_bytes());
+        frame
+    }
+}
+
+/// Encode a measurement into a telemetry frame
+pub fn encode_measurement(measurement: &Measurement, sequence: u16) -> [u8; FRAME_LEN] {
+    TelemetryFrame::from_measurement(measurement, sequence).encode()
+}
+
+struct Writer<'a> {
+    buffer: &'a mut [u8],
+    position: usize,
+}
+
+impl Writer<'_> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.buffer[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+}
+
+/// CRC-16/CCITT-FALSE: polynomial 0x1021, initial value 0xFFFF
+pub const fn crc16(data: &[u8]) -> u16 {
+    let mut crc: u16 = 0xFFFF;
+    let mut i = 0;
+    while i < data.len() {
+        crc ^= (data[i] as u16) << 8;
+        let mut bit = 0;
+        while bit < 8 {
+            crc = match crc & 0x8000 {
+                0 => crc << 1,
+                _ => (crc << 1) ^ 0x1021,
+            };
+            bit += 1;
+        }
+        i += 1;
+    }
+    crc
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frame(sequence: u16) -> TelemetryFrame {
+        TelemetryFrame {
+            sequence,
+            cell_count: 4,
+            cell_voltages_mv: [3301, 3302, 3303, 3304, 0],
+            cell_faults: [
+                CellFaults::empty(),
+                CellFaults::OVER_VOLTAGE,
+                CellFaults::empty(),
+                CellFaults::UNDER_VOLTAGE,
+                CellFaults::empty(),
+            ],
+            cell_sum_mv: 13210,
+            vbat_mv: 13205,
+            ntc_mv: Some(1250),
+            die_temp_c: 31,
+            coulomb_count: Some(CoulombCount {
+                inst_meas: -120,
+                acc: -30_000,
+                samples: 250,
+            }),
+            pack_faults: PackFaults::OVER_CURRENT_DISCHARGE,
+        }
+    }
+
+    #[test]
+    fn crc16_check_value() {
+        assert_eq!(crc16(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn encoded_frame_layout() {
+        let encoded = frame(0x1234).encode();
+        assert_eq!(encoded[0..2], FRAME_SYNC);
+        assert_eq!(encoded[2], FRAME_VERSION);
+        assert_eq!(encoded[3] as usize, PAYLOAD_LEN);
+        assert_eq!(encoded[4..6], [0x34, 0x12]);
+        assert_eq!(encoded[6], 4 | NTC_PRESENT | CC_PRESENT);
+        let crc = crc16(&encoded[2..FRAME_LEN - 2]);
+        assert_eq!(encoded[FRAME_LEN - 2..], crc.to_le_bytes());
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn round_trip() {
+        let sent = frame(7);
+        assert_eq!(TelemetryFrame::decode(&sent.encode()), Ok(sent));
+
+        let without_optional_values = TelemetryFrame {
+            ntc_mv: None,
+            coulomb_count: None,
+            ..frame(8)
+        };
+        assert_eq!(
+            TelemetryFrame::decode(&without_optional_values.encode()),
+            Ok(without_optional_values)
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn corrupted_frame_is_rejected() {
+        let mut encoded = frame(9).encode();
+        encoded[10] ^= 0x01;
+        assert!(matches!(
+            TelemetryFrame::decode(&encoded),
+            Err(DecodeError::CrcMismatch { .. })
+        ));
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decoder_resynchronizes_after_junk_and_truncated_frames() {
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&[0x00, 0x99, 0x12, 0x61]);
+        decoder.push(&frame(1).encode()[..20]);
+        decoder.push(&frame(2).encode());
+
+        // The truncated frame runs into the next one, so it fails its CRC
+        assert!(matches!(
+            decoder.next_frame(),
+            Some(Err(DecodeError::CrcMismatch { .. }))
+        ));
+        assert_eq!(decoder.next_frame(), Some(Ok(frame(2))));
+        assert_eq!(decoder.next_frame(), None);
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn decoder_waits_for_a_complete_frame() {
+        let encoded = frame(3).encode();
+        let mut decoder = FrameDecoder::new();
+        decoder.push(&encoded[..FRAME_LEN / 2]);
+        assert_eq!(decoder.next_frame(), None);
+        decoder.push(&encoded[FRAME_LEN / 2..]);
+        assert_eq!(decoder.collect::<std::vec::Vec<_>>(), [Ok(frame(3))]);
+    }
+}
@@ -0,0 +1,180 @@
+use std::{fmt, vec::Vec};
+
+use crate::faults::{CellFaults, PackFaults};
+
+use super::{
+    CC_PRESENT, CELL_COUNT_MASK, CoulombCount, FRAME_LEN, FRAME_SYNC, FRAME_VERSION, HEADER_LEN,
+    NTC_PRESENT, PAYLOAD_LEN, TelemetryFrame, crc16,
+};
+
+/// Reasons a telemetry frame could not be decoded
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum DecodeError {
+    /// Fewer bytes than a complete frame
+    Truncated,
+    /// The frame does not start with the sync bytes
+    BadSync,
+    /// The frame was encoded with a layout version this decoder does not know
+    UnsupportedVersion(u8),
+    /// The payload length does not match the frame version
+    BadLength(u8),
+    /// The CRC of the frame does not match its contents
+    CrcMismatch {
+        /// CRC carried by the frame
+        received: u16,
+        /// CRC calculated over the frame contents
+        calculated: u16,
+    },
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::Truncated => write!(f, "truncated telemetry frame"),
+            DecodeError::BadSync => write!(f, "missing telemetry frame sync bytes"),
+            DecodeError::UnsupportedVersion(version) => {
+                write!(f, "unsupported telemetry frame version {version}")
+            }
+            DecodeError::BadLength(length) => {
+                write!(f, "unexpected telemetry payload length {length}")
+            }
+            DecodeError::CrcMismatch {
+                received,
+                calculated,
+            } => write!(
+                f,
+                "telemetry frame CRC mismatch: received {received:#06X}, calculated {calculated:#06X}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl TelemetryFrame {
+    /// Decode a frame from the start of `bytes`
+    pub fn decode(bytes: &[u8]) -> Result<Self, DecodeError> {
+        if bytes.len() < HEADER_LEN {
+            return Err(DecodeError::Truncated);
+        }
+        if bytes[0..2] != FRAME_SYNC {
+            return Err(DecodeError::BadSync);
+        }
+        if bytes[2] != FRAME_VERSION {
+            return Err(DecodeError::UnsupportedVersion(bytes[2]));
+        }
+        if bytes[3] as usize != PAYLOAD_LEN {
+            return Err(DecodeError::BadLength(bytes[3]));
+        }
+        if bytes.len() < FRAME_LEN {
+            return Err(DecodeError::Truncated);
+        }
+        let frame = &bytes[..FRAME_LEN];
+        let received = u16::from_le_bytes([frame[FRAME_LEN - 2], frame[FRAME_LEN - 1]]);
+        let calculated = crc16(&frame[2..FRAME_LEN - 2]);
+        if received != calculated {
+            return Err(DecodeError::CrcMismatch {
+                received,
+                calculated,
+            });
+        }
+
+        let mut reader = Reader {
+            bytes: frame,
+            position: HEADER_LEN,
+        };
+        let sequence = u16::from_le_bytes(reader.take());
+        let [flags] = reader.take();
+        let cell_voltages_mv = [(); 5].map(|_| u16::from_le_bytes(reader.take()));
+        let cell_faults = [(); 5].map(|_| CellFaults::from_bits_truncate(reader.take::<1>()[0]));
+        let cell_sum_mv = u16::from_le_bytes(reader.take());
+        let vbat_mv = u16::from_le_bytes(reader.take());
+        let ntc_mv = u16::from_le_bytes(reader.take());
+        let die_temp_c = u16::from_le_bytes(reader.take());
+        let coulomb_count = CoulombCount {
+            inst_meas: i16::from_le_bytes(reader.take()),
+            acc: i32::from_le_bytes(reader.take()),
+            samples: reader.take::<1>()[0],
+        };
+        let pack_faults = PackFaults::from_bits_truncate(u16::from_le_bytes(reader.take()));
+
+        Ok(Self {
+            sequence,
+            cell_count: flags & CELL_COUNT_MASK,
+            cell_voltages_mv,
+            cell_faults,
+            cell_sum_mv,
+            vbat_mv,
+            ntc_mv: (flags & NTC_PRESENT != 0).then_some(ntc_mv),
+            die_temp_c,
+            coulomb_count: (flags & CC_PRESENT != 0).then_some(coulomb_count),
+            pack_faults,
+        })
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl Reader<'_> {
+    fn take<const N: usize>(&mut self) -> [u8; N] {
+        let bytes = self.bytes[self.position..self.position + N]
+            .try_into()
+            .unwrap();
+        self.position += N;
+        bytes
+    }
+}
+
+/// Splits a byte stream into telemetry frames, resynchronizing after corrupted or partial frames
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    /// Create a new decoder with an empty buffer
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append received bytes to the buffer
+    pub fn push(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decode the next frame in the buffer.
+    /// Returns `None` once the buffer does not hold a complete frame.
+    /// When a frame fails to decode, its first byte is discarded so decoding resumes at the next sync bytes.
+    pub fn next_frame(&mut self) -> Option<Result<TelemetryFrame, DecodeError>> {
+        // Discard anything before the next sync bytes
+        let start = self
+            .buffer
+            .windows(FRAME_SYNC.len())
+            .position(|window| window == FRAME_SYNC)
+            .unwrap_or(self.buffer.len().saturating_sub(1));
+        self.buffer.drain(..start);
+
+        match TelemetryFrame::decode(&self.buffer) {
+            Ok(frame) => {
+                self.buffer.drain(..FRAME_LEN);
+                Some(Ok(frame))
+            }
+            Err(DecodeError::Truncated) => None,
+            Err(error) => {
+                self.buffer.drain(..1);
+                Some(Err(error))
+            }
+        }
+    }
+}
+
+impl Iterator for FrameDecoder {
+    type Item = Result<TelemetryFrame, DecodeError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame()
+    }
+}
@@ -0,0 +1,552 @@
+//! # Warnings
+//! Software warning levels ahead of the hardware thresholds, so the application can derate or notify the user
+//! before the L9961 flags a fault and its protection reacts.
+//!
+//! [`WarningTiers`] places a warning and an alarm level at a margin inside each configured threshold of the
+//! [`crate::config::VoltageThresholds`] and [`crate::config::NtcThresholds`].
+//! A [`WarningMonitor`] resolves the margins against a configuration, and is updated with every measurement,
+//! returning a [`WarningEvent`] whenever the level of a threshold changes.
+//! A level is entered as soon as a measurement crosses it, and is only left once the measurement has moved
+//! back past it by the hysteresis of the tier, so a value close to a level does not toggle it at every measurement.
+//!
+//! ```no_run
+//! # use embedded_hal_async::{delay::DelayNs, digital::Wait, i2c::I2c};
+//! # use embedded_hal::digital::OutputPin;
+//! use l9961::{
+//!     L9961,
+//!     warnings::{TierMargins, WarningLevel, WarningTiers},
+//! };
+//!
+//! # async fn run<I2C: I2c, READY: Wait, FAULTN: Wait, WAKEUP: OutputPin>(
+//! #     mut l9961: L9961<I2C, READY, FAULTN, WAKEUP>,
+//! #     mut delay: impl DelayNs,
+//! # ) -> Result<(), I2C::Error> {
+//! let cell = TierMargins::new(100, 50, 20);
+//! let tiers = WarningTiers {
+//!     cell_over_voltage: Some(cell),
+//!     cell_under_voltage: Some(cell),
+//!     ..WarningTiers::new()
+//! };
+//! let mut warnings = l9961.warning_monitor(tiers);
+//! loop {
+//!     let Some(measurement) = l9961.make_measurement(&mut delay).await? else {
+//!         continue;
+//!     };
+//!     for event in warnings.update(&measurement).iter() {
+//!         if event.level == WarningLevel::Alarm {
+//!             // Reduce the charge or discharge current
+//!         }
+//!     }
+//! }
+//! # }
+//! ```
+
+use embedded_hal::digital::OutputPin;
+use embedded_hal_async::{digital::Wait, i2c::I2c};
+
+use crate::{Config, L9961, measurement::Measurement};
+
+/// Level of a threshold, in increasing severity
+#[derive(Clone, Copy, Debug, Default, Eq, Ord, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum WarningLevel {
+    /// Clear of the warning level
+    #[default]
+    Normal,
+    /// Within the warning margin of the threshold
+    Warning,
+    /// Within the alarm margin of the threshold, or beyond it
+    Alarm,
+}
+
+impl core::fmt::Display for WarningLevel {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::Normal => "Normal",
+            Self::Warning => "Warning",
+            Self::Alarm => "Alarm",
+        })
+    }
+}
+
+/// A hardware threshold which warning levels are placed ahead of
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Threshold {
+    /// Cell over-voltage, approached by the highest cell voltage
+    CellOverVoltage,
+    /// Cell under-voltage, approached by the lowest cell voltage
+    CellUnderVoltage,
+    /// Pack over-voltage, approached by the VB measurement
+    PackOverVoltage,
+    /// Pack under-voltage, approached by the VB measurement
+    PackUnderVoltage,
+    /// NTC over-temperature, approached as the NTC voltage falls
+    NtcOverTemperature,
+    /// NTC under-temperature, approached as the NTC voltage rises
+    NtcUnderTemperature,
+}
+
+impl Threshold {
+    /// Number of thresholds
+    pub const COUNT: usize = 6;
+
+    /// Every threshold, in the order of [`Threshold::index`]
+    pub const ALL: [Threshold; Self::COUNT] = [
+        Self::CellOverVoltage,
+        Self::CellUnderVoltage,
+        Self::PackOverVoltage,
+        Self::PackUnderVoltage,
+        Self::NtcOverTemperature,
+        Self::NtcUnderTemperature,
+    ];
+
+    /// Index of the threshold within [`Threshold::ALL`]
+    pub const fn index(self) -> usize {
+        self as usize
+    }
+
+    /// Whether the threshold is approached by a rising voltage
+    const fn is_rising(self) -> bool {
+        matches!(
+            self,
+            Self::CellOverVoltage | Self::PackOverVoltage | Self::NtcUnderTemperature
+        )
+    }
+}
+
+impl core::fmt::Display for Threshold {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.pad(match self {
+            Self::CellOverVoltage => "Cell over-voltage",
+            Self::CellUnderVoltage => "Cell under-voltage",
+            Self::PackOverVoltage => "Pack over-voltage",
+            Self::PackUnderVoltage => "Pack under-voltage",
+            Self::NtcOverTemperature => "NTC over-temperature",
+            Self::NtcUnderTemperature => "NTC under-temperature",
+        })
+    }
+}
+
+/// Distances in mV inside a threshold at which its warning levels are entered
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TierMargins {
+    /// Distance from the threshold at which the warning level is entered
+    pub warning_mv: u16,
+    /// Distance from the threshold at which the alarm level is entered, smaller than the warning distance
+    pub alarm_mv: u16,
+    /// Distance a measurement must move back past a level before the level is left
+    pub hysteresis_mv: u16,
+}
+
+impl TierMargins {
+    /// Create the margins of a tier
+    pub const fn new(warning_mv: u16, alarm_mv: u16, hysteresis_mv: u16) -> Self {
+        Self {
+            warning_mv,
+            alarm_mv,
+            hysteresis_mv,
+        }
+    }
+
+    /// The level of a measurement `distance_mv` inside the threshold, negative once it is beyond it
+    const fn level(&self, distance_mv: i32) -> WarningLevel {
+        if distance_mv <= self.alarm_mv as i32 {
+            WarningLevel::Alarm
+        } else if distance_mv <= self.warning_mv as i32 {
+            WarningLevel::Warning
+        } else {
+            WarningLevel::Normal
+        }
+    }
+}
+
+/// Warning margins for each threshold. `None` disables the warnings of a threshold.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarningTiers {
+    /// Margins inside the cell over-voltage threshold
+    pub cell_over_voltage: Option<TierMargins>,
+    /// Margins inside the cell under-voltage threshold
+    pub cell_under_voltage: Option<TierMargins>,
+    /// Margins inside the pack over-voltage threshold
+    pub pack_over_voltage: Option<TierMargins>,
+    /// Margins inside the pack under-voltage threshold
+    pub pack_under_voltage: Option<TierMargins>,
+    /// Margins inside the NTC over-temperature threshold, in mV of NTC voltage
+    #[cfg(feature = "ntc")]
+    pub ntc_over_temperature: Option<TierMargins>,
+    /// Margins inside the NTC under-temperature threshold, in mV of NTC voltage
+    #[cfg(feature = "ntc")]
+    pub ntc_under_temperature: Option<TierMargins>,
+}
+
+impl WarningTiers {
+    /// Tiers with every warning disabled
+    pub const fn new() -> Self {
+        Self {
+            cell_over_voltage: None,
+            cell_under_voltage: None,
+            pack_over_voltage: None,
+            pack_under_voltage: None,
+            #[cfg(feature = "ntc")]
+            ntc_over_temperature: None,
+            #[cfg(feature = "ntc")]
+            ntc_under_temperature: None,
+        }
+    }
+}
+
+/// A change of the warning level of a threshold
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WarningEvent {
+    /// The threshold whose level changed
+    pub threshold: Threshold,
+    /// The new level
+    pub level: WarningLevel,
+    /// The level before this measurement
+    pub previous: WarningLevel,
+    /// The cell closest to the threshold, for cell thresholds
+    pub cell: Option<u8>,
+    /// The measured voltage in mV closest to the threshold
+    pub value_mv: u16,
+}
+
+impl WarningEvent {
+    /// Whether the level rose
+    pub fn is_escalation(&self) -> bool {
+        self.level > self.previous
+    }
+}
+
+/// The warning events produced by one measurement, at most one per threshold
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WarningEvents {
+    events: [Option<WarningEvent>; Threshold::COUNT],
+}
+
+impl WarningEvents {
+    /// Iterate over the events, in the order of [`Threshold::ALL`]
+    pub fn iter(&self) -> impl Iterator<Item = &WarningEvent> {
+        self.events.iter().flatten()
+    }
+
+    /// Whether no level changed
+    pub fn is_empty(&self) -> bool {
+        self.events.iter().all(Option::is_none)
+    }
+}
+
+/// A threshold with warnings enabled, resolved against the configuration
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+struct Tier {
+    threshold_mv: u16,
+    margins: TierMargins,
+}
+
+/// Tracks the warning level of every threshold across measurements, see the [module documentation](self)
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct WarningMonitor {
+    tiers: [Option<Tier>; Threshold::COUNT],
+    levels: [WarningLevel; Threshold::COUNT],
+    cells: u8,
+}
+
+impl WarningMonitor {
+    /// Place the tiers inside the thresholds and enabled cells of the configuration, with every level normal
+    pub fn new(tiers: WarningTiers, config: &Config) -> Self {
+        let voltage = &config.voltage_thresholds;
+        let tier = |margins: Option<TierMargins>, threshold_mv| {
+            margins.map(|margins| Tier {
+                threshold_mv,
+                margins,
+            })
+        };
+        Self {
+            tiers: [
+                tier(
+                    tiers.cell_over_voltage,
+                    voltage.cell_over_voltage_threshold_mv,
+                ),
+                tier(
+                    tiers.cell_under_voltage,
+                    voltage.cell_under_voltage_threshold_mv,
+                ),
+                tier(
+                    tiers.pack_over_voltage,
+                    voltage.pack_over_voltage_threshold_mv,
+                ),
+                tier(
+                    tiers.pack_under_voltage,
+                    voltage.pack_under_voltage_threshold_mv,
+                ),
+                #[cfg(feature = "ntc")]
+                tier(
+                    tiers.ntc_over_temperature,
+                    config.ntc_thresholds.over_temp_threshold_mv,
+                ),
+                #[cfg(feature = "ntc")]
+                tier(
+                    tiers.ntc_under_temperature,
+                    config.ntc_thresholds.under_temp_threshold_mv,
+                ),
+                #[cfg(not(feature = "ntc"))]
+                None,
+                #[cfg(not(feature = "ntc"))]
+                None,
+            ],
+            levels: [WarningLevel::Normal; Threshold::COUNT],
+            cells: config.enables.cells,
+        }
+    }
+
+    /// The current level of a threshold
+    pub const fn level(&self, threshold: Threshold) -> WarningLevel {
+        self.levels[threshold.index()]
+    }
+
+    /// The highest level of any threshold
+    pub fn highest_level(&self) -> WarningLevel {
+        self.levels
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(WarningLevel::Normal)
+    }
+
+    /// Update the levels with a measurement, returning an event for every threshold whose level changed
+    pub fn update(&mut self, measurement: &Measurement) -> WarningEvents {
+        let mut events = WarningEvents::default();
+        for threshold in Threshold::ALL {
+            let index = threshold.index();
+            let Some(tier) = self.tiers[index] else {
+                continue;
+            };
+            let Some((value_mv, cell)) = self.value(threshold, measurement) else {
+                continue;
+            };
+            let distance_mv = if threshold.is_rising() {
+                tier.threshold_mv as i32 - value_mv as i32
+            } else {
+                value_mv as i32 - tier.threshold_mv as i32
+            };
+
+            let previous = self.levels[index];
+            let entered = tier.margins.level(distance_mv);
+            let level = if entered >= previous {
+                entered
+            } else {
+                // Only drop as far as the measurement has cleared each level by the hysteresis
+                let held = tier
+                    .margins
+                    .level(distance_mv - tier.margins.hysteresis_mv as i32);
+                held.min(previous)
+            };
+            if level != previous {
+                self.levels[index] = level;
+                events.events[index] = Some(WarningEvent {
+                    threshold,
+                    level,
+                    previous,
+                    cell,
+                    value_mv,
+                });
+            }
+        }
+        events
+    }
+
+    /// Return every threshold to the normal level
+    pub fn reset(&mut self) {
+        self.levels = [WarningLevel::Normal; Threshold::COUNT];
+    }
+
+    /// The measured voltage closest to the threshold, with the cell it was measured on
+    fn value(&self, threshold: Threshold, measurement: &Measurement) -> Option<(u16, Option<u8>)> {
+        let cells = (1..=self.cells)
+            .filter_map(|cell| measurement.cell(cell).map(|m| (m.voltage_mv, Some(cell))));
+        match threshold {
+            Threshold::CellOverVoltage => cells.max_by_key(|(voltage_mv, _)| *voltage_mv),
+            Threshold::CellUnderVoltage => cells.min_by_key(|(voltage_mv, _)| *voltage_mv),
+            Threshold::PackOverVoltage | Threshold::PackUnderVoltage => {
+                Some((measurement.vbat_mv, None))
+            }
+            #[cfg(feature = "ntc")]
+            Threshold::NtcOverTemperature | Threshold::NtcUnderTemperature => {
+                Some((measurement.ntc_mv, None))
+            }
+            #[cfg(not(feature = "ntc"))]
+            Threshold::NtcOverTemperature | Threshold::NtcUnderTemperature => None,
+        }
+    }
+}
+
+impl<I2C, READY, FAULTN, WAKEUP> L9961<I2C, READY, FAULTN, WAKEUP>
+where
+    I2C: I2c,
+    READY: Wait,
+    FAULTN: Wait,
+    WAKEUP: OutputPin,
+{
+    /// Create a [`WarningMonitor`] placing the tiers inside the thresholds of the driver's configuration
+    pub fn warning_monitor(&self, tiers: WarningTiers) -> WarningMonitor {
+        WarningMonitor::new(tiers, &self.config)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MARGINS: TierMargins = TierMargins::new(100, 50, 20);
+
+    fn config() -> Config {
+        let mut config = Config::default();
+        let thresholds = &mut config.voltage_thresholds;
+        thresholds.cell_over_voltage_threshold_mv = 4200;
+        thresholds.cell_under_voltage_threshold_mv = 3000;
+        thresholds.pack_over_voltage_threshold_mv = 21000;
+        thresholds.pack_under_voltage_threshold_mv = 15000;
+        config
+    }
+
+    /// Every cell at 3.7 V except cell 2
+    fn measurement(cell_2_mv: u16) -> Measurement {
+        let mut measurement = Measurement::default();
+        measurement.cell_1.voltage_mv = 3700;
+        measurement.cell_2.voltage_mv = cell_2_mv;
+        measurement.cell_3.voltage_mv = 3700;
+        #[cfg(feature = "4_cells")]
+        {
+            measurement.cell_4.voltage_mv = 3700;
+        }
+        #[cfg(feature = "5_cells")]
+        {
+            measurement.cell_5.voltage_mv = 3700;
+        }
+        measurement.vbat_mv = 18000;
+        measurement
+    }
+
+    /// Update the monitor with cell 2 at `cell_2_mv`, returning the new level of the threshold if it changed
+    fn update(
+        monitor: &mut WarningMonitor,
+        threshold: Threshold,
+        cell_2_mv: u16,
+    ) -> Option<WarningLevel> {
+        let previous = monitor.level(threshold);
+        let events = monitor.update(&measurement(cell_2_mv));
+        let event = events.iter().find(|event| event.threshold == threshold)?;
+        assert_eq!(event.previous, previous);
+        assert_eq!(event.cell, Some(2));
+        assert_eq!(event.value_mv, cell_2_mv);
+        assert_eq!(monitor.level(threshold), event.level);
+        Some(event.level)
+    }
+
+    #[test]
+    fn rising_threshold_escalates_and_drops_back_past_the_hysteresis() {
+        let tiers = WarningTiers {
+            cell_over_voltage: Some(MARGINS),
+            ..WarningTiers::new()
+        };
+        let mut monitor = WarningMonitor::new(tiers, &config());
+        let threshold = Threshold::CellOverVoltage;
+
+        assert_eq!(update(&mut monitor, threshold, 4099), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 4100),
+            Some(WarningLevel::Warning)
+        );
+        assert_eq!(
+            update(&mut monitor, threshold, 4150),
+            Some(WarningLevel::Alarm)
+        );
+        assert_eq!(update(&mut monitor, threshold, 4250), None);
+
+        // The alarm level is only left 20 mV below where it was entered
+        assert_eq!(update(&mut monitor, threshold, 4130), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 4129),
+            Some(WarningLevel::Warning)
+        );
+        assert_eq!(update(&mut monitor, threshold, 4080), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 4079),
+            Some(WarningLevel::Normal)
+        );
+
+        // A measurement well clear of both levels drops straight back to normal
+        assert_eq!(
+            update(&mut monitor, threshold, 4200),
+            Some(WarningLevel::Alarm)
+        );
+        assert_eq!(
+            update(&mut monitor, threshold, 4000),
+            Some(WarningLevel::Normal)
+        );
+    }
+
+    #[test]
+    fn falling_threshold_escalates_and_drops_back_past_the_hysteresis() {
+        let tiers = WarningTiers {
+            cell_under_voltage: Some(MARGINS),
+            ..WarningTiers::new()
+        };
+        let mut monitor = WarningMonitor::new(tiers, &config());
+        let threshold = Threshold::CellUnderVoltage;
+
+        assert_eq!(update(&mut monitor, threshold, 3101), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 3100),
+            Some(WarningLevel::Warning)
+        );
+        assert_eq!(
+            update(&mut monitor, threshold, 3050),
+            Some(WarningLevel::Alarm)
+        );
+        assert_eq!(update(&mut monitor, threshold, 2900), None);
+
+        assert_eq!(update(&mut monitor, threshold, 3070), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 3071),
+            Some(WarningLevel::Warning)
+        );
+        assert_eq!(update(&mut monitor, threshold, 3120), None);
+        assert_eq!(
+            update(&mut monitor, threshold, 3121),
+            Some(WarningLevel::Normal)
+        );
+    }
+
+    #[test]
+    fn disabled_tiers_stay_normal() {
+        let mut monitor = WarningMonitor::new(WarningTiers::new(), &config());
+        for cell_2_mv in [4300, 2900, 3700] {
+            assert!(monitor.update(&measurement(cell_2_mv)).is_empty());
+        }
+        assert_eq!(monitor.highest_level(), WarningLevel::Normal);
+
+        let tiers = WarningTiers {
+            cell_over_voltage: Some(MARGINS),
+            ..WarningTiers::new()
+        };
+        let mut monitor = WarningMonitor::new(tiers, &config());
+        let mut beyond_every_threshold = measurement(2900);
+        beyond_every_threshold.vbat_mv = 22000;
+        assert!(monitor.update(&beyond_every_threshold).is_empty());
+        for threshold in Threshold::ALL {
+            assert_eq!(monitor.level(threshold), WarningLevel::Normal);
+        }
+    }
+}